@@ -133,6 +133,335 @@ pub fn get_frontmost_app() -> AppInfo {
     AppInfo::default()
 }
 
+// ============================================
+// FRONTMOST WINDOW HANDLE
+// ============================================
+// Remembered when the overlay opens so focus can be handed back on hide
+
+/// Handle to another app's focused window
+///
+/// Windows: the HWND value. macOS: the process id (focus is per app).
+/// Linux/X11: the window id from `_NET_ACTIVE_WINDOW`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrontmostWindow(RawWindowId);
+
+#[cfg(target_os = "windows")]
+type RawWindowId = isize;
+#[cfg(target_os = "macos")]
+type RawWindowId = i32;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+type RawWindowId = u32;
+
+/// Get the foreground window on Windows
+#[cfg(target_os = "windows")]
+pub fn get_frontmost_window() -> Option<FrontmostWindow> {
+    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.0.is_null() {
+        return None;
+    }
+    Some(FrontmostWindow(hwnd.0 as isize))
+}
+
+/// Bring a previously focused window back to the foreground on Windows
+#[cfg(target_os = "windows")]
+pub fn activate_window(window: FrontmostWindow) -> bool {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{IsWindow, SetForegroundWindow};
+
+    unsafe {
+        let hwnd = HWND(window.0 as *mut std::ffi::c_void);
+        // The window may have been closed while the overlay was open
+        if !IsWindow(hwnd).as_bool() {
+            return false;
+        }
+        SetForegroundWindow(hwnd).as_bool()
+    }
+}
+
+/// Get the frontmost application's process on macOS
+#[allow(deprecated)]
+#[cfg(target_os = "macos")]
+pub fn get_frontmost_window() -> Option<FrontmostWindow> {
+    unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        if workspace == nil {
+            return None;
+        }
+
+        let frontmost_app: id = msg_send![workspace, frontmostApplication];
+        if frontmost_app == nil {
+            return None;
+        }
+
+        let pid: i32 = msg_send![frontmost_app, processIdentifier];
+        // Nothing to restore if we are already the active app
+        if pid <= 0 || pid as u32 == std::process::id() {
+            return None;
+        }
+        Some(FrontmostWindow(pid))
+    }
+}
+
+/// Re-activate a previously frontmost application on macOS
+#[allow(deprecated)]
+#[cfg(target_os = "macos")]
+pub fn activate_window(window: FrontmostWindow) -> bool {
+    // NSApplicationActivateIgnoringOtherApps
+    const ACTIVATE_IGNORING_OTHER_APPS: u64 = 1 << 1;
+
+    unsafe {
+        let app: id = msg_send![
+            class!(NSRunningApplication),
+            runningApplicationWithProcessIdentifier: window.0
+        ];
+        if app == nil {
+            return false;
+        }
+        let activated: bool = msg_send![app, activateWithOptions: ACTIVATE_IGNORING_OTHER_APPS];
+        activated
+    }
+}
+
+/// Get the active window on Linux (X11 only; Wayland doesn't expose it)
+#[cfg(target_os = "linux")]
+pub fn get_frontmost_window() -> Option<FrontmostWindow> {
+    use crate::linux_placement::{intern_atom, is_wayland, read_cardinals};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::AtomEnum;
+
+    if is_wayland() {
+        return None;
+    }
+
+    let (conn, screen_num) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen_num)?.root;
+    let atom = intern_atom(&conn, b"_NET_ACTIVE_WINDOW")?;
+    let xid = *read_cardinals(&conn, root, atom, AtomEnum::WINDOW)?.first()?;
+
+    (xid != 0).then_some(FrontmostWindow(xid))
+}
+
+/// Ask the window manager to activate a previously focused X11 window
+#[cfg(target_os = "linux")]
+pub fn activate_window(window: FrontmostWindow) -> bool {
+    use crate::linux_placement::{intern_atom, is_wayland};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ClientMessageEvent, ConnectionExt, EventMask};
+
+    // Source indication 2 ("pager") is exempt from focus-stealing prevention
+    const SOURCE_PAGER: u32 = 2;
+
+    if is_wayland() {
+        return false;
+    }
+
+    let Ok((conn, screen_num)) = x11rb::connect(None) else {
+        return false;
+    };
+    let Some(root) = conn.setup().roots.get(screen_num).map(|s| s.root) else {
+        return false;
+    };
+    let Some(atom) = intern_atom(&conn, b"_NET_ACTIVE_WINDOW") else {
+        return false;
+    };
+
+    let event = ClientMessageEvent::new(
+        32,
+        window.0,
+        atom,
+        [SOURCE_PAGER, x11rb::CURRENT_TIME, 0, 0, 0],
+    );
+    let mask = EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY;
+
+    conn.send_event(false, root, mask, event).is_ok() && conn.flush().is_ok()
+}
+
+/// Fallback for platforms without a way to track the focused window
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn get_frontmost_window() -> Option<FrontmostWindow> {
+    None
+}
+
+/// Fallback for platforms without a way to track the focused window
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn activate_window(_window: FrontmostWindow) -> bool {
+    false
+}
+
+/// Screen bounds of a window in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Get the bounds of the foreground window on Windows
+#[cfg(target_os = "windows")]
+pub fn get_frontmost_window_bounds() -> Option<WindowBounds> {
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowRect;
+
+    let window = get_frontmost_window()?;
+
+    unsafe {
+        let hwnd = HWND(window.0 as *mut std::ffi::c_void);
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).ok()?;
+
+        Some(WindowBounds {
+            x: rect.left,
+            y: rect.top,
+            width: (rect.right - rect.left).max(0) as u32,
+            height: (rect.bottom - rect.top).max(0) as u32,
+        })
+    }
+}
+
+/// Get the bounds of the frontmost app's front window on macOS
+///
+/// CGWindowList reports points with a top-left origin; they are scaled by
+/// the backing scale of the screen the window is on, as Tauri does for
+/// monitor positions.
+#[allow(deprecated)]
+#[cfg(target_os = "macos")]
+pub fn get_frontmost_window_bounds() -> Option<WindowBounds> {
+    use cocoa::foundation::NSAutoreleasePool;
+
+    // kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements
+    const ON_SCREEN_WINDOWS: u32 = (1 << 0) | (1 << 4);
+    // Windows above the normal level are menus, panels and the menu bar
+    const NORMAL_WINDOW_LAYER: f64 = 0.0;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: u32) -> id;
+    }
+
+    let window = get_frontmost_window()?;
+
+    unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+
+        // Front to back, so the first match is the app's front window
+        let windows = CGWindowListCopyWindowInfo(ON_SCREEN_WINDOWS, 0);
+        let frame = if windows == nil {
+            None
+        } else {
+            let count: usize = msg_send![windows, count];
+            let frame = (0..count).find_map(|index| {
+                let info: id = msg_send![windows, objectAtIndex: index];
+                let owner = dictionary_number(info, "kCGWindowOwnerPID")?;
+                let layer = dictionary_number(info, "kCGWindowLayer")?;
+                if owner as i32 != window.0 || layer != NORMAL_WINDOW_LAYER {
+                    return None;
+                }
+                let rect = dictionary_object(info, "kCGWindowBounds")?;
+                Some((
+                    dictionary_number(rect, "X")?,
+                    dictionary_number(rect, "Y")?,
+                    dictionary_number(rect, "Width")?,
+                    dictionary_number(rect, "Height")?,
+                ))
+            });
+            let _: () = msg_send![windows, release];
+            frame
+        };
+        let bounds = frame.map(|(x, y, width, height)| {
+            let scale = screen_scale_at(x + width / 2.0, y + height / 2.0);
+            WindowBounds {
+                x: (x * scale).round() as i32,
+                y: (y * scale).round() as i32,
+                width: (width * scale).round().max(0.0) as u32,
+                height: (height * scale).round().max(0.0) as u32,
+            }
+        });
+
+        pool.drain();
+        bounds
+    }
+}
+
+/// Backing scale factor of the screen containing a point in CoreGraphics
+/// coordinates (top-left origin), 1.0 if none does
+#[allow(deprecated)]
+#[cfg(target_os = "macos")]
+unsafe fn screen_scale_at(x: f64, y: f64) -> f64 {
+    use cocoa::appkit::NSScreen;
+
+    let screens = NSScreen::screens(nil);
+    let count: usize = msg_send![screens, count];
+    if count == 0 {
+        return 1.0;
+    }
+    // AppKit frames have a bottom-left origin on the primary (first) screen
+    let primary: id = msg_send![screens, objectAtIndex: 0usize];
+    let flipped_y = NSScreen::frame(primary).size.height - y;
+
+    (0..count)
+        .map(|index| -> id { msg_send![screens, objectAtIndex: index] })
+        .find(|&screen| {
+            let frame = NSScreen::frame(screen);
+            x >= frame.origin.x
+                && x < frame.origin.x + frame.size.width
+                && flipped_y > frame.origin.y
+                && flipped_y <= frame.origin.y + frame.size.height
+        })
+        .map_or(1.0, |screen| NSScreen::backingScaleFactor(screen))
+}
+
+/// Value for a key of an NSDictionary (or bridged CFDictionary)
+#[allow(deprecated)]
+#[cfg(target_os = "macos")]
+unsafe fn dictionary_object(dictionary: id, key: &str) -> Option<id> {
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+
+    let key = NSString::alloc(nil).init_str(key).autorelease();
+    let value: id = msg_send![dictionary, objectForKey: key];
+    (value != nil).then_some(value)
+}
+
+/// NSNumber value for a key of an NSDictionary
+#[allow(deprecated)]
+#[cfg(target_os = "macos")]
+unsafe fn dictionary_number(dictionary: id, key: &str) -> Option<f64> {
+    let value = dictionary_object(dictionary, key)?;
+    let number: f64 = msg_send![value, doubleValue];
+    Some(number)
+}
+
+/// Get the bounds of the active window on Linux (X11 only)
+#[cfg(target_os = "linux")]
+pub fn get_frontmost_window_bounds() -> Option<WindowBounds> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::ConnectionExt;
+
+    let window = get_frontmost_window()?;
+    let (conn, screen_num) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen_num)?.root;
+
+    // Geometry is relative to the parent (often a WM frame), so translate
+    // the window's origin to root coordinates
+    let geometry = conn.get_geometry(window.0).ok()?.reply().ok()?;
+    let origin = conn.translate_coordinates(window.0, root, 0, 0).ok()?.reply().ok()?;
+
+    Some(WindowBounds {
+        x: i32::from(origin.dst_x),
+        y: i32::from(origin.dst_y),
+        width: u32::from(geometry.width),
+        height: u32::from(geometry.height),
+    })
+}
+
+/// Fallback for platforms where the focused window geometry isn't available
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn get_frontmost_window_bounds() -> Option<WindowBounds> {
+    None
+}
+
 /// Convert NSString to Rust String
 #[allow(deprecated)]
 #[cfg(target_os = "macos")]
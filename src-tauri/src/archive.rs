@@ -0,0 +1,920 @@
+// Archive Module - Portable .copygum archives
+// A .copygum file is a zip containing:
+// - manifest.json: format version, counts, categories and tags
+// - items.jsonl: one item per line, images referenced as images/<file>
+// - images/: the image files and their thumbnails
+// Image paths in the database are absolute, so importing copies the images
+// into this machine's images dir and rewrites the paths. Items already in the
+// history are skipped, categories and tags are merged by name, and the
+// import reports what was added, skipped or conflicted. Archives can be
+// encrypted with a passphrase (see encryption.rs).
+
+use std::fs;
+use std::io::{BufReader, BufWriter, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use zip::write::SimpleFileOptions;
+use zeroize::Zeroize;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::db::items::{self, ItemFilter};
+use crate::encryption::{self, DecryptReader, EncryptWriter};
+use crate::image_handler::thumbnail_path;
+use crate::json_import;
+use crate::paths::{database_path, images_dir, settings_path};
+use crate::settings::AppSettings;
+
+/// Identifies the file as a CopyGum archive
+const ARCHIVE_FORMAT: &str = "copygum-archive";
+/// Newest archive version this build can read
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const ITEMS_FILE: &str = "items.jsonl";
+const IMAGES_DIR: &str = "images";
+/// Larger manifests are cut off (and fail to parse) rather than buffered
+const MAX_MANIFEST_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub item_count: usize,
+    pub image_count: usize,
+    #[serde(default)]
+    pub categories: Vec<ArchiveCategory>,
+    #[serde(default)]
+    pub tags: Vec<ArchiveTag>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveCategory {
+    pub name: String,
+    pub icon: String,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveTag {
+    pub name: String,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+}
+
+/// One line of items.jsonl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveItem {
+    pub content: String,
+    pub content_type: String,
+    pub category: String,
+    pub app_name: Option<String>,
+    pub app_bundle_id: Option<String>,
+    pub is_image: bool,
+    /// Path inside the archive (images/...)
+    pub image: Option<String>,
+    pub thumbnail: Option<String>,
+    pub image_width: Option<i64>,
+    pub image_height: Option<i64>,
+    pub image_size: Option<i64>,
+    pub image_dominant_color: Option<String>,
+    pub timestamp: i64,
+    pub is_pinned: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Text recognized in the image (ocr.rs), kept searchable
+    #[serde(default)]
+    pub search_text: Option<String>,
+    /// QR code or barcode content (barcode.rs)
+    #[serde(default)]
+    pub decoded_text: Option<String>,
+    #[serde(default)]
+    pub decoded_type: Option<String>,
+    /// Perceptual hash, so later captures can stack with it (similar_images.rs)
+    #[serde(default)]
+    pub image_phash: Option<String>,
+    /// Palette JSON (palette.rs)
+    #[serde(default)]
+    pub image_palette: Option<String>,
+    /// File details (image_metadata.rs)
+    #[serde(default)]
+    pub image_format: Option<String>,
+    #[serde(default)]
+    pub image_color_space: Option<String>,
+    #[serde(default)]
+    pub image_dpi: Option<i64>,
+    #[serde(default)]
+    pub image_has_alpha: Option<bool>,
+    #[serde(default)]
+    pub image_animated: Option<bool>,
+}
+
+/// Result of writing an archive
+#[derive(Debug, Serialize)]
+pub struct ArchiveSummary {
+    pub item_count: usize,
+    pub image_count: usize,
+    /// Image items left out because their file no longer exists
+    pub missing_images: usize,
+}
+
+/// What an import did
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub added: usize,
+    pub skipped: usize,
+    pub conflicted: usize,
+    pub categories_added: Vec<String>,
+    pub tags_added: Vec<String>,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+/// Something that already existed with different details; the local copy is kept
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportConflict {
+    pub kind: String,
+    pub name: String,
+    pub reason: String,
+}
+
+impl ImportReport {
+    fn conflict(&mut self, kind: &str, name: &str, reason: impl Into<String>) {
+        self.conflicts.push(ImportConflict {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            reason: reason.into(),
+        });
+    }
+}
+
+// ============================================
+// EXPORT
+// ============================================
+
+/// Write the items matching `filter`, with their images, as a .copygum archive
+pub fn write_archive<W: Write + Seek>(
+    conn: &Connection,
+    out: W,
+    filter: &ItemFilter,
+) -> Result<ArchiveSummary, String> {
+    let mut zip = ZipWriter::new(out);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // PNGs are already compressed
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let (where_sql, values) = filter.where_clause();
+    let sql = format!(
+        "SELECT v.id, v.content, v.content_type, v.category, v.app_name, v.app_bundle_id, v.is_image,
+                v.image_path, v.image_width, v.image_height, v.image_size, v.image_dominant_color,
+                v.timestamp, v.is_pinned, v.tag_names, v.search_text, v.decoded_text, v.decoded_type,
+                v.image_phash, v.image_palette, v.image_format, v.image_color_space, v.image_dpi,
+                v.image_has_alpha, v.image_animated
+         FROM clipboard_items_with_tags v WHERE {} ORDER BY v.timestamp DESC",
+        where_sql
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to query items: {}", e))?;
+    let mut rows = stmt
+        .query(params_from_iter(values))
+        .map_err(|e| format!("Failed to query items: {}", e))?;
+
+    zip.start_file(ITEMS_FILE, deflated)
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+
+    let mut summary = ArchiveSummary { item_count: 0, image_count: 0, missing_images: 0 };
+    let mut images: Vec<(String, PathBuf)> = Vec::new();
+
+    while let Some(row) = rows.next().map_err(|e| format!("Failed to read items: {}", e))? {
+        let read = |e: rusqlite::Error| format!("Failed to read items: {}", e);
+        let id: i64 = row.get(0).map_err(read)?;
+        let is_image: bool = row.get(6).map_err(read)?;
+        let image_path: Option<String> = row.get(7).map_err(read)?;
+        let tag_names: Option<String> = row.get(14).map_err(read)?;
+
+        let mut item = ArchiveItem {
+            content: row.get(1).map_err(read)?,
+            content_type: row.get(2).map_err(read)?,
+            category: row.get(3).map_err(read)?,
+            app_name: row.get(4).map_err(read)?,
+            app_bundle_id: row.get(5).map_err(read)?,
+            is_image,
+            image: None,
+            thumbnail: None,
+            image_width: row.get(8).map_err(read)?,
+            image_height: row.get(9).map_err(read)?,
+            image_size: row.get(10).map_err(read)?,
+            image_dominant_color: row.get(11).map_err(read)?,
+            timestamp: row.get(12).map_err(read)?,
+            is_pinned: row.get(13).map_err(read)?,
+            tags: tag_names
+                .map(|names| names.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+            search_text: row.get(15).map_err(read)?,
+            decoded_text: row.get(16).map_err(read)?,
+            decoded_type: row.get(17).map_err(read)?,
+            image_phash: row.get(18).map_err(read)?,
+            image_palette: row.get(19).map_err(read)?,
+            image_format: row.get(20).map_err(read)?,
+            image_color_space: row.get(21).map_err(read)?,
+            image_dpi: row.get(22).map_err(read)?,
+            image_has_alpha: row.get(23).map_err(read)?,
+            image_animated: row.get(24).map_err(read)?,
+        };
+
+        if is_image {
+            let Some(source) = image_path.map(PathBuf::from).filter(|p| p.is_file()) else {
+                summary.missing_images += 1;
+                continue;
+            };
+            let extension = source.extension().and_then(|e| e.to_str()).unwrap_or("png");
+            let name = format!("{}/{}.{}", IMAGES_DIR, id, extension);
+            images.push((name.clone(), source.clone()));
+            item.image = Some(name);
+
+            let thumbnail = thumbnail_path(&source);
+            if thumbnail.is_file() {
+                let name = format!("{}/{}_thumb.png", IMAGES_DIR, id);
+                images.push((name.clone(), thumbnail));
+                item.thumbnail = Some(name);
+            }
+            summary.image_count += 1;
+        }
+
+        let line = serde_json::to_string(&item)
+            .map_err(|e| format!("Failed to serialize item {}: {}", id, e))?;
+        writeln!(zip, "{}", line).map_err(|e| format!("Failed to write archive: {}", e))?;
+        summary.item_count += 1;
+    }
+
+    for (name, source) in images {
+        let bytes = fs::read(&source)
+            .map_err(|e| format!("Failed to read image {}: {}", source.display(), e))?;
+        zip.start_file(name, stored)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        zip.write_all(&bytes)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+    }
+
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        item_count: summary.item_count,
+        image_count: summary.image_count,
+        categories: read_categories(conn)?,
+        tags: read_tags(conn)?,
+    };
+    zip.start_file(MANIFEST_FILE, deflated)
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    serde_json::to_writer_pretty(&mut zip, &manifest)
+        .map_err(|e| format!("Failed to write manifest: {}", e))?;
+
+    zip.finish().map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(summary)
+}
+
+fn read_categories(conn: &Connection) -> Result<Vec<ArchiveCategory>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, icon, color FROM categories ORDER BY sort_order")
+        .map_err(|e| format!("Failed to read categories: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ArchiveCategory { name: row.get(0)?, icon: row.get(1)?, color: row.get(2)? })
+        })
+        .map_err(|e| format!("Failed to read categories: {}", e))?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read categories: {}", e))
+}
+
+fn read_tags(conn: &Connection) -> Result<Vec<ArchiveTag>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, icon, color FROM tags ORDER BY name")
+        .map_err(|e| format!("Failed to read tags: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ArchiveTag { name: row.get(0)?, icon: row.get(1)?, color: row.get(2)? })
+        })
+        .map_err(|e| format!("Failed to read tags: {}", e))?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read tags: {}", e))
+}
+
+// ============================================
+// IMPORT
+// ============================================
+
+/// Read the manifest and check this build understands the archive
+pub fn read_manifest<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Manifest, String> {
+    let file = archive
+        .by_name(MANIFEST_FILE)
+        .map_err(|_| "Not a CopyGum archive (manifest.json missing)".to_string())?;
+    let manifest: Manifest = serde_json::from_reader(file.take(MAX_MANIFEST_BYTES))
+        .map_err(|e| format!("Invalid archive manifest: {}", e))?;
+
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(format!("Not a CopyGum archive (format {})", manifest.format));
+    }
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(format!(
+            "Archive version {} is newer than this version of CopyGum supports ({})",
+            manifest.version, ARCHIVE_VERSION
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Import an archive in one transaction, copying its images into `images_dir`
+/// Images (and thumbnails) over `max_image_bytes` are skipped.
+pub fn import_archive<R: Read + Seek>(
+    conn: &mut Connection,
+    reader: R,
+    images_dir: &Path,
+    max_image_bytes: u64,
+) -> Result<ImportReport, String> {
+    let mut archive = ZipArchive::new(reader).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let manifest = read_manifest(&mut archive)?;
+
+    let items = read_entry(&mut archive, ITEMS_FILE, json_import::MAX_FILE_BYTES)?
+        .ok_or_else(|| "Archive has no items.jsonl".to_string())?;
+    let items = String::from_utf8(items).map_err(|e| format!("Failed to read items: {}", e))?;
+    let lines: Vec<String> = items.lines().map(str::to_string).collect();
+
+    fs::create_dir_all(images_dir).map_err(|e| format!("Failed to create images directory: {}", e))?;
+
+    // Images are written before the commit; remove them again if it fails
+    let mut written = Vec::new();
+    let result = import_records(conn, &mut archive, &manifest, &lines, images_dir, max_image_bytes, &mut written);
+    if result.is_err() {
+        for path in written {
+            let _ = fs::remove_file(path);
+        }
+    }
+    result
+}
+
+fn import_records<R: Read + Seek>(
+    conn: &mut Connection,
+    archive: &mut ZipArchive<R>,
+    manifest: &Manifest,
+    lines: &[String],
+    images_dir: &Path,
+    max_image_bytes: u64,
+    written: &mut Vec<PathBuf>,
+) -> Result<ImportReport, String> {
+    let tx = conn.transaction().map_err(|e| format!("Failed to start import: {}", e))?;
+    let mut report = ImportReport::default();
+
+    for category in &manifest.categories {
+        merge_category(&tx, category, &mut report)?;
+    }
+    for tag in &manifest.tags {
+        merge_tag(&tx, tag, &mut report)?;
+    }
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let item: ArchiveItem = match serde_json::from_str(line) {
+            Ok(item) => item,
+            Err(e) => {
+                report.skipped += 1;
+                report.conflict("item", &format!("line {}", index + 1), format!("Invalid record: {}", e));
+                continue;
+            }
+        };
+        import_item(&tx, archive, item, images_dir, max_image_bytes, written, &mut report)?;
+    }
+
+    tx.commit().map_err(|e| format!("Failed to commit import: {}", e))?;
+    Ok(report)
+}
+
+fn merge_category(tx: &Transaction, category: &ArchiveCategory, report: &mut ImportReport) -> Result<(), String> {
+    let existing: Option<(String, Option<String>)> = tx
+        .query_row(
+            "SELECT icon, color FROM categories WHERE name = ?1",
+            params![category.name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read categories: {}", e))?;
+
+    match existing {
+        None => {
+            tx.execute(
+                "INSERT INTO categories (name, icon, color, is_custom, sort_order)
+                 VALUES (?1, ?2, ?3, 1, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM categories))",
+                params![category.name, category.icon, category.color],
+            )
+            .map_err(|e| format!("Failed to add category {}: {}", category.name, e))?;
+            report.categories_added.push(category.name.clone());
+        }
+        Some((icon, color)) if icon != category.icon || color != category.color => {
+            report.conflict("category", &category.name, "Exists with a different icon or color; kept local");
+        }
+        Some(_) => {}
+    }
+    Ok(())
+}
+
+fn merge_tag(tx: &Transaction, tag: &ArchiveTag, report: &mut ImportReport) -> Result<(), String> {
+    let existing: Option<(Option<String>, Option<String>)> = tx
+        .query_row(
+            "SELECT icon, color FROM tags WHERE name = ?1",
+            params![tag.name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read tags: {}", e))?;
+
+    match existing {
+        None => {
+            tx.execute(
+                "INSERT INTO tags (name, icon, color, is_custom) VALUES (?1, ?2, ?3, 1)",
+                params![tag.name, tag.icon, tag.color],
+            )
+            .map_err(|e| format!("Failed to add tag {}: {}", tag.name, e))?;
+            report.tags_added.push(tag.name.clone());
+        }
+        Some((icon, color)) if icon != tag.icon || color != tag.color => {
+            report.conflict("tag", &tag.name, "Exists with a different icon or color; kept local");
+        }
+        Some(_) => {}
+    }
+    Ok(())
+}
+
+fn import_item<R: Read + Seek>(
+    tx: &Transaction,
+    archive: &mut ZipArchive<R>,
+    mut item: ArchiveItem,
+    images_dir: &Path,
+    max_image_bytes: u64,
+    written: &mut Vec<PathBuf>,
+    report: &mut ImportReport,
+) -> Result<(), String> {
+    let image_bytes = match (&item.is_image, &item.image) {
+        (true, Some(name)) => match read_entry(archive, name, max_image_bytes) {
+            Ok(Some(bytes)) => Some(bytes),
+            Ok(None) => {
+                report.skipped += 1;
+                report.conflict("item", name, "Image missing from archive");
+                return Ok(());
+            }
+            Err(e) => {
+                report.skipped += 1;
+                report.conflict("item", name, e);
+                return Ok(());
+            }
+        },
+        (true, None) => {
+            report.skipped += 1;
+            report.conflict("item", &item.content, "Image item without an image");
+            return Ok(());
+        }
+        _ => None,
+    };
+
+    let existing = match &image_bytes {
+        Some(bytes) => find_existing_image(tx, bytes)?,
+        None => find_existing_text(tx, &item.content)?,
+    };
+
+    if let Some((id, category, is_pinned)) = existing {
+        add_tags(tx, id, &item.tags, report)?;
+        if category != item.category || is_pinned != item.is_pinned {
+            report.conflicted += 1;
+            report.conflict("item", &preview(&item.content), "Already in history with a different category or pin; kept local");
+        } else {
+            report.skipped += 1;
+        }
+        return Ok(());
+    }
+
+    // Unknown categories (not in the manifest either) fall back to the defaults
+    let category_exists: bool = tx
+        .query_row("SELECT COUNT(*) > 0 FROM categories WHERE name = ?1", params![item.category], |row| row.get(0))
+        .map_err(|e| format!("Failed to read categories: {}", e))?;
+    if !category_exists {
+        item.category = if item.is_image { "image" } else { "text" }.to_string();
+    }
+
+    let mut image_path = None;
+    if let Some(bytes) = image_bytes {
+        let extension = item
+            .image
+            .as_deref()
+            .and_then(|name| Path::new(name).extension())
+            .and_then(|e| e.to_str())
+            .unwrap_or("png")
+            .to_string();
+        let path = unique_image_path(images_dir, &extension);
+        fs::write(&path, &bytes).map_err(|e| format!("Failed to save image: {}", e))?;
+        written.push(path.clone());
+
+        let thumbnail = item
+            .thumbnail
+            .as_deref()
+            .and_then(|name| read_entry(archive, name, max_image_bytes).ok().flatten());
+        if let Some(thumbnail) = thumbnail {
+            let thumbnail_path = thumbnail_path(&path);
+            fs::write(&thumbnail_path, thumbnail).map_err(|e| format!("Failed to save thumbnail: {}", e))?;
+            written.push(thumbnail_path);
+        }
+
+        // Image items display their file name
+        item.content = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        item.image_size = Some(bytes.len() as i64);
+        image_path = Some(path.to_string_lossy().to_string());
+    }
+
+    tx.execute(
+        "INSERT INTO clipboard_items
+         (content, content_type, category, app_name, app_bundle_id, is_image, image_path, image_width,
+          image_height, image_size, image_dominant_color, timestamp, is_pinned, search_text, decoded_text,
+          decoded_type, image_phash, image_palette, image_format, image_color_space, image_dpi,
+          image_has_alpha, image_animated, is_deleted)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20,
+                 ?21, ?22, ?23, 0)",
+        params![
+            item.content,
+            item.content_type,
+            item.category,
+            item.app_name,
+            item.app_bundle_id,
+            item.is_image,
+            image_path,
+            item.image_width,
+            item.image_height,
+            item.image_size,
+            item.image_dominant_color,
+            item.timestamp,
+            item.is_pinned,
+            item.search_text,
+            item.decoded_text,
+            item.decoded_type,
+            item.image_phash,
+            item.image_palette,
+            item.image_format,
+            item.image_color_space,
+            item.image_dpi,
+            item.image_has_alpha,
+            item.image_animated,
+        ],
+    )
+    .map_err(|e| format!("Failed to import item: {}", e))?;
+    add_tags(tx, tx.last_insert_rowid(), &item.tags, report)?;
+    report.added += 1;
+    Ok(())
+}
+
+/// Contents of an archive entry, None if it is missing
+/// Entries over `limit` bytes are refused, so a zip bomb can't exhaust memory.
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str, limit: u64) -> Result<Option<Vec<u8>>, String> {
+    let Ok(file) = archive.by_name(name) else {
+        return Ok(None);
+    };
+    let too_large = || format!("{} is larger than {} MB", name, limit / 1024 / 1024);
+    if file.size() > limit {
+        return Err(too_large());
+    }
+    // The declared size can't be trusted, so the read is capped too
+    let mut bytes = Vec::new();
+    file.take(limit.saturating_add(1))
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    if bytes.len() as u64 > limit {
+        return Err(too_large());
+    }
+    Ok(Some(bytes))
+}
+
+fn find_existing_text(tx: &Transaction, content: &str) -> Result<Option<(i64, String, bool)>, String> {
+    tx.query_row(
+        "SELECT id, category, is_pinned FROM clipboard_items
+         WHERE content = ?1 AND is_image = 0 AND is_deleted = 0
+         ORDER BY timestamp DESC LIMIT 1",
+        params![content],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to check for duplicates: {}", e))
+}
+
+/// Compare file contents, only for images of the same size
+fn find_existing_image(tx: &Transaction, bytes: &[u8]) -> Result<Option<(i64, String, bool)>, String> {
+    let mut stmt = tx
+        .prepare(
+            "SELECT id, category, is_pinned, image_path FROM clipboard_items
+             WHERE is_image = 1 AND is_deleted = 0 AND image_size = ?1",
+        )
+        .map_err(|e| format!("Failed to check for duplicates: {}", e))?;
+    let candidates = stmt
+        .query_map(params![bytes.len() as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get::<_, Option<String>>(3)?))
+        })
+        .map_err(|e| format!("Failed to check for duplicates: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to check for duplicates: {}", e))?;
+
+    Ok(candidates.into_iter().find_map(|(id, category, is_pinned, path)| {
+        let existing = fs::read(path?).ok()?;
+        (existing == bytes).then_some((id, category, is_pinned))
+    }))
+}
+
+fn add_tags(tx: &Transaction, item_id: i64, tags: &[String], report: &mut ImportReport) -> Result<(), String> {
+    for name in tags.iter().filter(|name| !name.is_empty()) {
+        let created = tx
+            .execute("INSERT OR IGNORE INTO tags (name, is_custom) VALUES (?1, 1)", params![name])
+            .map_err(|e| format!("Failed to add tag {}: {}", name, e))?;
+        if created > 0 {
+            report.tags_added.push(name.clone());
+        }
+        tx.execute(
+            "INSERT OR IGNORE INTO item_tags (item_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+            params![item_id, name],
+        )
+        .map_err(|e| format!("Failed to tag item: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Same naming scheme as image_handler, never overwriting an existing file
+fn unique_image_path(images_dir: &Path, extension: &str) -> PathBuf {
+    loop {
+        let path = images_dir.join(format!(
+            "{}_{}.{}",
+            items::now_millis(),
+            fastrand::u32(1000..9999),
+            extension
+        ));
+        if !path.exists() {
+            return path;
+        }
+    }
+}
+
+fn preview(content: &str) -> String {
+    let preview: String = content.chars().take(40).collect();
+    if preview.len() < content.len() {
+        format!("{}…", preview)
+    } else {
+        preview
+    }
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
+/// Export history and images to a .copygum archive, encrypted if a passphrase is given
+#[tauri::command]
+pub async fn export_archive(
+    file_path: String,
+    filter: Option<ItemFilter>,
+    passphrase: Option<String>,
+) -> Result<ArchiveSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = items::open_read_only(&database_path()?)?;
+        let filter = filter.unwrap_or_default();
+        let summary = match passphrase {
+            // Built in memory so no plaintext copy of the archive touches the disk
+            Some(passphrase) => {
+                let mut zip = Cursor::new(Vec::new());
+                let written = write_archive(&conn, &mut zip, &filter);
+                let mut bytes = zip.into_inner();
+                let encrypted = written.and_then(|summary| {
+                    let file = fs::File::create(&file_path)
+                        .map_err(|e| format!("Failed to create archive: {}", e))?;
+                    let mut writer = EncryptWriter::new(BufWriter::new(file), &passphrase)?;
+                    writer
+                        .write_all(&bytes)
+                        .map_err(|e| format!("Failed to write archive: {}", e))?;
+                    writer.finish()?;
+                    Ok(summary)
+                });
+                bytes.zeroize();
+                encrypted?
+            }
+            None => {
+                let file = fs::File::create(&file_path)
+                    .map_err(|e| format!("Failed to create archive: {}", e))?;
+                write_archive(&conn, file, &filter)?
+            }
+        };
+        println!("[CopyGum] Archived {} items ({} images) to {}", summary.item_count, summary.image_count, file_path);
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("Archive export failed: {}", e))?
+}
+
+/// Import a .copygum archive into the history, decrypting it if needed
+#[tauri::command]
+pub async fn import_archive_file(
+    app: AppHandle,
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<ImportReport, String> {
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = items::open_read_write(&database_path()?)?;
+        let encrypted = encryption::is_encrypted_file(Path::new(&file_path))?;
+        let file = fs::File::open(&file_path)
+            .map_err(|e| format!("Failed to open archive: {}", e))?;
+        let settings = AppSettings::load_from(&settings_path()?)?;
+        let max_image_bytes = (settings.max_image_size_mb.max(0) as u64) * 1024 * 1024;
+        if !encrypted {
+            return import_archive(&mut conn, file, &images_dir()?, max_image_bytes);
+        }
+
+        // Decrypted in memory so no plaintext copy of the archive touches the disk
+        let passphrase = passphrase.ok_or_else(|| encryption::PASSPHRASE_REQUIRED.to_string())?;
+        let mut bytes = Vec::new();
+        let result = DecryptReader::new(BufReader::new(file), &passphrase)?
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to decrypt archive: {}", e))
+            .and_then(|_| import_archive(&mut conn, Cursor::new(bytes.as_slice()), &images_dir()?, max_image_bytes));
+        bytes.zeroize();
+        result
+    })
+    .await
+    .map_err(|e| format!("Archive import failed: {}", e))??;
+
+    println!(
+        "[CopyGum] Archive import: {} added, {} skipped, {} conflicted",
+        report.added, report.skipped, report.conflicted
+    );
+    let _ = app.emit("items-changed", ());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE_LIMIT: u64 = 10 * 1024 * 1024;
+
+    fn insert_text(conn: &Connection, content: &str, category: &str, pinned: bool) -> i64 {
+        conn.execute(
+            "INSERT INTO clipboard_items (content, content_type, category, is_image, timestamp, is_pinned, is_deleted)
+             VALUES (?1, 'text', ?2, 0, 1000, ?3, 0)",
+            params![content, category, pinned],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn insert_image(conn: &Connection, path: &Path) {
+        let size = fs::metadata(path).unwrap().len() as i64;
+        conn.execute(
+            "INSERT INTO clipboard_items (content, content_type, category, is_image, image_path, image_size, timestamp, is_pinned, is_deleted)
+             VALUES ('shot.png', 'image', 'image', 1, ?1, ?2, 2000, 0, 0)",
+            params![path.to_string_lossy(), size],
+        )
+        .unwrap();
+    }
+
+    fn archive_of(conn: &Connection) -> Vec<u8> {
+        let mut out = Cursor::new(Vec::new());
+        write_archive(conn, &mut out, &ItemFilter::default()).unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn test_round_trip_rewrites_image_paths_and_merges_by_name() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let image = source_dir.path().join("1_1234.png");
+        fs::write(&image, b"png bytes").unwrap();
+        fs::write(source_dir.path().join("1_1234_thumb.png"), b"thumb").unwrap();
+
+        let source = items::test_connection();
+        source
+            .execute("INSERT INTO categories (name, icon, is_custom, sort_order) VALUES ('snippets', '✂️', 1, 20)", [])
+            .unwrap();
+        let id = insert_text(&source, "hello", "snippets", false);
+        source.execute("INSERT INTO tags (name, icon) VALUES ('Travel', '✈️')", []).unwrap();
+        source
+            .execute("INSERT INTO item_tags (item_id, tag_id) SELECT ?1, id FROM tags WHERE name = 'Travel'", params![id])
+            .unwrap();
+        insert_image(&source, &image);
+        source
+            .execute_batch(
+                "UPDATE clipboard_items SET search_text = 'Invoice 2041', decoded_text = 'https://example.com',
+                     decoded_type = 'links', image_phash = 'f0f0f0f0f0f0f0f0', image_palette = '[\"#112233\"]',
+                     image_format = 'png', image_color_space = 'sRGB', image_dpi = 144, image_has_alpha = 1,
+                     image_animated = 0
+                 WHERE is_image = 1",
+            )
+            .unwrap();
+        let archive = archive_of(&source);
+
+        let target_dir = tempfile::tempdir().unwrap();
+        let mut target = items::test_connection();
+        let report = import_archive(&mut target, Cursor::new(archive.clone()), target_dir.path(), IMAGE_LIMIT).unwrap();
+
+        assert_eq!((report.added, report.skipped, report.conflicted), (2, 0, 0));
+        assert_eq!(report.categories_added, vec!["snippets"]);
+        assert_eq!(report.tags_added, vec!["Travel"]);
+
+        let imported_path: String = target
+            .query_row("SELECT image_path FROM clipboard_items WHERE is_image = 1", [], |row| row.get(0))
+            .unwrap();
+        assert!(imported_path.starts_with(&target_dir.path().to_string_lossy().to_string()));
+        assert_eq!(fs::read(&imported_path).unwrap(), b"png bytes");
+        assert!(thumbnail_path(Path::new(&imported_path)).is_file());
+
+        // OCR text, decoded code, hash, palette and file details come along
+        let details: (String, String, String, String, String, String, String, i64, bool, bool) = target
+            .query_row(
+                "SELECT search_text, decoded_text, decoded_type, image_phash, image_palette, image_format,
+                        image_color_space, image_dpi, image_has_alpha, image_animated
+                 FROM clipboard_items WHERE is_image = 1",
+                [],
+                |row| {
+                    Ok((
+                        row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?,
+                        row.get(5)?, row.get(6)?, row.get(7)?, row.get(8)?, row.get(9)?,
+                    ))
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            details,
+            (
+                "Invoice 2041".into(), "https://example.com".into(), "links".into(), "f0f0f0f0f0f0f0f0".into(),
+                "[\"#112233\"]".into(), "png".into(), "sRGB".into(), 144, true, false,
+            )
+        );
+
+        let tags: String = target
+            .query_row("SELECT tag_names FROM clipboard_items_with_tags WHERE content = 'hello'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tags, "Travel");
+
+        // Importing the same archive again adds nothing
+        let again = import_archive(&mut target, Cursor::new(archive), target_dir.path(), IMAGE_LIMIT).unwrap();
+        assert_eq!((again.added, again.skipped, again.conflicted), (0, 2, 0));
+    }
+
+    #[test]
+    fn test_conflicting_items_keep_local_copy() {
+        let source = items::test_connection();
+        insert_text(&source, "shared", "code", true);
+        let archive = archive_of(&source);
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut target = items::test_connection();
+        insert_text(&target, "shared", "text", false);
+        let report = import_archive(&mut target, Cursor::new(archive), dir.path(), IMAGE_LIMIT).unwrap();
+
+        assert_eq!((report.added, report.conflicted), (0, 1));
+        assert_eq!(report.conflicts[0].kind, "item");
+        let category: String = target
+            .query_row("SELECT category FROM clipboard_items WHERE content = 'shared'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(category, "text");
+    }
+
+    #[test]
+    fn test_oversized_entries_are_refused() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let image = source_dir.path().join("1_1234.png");
+        fs::write(&image, vec![0u8; 4096]).unwrap();
+        let source = items::test_connection();
+        insert_text(&source, "small", "text", false);
+        insert_image(&source, &image);
+        let archive = archive_of(&source);
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut target = items::test_connection();
+        let report = import_archive(&mut target, Cursor::new(archive), dir.path(), 1024).unwrap();
+        assert_eq!((report.added, report.skipped), (1, 1));
+        assert!(report.conflicts[0].reason.contains("larger than"));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_rejects_newer_archive_versions() {
+        let mut out = Cursor::new(Vec::new());
+        let mut zip = ZipWriter::new(&mut out);
+        zip.start_file(MANIFEST_FILE, SimpleFileOptions::default()).unwrap();
+        zip.write_all(
+            br#"{"format":"copygum-archive","version":99,"app_version":"9.0.0","created_at":"","item_count":0,"image_count":0}"#,
+        )
+        .unwrap();
+        zip.finish().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = items::test_connection();
+        let err = import_archive(&mut conn, Cursor::new(out.into_inner()), dir.path(), IMAGE_LIMIT).unwrap_err();
+        assert!(err.contains("newer"));
+    }
+}
@@ -0,0 +1,311 @@
+// Autostart Module - Launch CopyGum at login
+// Installs or removes the platform's login item when the launch_at_startup
+// setting changes:
+// - Linux: XDG autostart entry (~/.config/autostart/copygum.desktop)
+// - macOS: LaunchAgent (~/Library/LaunchAgents/com.copygum.clipboard.plist)
+// - Windows: HKCU\Software\Microsoft\Windows\CurrentVersion\Run value
+// The login item passes --autostart so the app starts hidden in the tray.
+
+use std::path::PathBuf;
+
+use tauri::AppHandle;
+
+use crate::paths::APP_ID;
+
+/// Argument added to the login item's command line
+pub const AUTOSTART_ARG: &str = "--autostart";
+
+/// Whether this process was started by the login item
+pub fn launched_at_login() -> bool {
+    std::env::args().any(|arg| arg == AUTOSTART_ARG)
+}
+
+/// Executable the login item should run
+///
+/// AppImages run from a temporary mount, so point at the AppImage itself.
+fn executable_path() -> Result<PathBuf, String> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+    std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))
+}
+
+// ============================================
+// LINUX (XDG AUTOSTART)
+// ============================================
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn entry_path(app: &AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+
+    let config_dir = app.path().config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?;
+    Ok(config_dir.join("autostart").join("copygum.desktop"))
+}
+
+/// Check whether the autostart entry is installed and not disabled
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn is_enabled(app: &AppHandle) -> bool {
+    let Ok(path) = entry_path(app) else {
+        return false;
+    };
+    match std::fs::read_to_string(path) {
+        Ok(contents) => desktop_entry_enabled(&contents),
+        Err(_) => false,
+    }
+}
+
+/// Install or remove the autostart entry
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn set_enabled(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let path = entry_path(app)?;
+
+    if enabled {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create autostart dir: {}", e))?;
+        }
+        std::fs::write(&path, desktop_entry(&executable_path()?.to_string_lossy()))
+            .map_err(|e| format!("Failed to write autostart entry: {}", e))?;
+    } else if path.exists() {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove autostart entry: {}", e))?;
+    }
+
+    Ok(())
+}
+
+// ============================================
+// MACOS (LAUNCH AGENT)
+// ============================================
+
+#[cfg(target_os = "macos")]
+fn entry_path(app: &AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+
+    let home = app.path().home_dir()
+        .map_err(|e| format!("Failed to get home dir: {}", e))?;
+    Ok(home.join("Library").join("LaunchAgents").join(format!("{}.plist", APP_ID)))
+}
+
+/// Check whether the LaunchAgent is installed
+#[cfg(target_os = "macos")]
+pub fn is_enabled(app: &AppHandle) -> bool {
+    entry_path(app).map(|path| path.exists()).unwrap_or(false)
+}
+
+/// Install or remove the LaunchAgent (picked up at next login)
+#[cfg(target_os = "macos")]
+pub fn set_enabled(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let path = entry_path(app)?;
+
+    if enabled {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create LaunchAgents dir: {}", e))?;
+        }
+        std::fs::write(&path, launch_agent_plist(&executable_path()?.to_string_lossy()))
+            .map_err(|e| format!("Failed to write LaunchAgent: {}", e))?;
+    } else if path.exists() {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove LaunchAgent: {}", e))?;
+    }
+
+    Ok(())
+}
+
+// ============================================
+// WINDOWS (RUN KEY)
+// ============================================
+
+#[cfg(target_os = "windows")]
+const RUN_KEY: windows::core::PCWSTR =
+    windows::core::w!("Software\\Microsoft\\Windows\\CurrentVersion\\Run");
+#[cfg(target_os = "windows")]
+const RUN_VALUE: windows::core::PCWSTR = windows::core::w!("CopyGum");
+
+/// Check whether the Run key value exists
+#[cfg(target_os = "windows")]
+pub fn is_enabled(_app: &AppHandle) -> bool {
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_SZ};
+
+    unsafe {
+        RegGetValueW(HKEY_CURRENT_USER, RUN_KEY, RUN_VALUE, RRF_RT_REG_SZ, None, None, None)
+            == ERROR_SUCCESS
+    }
+}
+
+/// Add or remove the Run key value
+#[cfg(target_os = "windows")]
+pub fn set_enabled(_app: &AppHandle, enabled: bool) -> Result<(), String> {
+    use windows::Win32::Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS};
+    use windows::Win32::System::Registry::{
+        RegDeleteKeyValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ,
+    };
+
+    unsafe {
+        if enabled {
+            let command = run_command(&executable_path()?.to_string_lossy());
+            let data: Vec<u16> = command.encode_utf16().chain(std::iter::once(0)).collect();
+            let result = RegSetKeyValueW(
+                HKEY_CURRENT_USER,
+                RUN_KEY,
+                RUN_VALUE,
+                REG_SZ.0,
+                Some(data.as_ptr() as *const std::ffi::c_void),
+                (data.len() * std::mem::size_of::<u16>()) as u32,
+            );
+            if result != ERROR_SUCCESS {
+                return Err(format!("Failed to write Run key: {:?}", result));
+            }
+        } else {
+            let result = RegDeleteKeyValueW(HKEY_CURRENT_USER, RUN_KEY, RUN_VALUE);
+            if result != ERROR_SUCCESS && result != ERROR_FILE_NOT_FOUND {
+                return Err(format!("Failed to remove Run key: {:?}", result));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// ============================================
+// ENTRY CONTENTS
+// ============================================
+
+/// XDG autostart entry launching the given executable
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+fn desktop_entry(exec: &str) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=CopyGum\n\
+         Comment=Clipboard manager\n\
+         Exec={} {}\n\
+         Icon=copygum-app\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        quote_exec_arg(exec),
+        AUTOSTART_ARG,
+    )
+}
+
+/// A user (or the desktop's startup settings) can disable an entry in place
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+fn desktop_entry_enabled(contents: &str) -> bool {
+    !contents.lines().map(str::trim).any(|line| {
+        line.eq_ignore_ascii_case("Hidden=true")
+            || line.eq_ignore_ascii_case("X-GNOME-Autostart-enabled=false")
+    })
+}
+
+/// Quote an Exec argument per the Desktop Entry spec
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+fn quote_exec_arg(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            // Field codes like %f would be expanded by the launcher
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// LaunchAgent plist running the given executable at login
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn launch_agent_plist(exec: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        APP_ID,
+        escape_xml(exec),
+        AUTOSTART_ARG,
+    )
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Command line stored in the Windows Run key
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn run_command(exec: &str) -> String {
+    format!("\"{}\" {}", exec, AUTOSTART_ARG)
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
+/// Report whether the login item is actually installed
+#[tauri::command]
+pub async fn get_launch_at_startup(app: AppHandle) -> Result<bool, String> {
+    Ok(is_enabled(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desktop_entry_quotes_exec_and_passes_autostart_arg() {
+        let entry = desktop_entry("/opt/Copy Gum/copygum");
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("\nExec=\"/opt/Copy Gum/copygum\" --autostart\n"));
+        assert!(desktop_entry_enabled(&entry));
+    }
+
+    #[test]
+    fn test_quote_exec_arg_escapes_reserved_characters() {
+        assert_eq!(quote_exec_arg(r#"/a"b$c`d\e%f"#), r#""/a\"b\$c\`d\\e%%f""#);
+    }
+
+    #[test]
+    fn test_disabled_desktop_entry() {
+        assert!(!desktop_entry_enabled("[Desktop Entry]\nHidden=true\n"));
+        assert!(!desktop_entry_enabled("[Desktop Entry]\nX-GNOME-Autostart-enabled=false\n"));
+    }
+
+    #[test]
+    fn test_launch_agent_plist_escapes_path() {
+        let plist = launch_agent_plist("/Applications/A&B.app/Contents/MacOS/copygum");
+        assert!(plist.contains("<string>/Applications/A&amp;B.app/Contents/MacOS/copygum</string>"));
+        assert!(plist.contains("<string>--autostart</string>"));
+        assert!(plist.contains(&format!("<string>{}</string>", APP_ID)));
+    }
+
+    #[test]
+    fn test_run_command_quotes_path() {
+        assert_eq!(
+            run_command(r"C:\Program Files\CopyGum\copygum.exe"),
+            r#""C:\Program Files\CopyGum\copygum.exe" --autostart"#
+        );
+    }
+}
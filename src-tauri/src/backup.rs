@@ -0,0 +1,550 @@
+// Backup Module - Scheduled local snapshots of the history
+// Each snapshot is a directory copygum-backup-<YYYYmmdd-HHMMSS>/ holding:
+// - copygum.db: copied with SQLite's online backup API, so it is consistent
+//   even while the app is writing
+// - settings.json
+// - images/: hard links to the image files where possible (they never change
+//   once saved), copies otherwise
+// - backup.json: when and why it was taken, app version and item count
+// A scheduler thread checks hourly whether a daily or weekly snapshot is due
+// and removes the oldest snapshots beyond the configured number to keep.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::db::items;
+use crate::paths::{app_data_dir, database_path, images_dir, settings_path};
+use crate::settings::AppSettings;
+
+const BACKUP_FORMAT: &str = "copygum-backup";
+const BACKUP_VERSION: u32 = 1;
+const BACKUP_PREFIX: &str = "copygum-backup-";
+const MANIFEST_FILE: &str = "backup.json";
+const DATABASE_FILE: &str = "copygum.db";
+const SETTINGS_FILE: &str = "settings.json";
+const IMAGES_DIR: &str = "images";
+
+/// How often the scheduler checks whether a backup is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Wait after launch so the first check doesn't compete with startup
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: String,
+    pub version: u32,
+    pub created_at: i64,
+    pub app_version: String,
+    pub item_count: i64,
+    /// "scheduled", "manual" or "pre-restore"
+    pub reason: String,
+}
+
+/// A snapshot on disk, as listed in settings
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub name: String,
+    pub created_at: i64,
+    pub item_count: i64,
+    pub reason: String,
+    /// Size of the database file
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreReport {
+    pub item_count: i64,
+    pub images_restored: usize,
+    /// Snapshot of the state just before the restore, to undo it
+    pub safety_backup: String,
+}
+
+/// Where snapshots go: the configured directory or <app data>/backups
+pub fn backup_dir(settings: &AppSettings) -> Result<PathBuf, String> {
+    match settings.backup_directory.as_deref().filter(|dir| !dir.trim().is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(app_data_dir()?.join("backups")),
+    }
+}
+
+/// Interval between scheduled backups, in milliseconds
+fn interval_ms(frequency: &str) -> i64 {
+    match frequency {
+        "weekly" => 7 * DAY_MS,
+        _ => DAY_MS,
+    }
+}
+
+/// Whether a scheduled backup should run now
+pub fn is_due(settings: &AppSettings, newest: Option<i64>, now: i64) -> bool {
+    settings.backup_enabled
+        && newest.is_none_or(|created_at| now - created_at >= interval_ms(&settings.backup_frequency))
+}
+
+// ============================================
+// CREATING SNAPSHOTS
+// ============================================
+
+/// Copy `source` into the SQLite file at `dest` with the online backup API
+/// Steps in small batches and retries while another connection holds a lock.
+fn copy_database(source: &Connection, dest: &mut Connection) -> Result<(), String> {
+    dest.busy_timeout(Duration::from_secs(5))
+        .map_err(|e| format!("Failed to configure backup: {}", e))?;
+    Backup::new(source, dest)
+        .and_then(|backup| backup.run_to_completion(256, Duration::from_millis(25), None))
+        .map_err(|e| format!("Failed to copy database: {}", e))
+}
+
+/// Hard-link each file into `dest`, copying when linking isn't possible
+/// (different file system, FAT, ...). Existing files in `dest` are kept.
+fn link_or_copy_files(source: &Path, dest: &Path) -> Result<usize, String> {
+    let Ok(entries) = fs::read_dir(source) else {
+        return Ok(0);
+    };
+    fs::create_dir_all(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+
+    let mut count = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let target = dest.join(entry.file_name());
+        if target.exists() {
+            continue;
+        }
+        if fs::hard_link(&path, &target).is_err() {
+            fs::copy(&path, &target).map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Take a snapshot of the database, settings and images into `backups`
+pub fn create_backup(
+    db_path: &Path,
+    settings_file: &Path,
+    images: &Path,
+    backups: &Path,
+    reason: &str,
+) -> Result<BackupInfo, String> {
+    fs::create_dir_all(backups).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut name = format!("{}{}", BACKUP_PREFIX, stamp);
+    let mut suffix = 1;
+    while backups.join(&name).exists() {
+        suffix += 1;
+        name = format!("{}{}-{}", BACKUP_PREFIX, stamp, suffix);
+    }
+
+    // Build under a hidden name so a half-written snapshot is never listed
+    let partial = backups.join(format!(".{}.partial", name));
+    let result = write_snapshot(db_path, settings_file, images, &partial, reason);
+    let manifest = match result {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&partial);
+            return Err(e);
+        }
+    };
+
+    let final_path = backups.join(&name);
+    fs::rename(&partial, &final_path).map_err(|e| format!("Failed to finish backup: {}", e))?;
+    Ok(backup_info(&final_path, manifest))
+}
+
+fn write_snapshot(
+    db_path: &Path,
+    settings_file: &Path,
+    images: &Path,
+    dest: &Path,
+    reason: &str,
+) -> Result<BackupManifest, String> {
+    fs::create_dir_all(dest).map_err(|e| format!("Failed to create backup: {}", e))?;
+
+    let source = items::open_read_only(db_path)?;
+    let mut snapshot = Connection::open(dest.join(DATABASE_FILE))
+        .map_err(|e| format!("Failed to create backup database: {}", e))?;
+    copy_database(&source, &mut snapshot)?;
+    let item_count: i64 = snapshot
+        .query_row("SELECT COUNT(*) FROM clipboard_items WHERE is_deleted = 0", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to count backed up items: {}", e))?;
+    drop(snapshot);
+
+    if settings_file.is_file() {
+        fs::copy(settings_file, dest.join(SETTINGS_FILE))
+            .map_err(|e| format!("Failed to back up settings: {}", e))?;
+    }
+    link_or_copy_files(images, &dest.join(IMAGES_DIR))?;
+
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: items::now_millis(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        item_count,
+        reason: reason.to_string(),
+    };
+    let json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+    fs::write(dest.join(MANIFEST_FILE), json).map_err(|e| format!("Failed to write backup manifest: {}", e))?;
+    Ok(manifest)
+}
+
+fn backup_info(path: &Path, manifest: BackupManifest) -> BackupInfo {
+    BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        created_at: manifest.created_at,
+        item_count: manifest.item_count,
+        reason: manifest.reason,
+        bytes: fs::metadata(path.join(DATABASE_FILE)).map(|m| m.len()).unwrap_or(0),
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<BackupManifest, String> {
+    let json = fs::read_to_string(path.join(MANIFEST_FILE))
+        .map_err(|_| format!("{} is not a CopyGum backup", path.display()))?;
+    let manifest: BackupManifest =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid backup manifest: {}", e))?;
+    if manifest.format != BACKUP_FORMAT {
+        return Err(format!("{} is not a CopyGum backup", path.display()));
+    }
+    if manifest.version > BACKUP_VERSION {
+        return Err(format!("Backup version {} is newer than this version of CopyGum supports", manifest.version));
+    }
+    Ok(manifest)
+}
+
+/// Snapshots in `backups`, newest first
+pub fn list_backups(backups: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = fs::read_dir(backups) else {
+        return Vec::new();
+    };
+    let mut found: Vec<BackupInfo> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(BACKUP_PREFIX))
+        })
+        .filter_map(|path| read_manifest(&path).ok().map(|manifest| backup_info(&path, manifest)))
+        .collect();
+    found.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    found
+}
+
+/// Delete all but the newest `keep` scheduled snapshots, returning the
+/// removed paths. Manual and pre-restore snapshots are left for the user.
+pub fn rotate(backups: &Path, keep: usize) -> Vec<String> {
+    let mut removed = Vec::new();
+    let scheduled = list_backups(backups).into_iter().filter(|b| b.reason == "scheduled");
+    for old in scheduled.skip(keep.max(1)) {
+        match fs::remove_dir_all(&old.path) {
+            Ok(()) => removed.push(old.path),
+            Err(e) => println!("[CopyGum] Failed to remove old backup {}: {}", old.path, e),
+        }
+    }
+    removed
+}
+
+// ============================================
+// RESTORING
+// ============================================
+
+/// Highest migration applied by tauri-plugin-sql (sqlx), if any
+fn schema_version(conn: &Connection) -> Result<Option<i64>, String> {
+    let has_table: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+    if !has_table {
+        return Ok(None);
+    }
+    conn.query_row("SELECT MAX(version) FROM _sqlx_migrations", [], |row| row.get(0))
+        .optional()
+        .map(Option::flatten)
+        .map_err(|e| format!("Failed to read schema version: {}", e))
+}
+
+/// Check a snapshot can be restored over the database at `live_db`
+pub fn validate_backup(path: &Path, live_db: &Path) -> Result<BackupManifest, String> {
+    let manifest = read_manifest(path)?;
+    let snapshot = items::open_read_only(&path.join(DATABASE_FILE))?;
+
+    let integrity: String = snapshot
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to check backup: {}", e))?;
+    if integrity != "ok" {
+        return Err(format!("Backup database is damaged: {}", integrity));
+    }
+    snapshot
+        .query_row("SELECT COUNT(*) FROM clipboard_items", [], |row| row.get::<_, i64>(0))
+        .map_err(|_| "Backup database has no clipboard history".to_string())?;
+
+    // An older app can't run against a schema migrated by a newer one
+    if live_db.exists() {
+        let live = items::open_read_only(live_db)?;
+        if let (Some(backup), Some(current)) = (schema_version(&snapshot)?, schema_version(&live)?) {
+            if backup > current {
+                return Err("Backup was made by a newer version of CopyGum".to_string());
+            }
+        }
+    }
+    Ok(manifest)
+}
+
+/// Validate a snapshot, save the current state, then copy the snapshot in
+/// The database is written through SQLite's backup API, so the app's own
+/// connections see the restored data without reopening the file.
+pub fn restore(
+    path: &Path,
+    db_path: &Path,
+    settings_file: &Path,
+    images: &Path,
+    backups: &Path,
+) -> Result<RestoreReport, String> {
+    let manifest = validate_backup(path, db_path)?;
+    let safety = create_backup(db_path, settings_file, images, backups, "pre-restore")?;
+
+    let snapshot = items::open_read_only(&path.join(DATABASE_FILE))?;
+    let mut live = Connection::open(db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+    copy_database(&snapshot, &mut live)?;
+
+    let images_restored = link_or_copy_files(&path.join(IMAGES_DIR), images)?;
+    let backed_up_settings = path.join(SETTINGS_FILE);
+    if backed_up_settings.is_file() {
+        fs::copy(&backed_up_settings, settings_file).map_err(|e| format!("Failed to restore settings: {}", e))?;
+    }
+
+    Ok(RestoreReport {
+        item_count: manifest.item_count,
+        images_restored,
+        safety_backup: safety.path,
+    })
+}
+
+// ============================================
+// SCHEDULER
+// ============================================
+
+/// Take a snapshot now and rotate old ones, using the current settings
+fn run_backup(reason: &str) -> Result<BackupInfo, String> {
+    let settings = AppSettings::load_from(&settings_path()?)?;
+    let backups = backup_dir(&settings)?;
+    let info = create_backup(&database_path()?, &settings_path()?, &images_dir()?, &backups, reason)?;
+    let removed = rotate(&backups, settings.backup_keep as usize);
+    println!(
+        "[CopyGum] Backed up {} items to {} ({} old backups removed)",
+        info.item_count,
+        info.path,
+        removed.len()
+    );
+    Ok(info)
+}
+
+fn backup_if_due() -> Result<(), String> {
+    let settings = AppSettings::load_from(&settings_path()?)?;
+    let newest = list_backups(&backup_dir(&settings)?).first().map(|b| b.created_at);
+    if is_due(&settings, newest, items::now_millis()) {
+        run_backup("scheduled")?;
+    }
+    Ok(())
+}
+
+/// Start the background thread that takes scheduled backups
+pub fn start_scheduler() {
+    thread::spawn(|| {
+        thread::sleep(STARTUP_DELAY);
+        loop {
+            if let Err(e) = backup_if_due() {
+                println!("[CopyGum] Scheduled backup failed: {}", e);
+            }
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
+/// Take a backup right away
+#[tauri::command]
+pub async fn create_backup_now() -> Result<BackupInfo, String> {
+    tauri::async_runtime::spawn_blocking(|| run_backup("manual"))
+        .await
+        .map_err(|e| format!("Backup failed: {}", e))?
+}
+
+/// List snapshots in the configured backup directory, newest first
+#[tauri::command]
+pub async fn list_backup_snapshots() -> Result<Vec<BackupInfo>, String> {
+    let settings = AppSettings::load_from(&settings_path()?)?;
+    Ok(list_backups(&backup_dir(&settings)?))
+}
+
+/// Validate a snapshot and restore it over the current history
+#[tauri::command]
+pub async fn restore_backup(app: AppHandle, backup_path: String) -> Result<RestoreReport, String> {
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let settings = AppSettings::load_from(&settings_path()?)?;
+        restore(
+            Path::new(&backup_path),
+            &database_path()?,
+            &settings_path()?,
+            &images_dir()?,
+            &backup_dir(&settings)?,
+        )
+    })
+    .await
+    .map_err(|e| format!("Restore failed: {}", e))??;
+
+    println!("[CopyGum] Restored backup with {} items", report.item_count);
+    let _ = app.emit("items-changed", ());
+    if let Ok(settings) = AppSettings::load_from(&settings_path()?) {
+        let _ = app.emit("settings-changed", &settings);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::DatabaseName;
+
+    struct Fixture {
+        _dir: tempfile::TempDir,
+        db: PathBuf,
+        settings: PathBuf,
+        images: PathBuf,
+        backups: PathBuf,
+    }
+
+    fn fixture() -> Fixture {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("copygum.db");
+        let conn = items::test_connection();
+        items::add_text_item(&conn, "first", "text", "text", None).unwrap();
+        conn.backup(DatabaseName::Main, &db, None).unwrap();
+
+        let settings = dir.path().join("settings.json");
+        fs::write(&settings, r#"{"theme":"dark"}"#).unwrap();
+        let images = dir.path().join("images");
+        fs::create_dir_all(&images).unwrap();
+        fs::write(images.join("1_1000.png"), b"png").unwrap();
+
+        let backups = dir.path().join("backups");
+        Fixture { db, settings, images, backups, _dir: dir }
+    }
+
+    fn item_count(db: &Path) -> i64 {
+        Connection::open(db)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM clipboard_items", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_contains_database_settings_and_images() {
+        let f = fixture();
+        let info = create_backup(&f.db, &f.settings, &f.images, &f.backups, "manual").unwrap();
+        let path = PathBuf::from(&info.path);
+
+        assert_eq!(info.item_count, 1);
+        assert!(info.name.starts_with(BACKUP_PREFIX));
+        assert_eq!(item_count(&path.join(DATABASE_FILE)), 1);
+        assert_eq!(fs::read(path.join(IMAGES_DIR).join("1_1000.png")).unwrap(), b"png");
+        assert!(path.join(SETTINGS_FILE).is_file());
+        assert!(validate_backup(&path, &f.db).is_ok());
+    }
+
+    #[test]
+    fn test_rotation_keeps_newest_generations() {
+        let f = fixture();
+        for _ in 0..4 {
+            create_backup(&f.db, &f.settings, &f.images, &f.backups, "scheduled").unwrap();
+        }
+        let newest = list_backups(&f.backups)[0].path.clone();
+        assert_eq!(rotate(&f.backups, 2).len(), 2);
+        let left = list_backups(&f.backups);
+        assert_eq!(left.len(), 2);
+        assert_eq!(left[0].path, newest);
+    }
+
+    #[test]
+    fn test_rotation_keeps_manual_and_safety_snapshots() {
+        let f = fixture();
+        let manual = create_backup(&f.db, &f.settings, &f.images, &f.backups, "manual").unwrap();
+        let safety = create_backup(&f.db, &f.settings, &f.images, &f.backups, "pre-restore").unwrap();
+        for _ in 0..3 {
+            create_backup(&f.db, &f.settings, &f.images, &f.backups, "scheduled").unwrap();
+        }
+        assert_eq!(rotate(&f.backups, 1).len(), 2);
+        let left: Vec<String> = list_backups(&f.backups).into_iter().map(|b| b.path).collect();
+        assert_eq!(left.len(), 3);
+        assert!(left.contains(&manual.path) && left.contains(&safety.path));
+    }
+
+    #[test]
+    fn test_restore_swaps_in_snapshot_and_keeps_safety_copy() {
+        let f = fixture();
+        let info = create_backup(&f.db, &f.settings, &f.images, &f.backups, "manual").unwrap();
+
+        let conn = items::open_read_write(&f.db).unwrap();
+        items::add_text_item(&conn, "added later", "text", "text", None).unwrap();
+        drop(conn);
+        fs::remove_file(f.images.join("1_1000.png")).unwrap();
+        assert_eq!(item_count(&f.db), 2);
+
+        let report = restore(Path::new(&info.path), &f.db, &f.settings, &f.images, &f.backups).unwrap();
+        assert_eq!(item_count(&f.db), 1);
+        assert_eq!(report.images_restored, 1);
+        assert!(f.images.join("1_1000.png").is_file());
+        assert_eq!(item_count(&Path::new(&report.safety_backup).join(DATABASE_FILE)), 2);
+    }
+
+    #[test]
+    fn test_damaged_snapshots_are_rejected() {
+        let f = fixture();
+        let info = create_backup(&f.db, &f.settings, &f.images, &f.backups, "manual").unwrap();
+        let path = PathBuf::from(&info.path);
+        fs::write(path.join(DATABASE_FILE), b"not a database").unwrap();
+        assert!(validate_backup(&path, &f.db).is_err());
+        assert!(validate_backup(&f.backups, &f.db).is_err());
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut settings = AppSettings::default();
+        let now = 100 * DAY_MS;
+        assert!(!is_due(&settings, None, now));
+
+        settings.backup_enabled = true;
+        assert!(is_due(&settings, None, now));
+        assert!(!is_due(&settings, Some(now - DAY_MS / 2), now));
+        assert!(is_due(&settings, Some(now - DAY_MS), now));
+
+        settings.backup_frequency = "weekly".to_string();
+        assert!(!is_due(&settings, Some(now - 3 * DAY_MS), now));
+        assert!(is_due(&settings, Some(now - 7 * DAY_MS), now));
+    }
+}
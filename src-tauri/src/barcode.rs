@@ -0,0 +1,686 @@
+// Barcode Module - Finds QR codes and 1D barcodes in captured images
+// Screenshots of Wi-Fi cards, 2FA enrollment pages and product labels carry
+// their useful part in a code; image_handler.rs decodes it at capture:
+// - QR codes with rqrr
+// - EAN-13 / UPC-A, EAN-8, Code 128 and Code 39 from horizontal scanlines
+//   (screenshots are upright and sharp, so there is no rotation or deskewing)
+// - a 1D result only counts when several scanlines agree, so lines of text
+//   aren't read as bars
+// The decoded text becomes the image's secondary text flavor, typed by
+// content_detector.rs like anything else that is copied.
+
+use image::{DynamicImage, GenericImageView, GrayImage, Luma};
+use serde::Serialize;
+
+use crate::content_detector::detect_content_type;
+
+/// Larger images aren't scanned (in pixels)
+const MAX_SCAN_PIXELS: u64 = 4096 * 4096;
+/// Horizontal lines sampled for 1D barcodes
+const SCANLINES: u32 = 48;
+/// Scanlines that must agree on a 1D result
+const MIN_AGREEING_LINES: usize = 2;
+/// Gray levels between the lightest and darkest pixel of a scanline with bars
+const MIN_CONTRAST: u8 = 48;
+/// Light space required in front of a barcode, in modules (narrow bars)
+const QUIET_ZONE_MODULES: f32 = 5.0;
+/// Largest average deviation of a bar or space from its pattern, in modules
+const MAX_MODULE_DEVIATION: f32 = 0.4;
+
+/// Kind of code a payload was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodeFormat {
+    QrCode,
+    Ean13,
+    UpcA,
+    Ean8,
+    Code128,
+    Code39,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedCode {
+    pub format: CodeFormat,
+    pub text: String,
+}
+
+/// Every code found in the image, QR codes first
+pub fn decode_codes(img: &DynamicImage) -> Vec<DecodedCode> {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 || width as u64 * height as u64 > MAX_SCAN_PIXELS {
+        return Vec::new();
+    }
+    let luma = to_luma_on_white(img);
+
+    let mut codes: Vec<DecodedCode> = decode_qr(&luma)
+        .into_iter()
+        .map(|text| DecodedCode { format: CodeFormat::QrCode, text })
+        .collect();
+    for code in decode_linear(&luma) {
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    codes
+}
+
+/// The image's text flavor: the decoded payloads (one per line) and the
+/// content type of the first, as stored in content_type ("links", "apikey", ...)
+pub fn text_flavor(codes: &[DecodedCode]) -> Option<(String, &'static str)> {
+    let first = codes.first()?;
+    let mut texts: Vec<&str> = Vec::new();
+    for code in codes {
+        if !texts.contains(&code.text.as_str()) {
+            texts.push(&code.text);
+        }
+    }
+    Some((texts.join("\n"), detect_content_type(&first.text).as_str()))
+}
+
+/// Grayscale with transparent pixels treated as white, as they are shown
+fn to_luma_on_white(img: &DynamicImage) -> GrayImage {
+    if !img.color().has_alpha() {
+        return img.to_luma8();
+    }
+    let rgba = img.to_rgba8();
+    GrayImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+        let alpha = a as f32 / 255.0;
+        Luma([(luma * alpha + 255.0 * (1.0 - alpha)).round() as u8])
+    })
+}
+
+// ============================================
+// QR CODES
+// ============================================
+
+fn decode_qr(luma: &GrayImage) -> Vec<String> {
+    let (width, height) = luma.dimensions();
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(width as usize, height as usize, |x, y| {
+        luma.get_pixel(x as u32, y as u32)[0]
+    });
+    prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| grid.decode().ok())
+        .map(|(_, text)| text)
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+// ============================================
+// 1D BARCODES
+// ============================================
+
+/// Decode every scanline and keep results found on enough of them
+fn decode_linear(luma: &GrayImage) -> Vec<DecodedCode> {
+    let (width, height) = luma.dimensions();
+    let lines = SCANLINES.min(height);
+    let mut found: Vec<(DecodedCode, usize)> = Vec::new();
+
+    for line in 0..lines {
+        // Middle of each of `lines` equal bands
+        let y = (line * 2 + 1) * height / (lines * 2);
+        let row: Vec<u8> = (0..width).map(|x| luma.get_pixel(x, y)[0]).collect();
+        let Some(runs) = run_lengths(&row) else {
+            continue;
+        };
+        let mut line_codes = decode_runs(&runs);
+        line_codes.extend(decode_runs(&reversed(&runs)));
+        line_codes.dedup();
+
+        for code in line_codes {
+            match found.iter_mut().find(|(known, _)| *known == code) {
+                Some((_, count)) => *count += 1,
+                None => found.push((code, 1)),
+            }
+        }
+    }
+
+    let needed = MIN_AGREEING_LINES.min(lines as usize);
+    found
+        .into_iter()
+        .filter(|(_, count)| *count >= needed)
+        .map(|(code, _)| code)
+        .collect()
+}
+
+/// Widths of alternating light and dark runs, starting with light (possibly
+/// 0 wide), so dark runs sit at odd indices; None without enough contrast
+fn run_lengths(row: &[u8]) -> Option<Vec<u32>> {
+    let min = *row.iter().min()?;
+    let max = *row.iter().max()?;
+    if max - min < MIN_CONTRAST {
+        return None;
+    }
+    let threshold = min + (max - min) / 2;
+
+    let mut runs = vec![0u32];
+    let mut dark = false;
+    for &value in row {
+        let is_dark = value < threshold;
+        if is_dark != dark {
+            runs.push(0);
+            dark = is_dark;
+        }
+        *runs.last_mut().unwrap() += 1;
+    }
+    Some(runs)
+}
+
+/// The same runs read right to left, still starting with light
+fn reversed(runs: &[u32]) -> Vec<u32> {
+    let mut reversed: Vec<u32> = runs.iter().rev().copied().collect();
+    // An even count means the row ended dark
+    if runs.len().is_multiple_of(2) {
+        reversed.insert(0, 0);
+    }
+    reversed
+}
+
+fn decode_runs(runs: &[u32]) -> Vec<DecodedCode> {
+    let mut codes = Vec::new();
+    for start in (1..runs.len()).step_by(2) {
+        let code = decode_ean(runs, start)
+            .or_else(|| decode_code128(runs, start).map(|text| DecodedCode { format: CodeFormat::Code128, text }))
+            .or_else(|| decode_code39(runs, start).map(|text| DecodedCode { format: CodeFormat::Code39, text }));
+        if let Some(code) = code {
+            codes.push(code);
+        }
+    }
+    codes
+}
+
+/// Average distance of `runs` from `pattern`, in modules
+fn deviation(runs: &[u32], pattern: &[u8], module: f32) -> f32 {
+    let total: f32 = runs
+        .iter()
+        .zip(pattern)
+        .map(|(&run, &modules)| (run as f32 - modules as f32 * module).abs())
+        .sum();
+    total / (runs.len() as f32 * module)
+}
+
+/// Index of the pattern closest to `runs` (each pattern `modules` wide)
+fn best_match<P: AsRef<[u8]>>(runs: &[u32], patterns: &[P], modules: u32) -> Option<usize> {
+    let module = runs.iter().sum::<u32>() as f32 / modules as f32;
+    if module <= 0.0 {
+        return None;
+    }
+    patterns
+        .iter()
+        .map(|pattern| deviation(runs, pattern.as_ref(), module))
+        .enumerate()
+        .filter(|(_, deviation)| *deviation < MAX_MODULE_DEVIATION)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+/// Whether the light run before `start` is wide enough to be a quiet zone
+fn has_quiet_zone(runs: &[u32], start: usize, module: f32) -> bool {
+    // Codes touching the left edge of the image are accepted
+    (start == 1 && runs[0] == 0) || runs[start - 1] as f32 >= QUIET_ZONE_MODULES * module
+}
+
+// EAN / UPC ----------------------------------
+
+/// L-code digit widths (space, bar, space, bar); G codes are these mirrored,
+/// R codes the same widths starting with a bar
+const EAN_DIGITS: [[u8; 4]; 10] = [
+    [3, 2, 1, 1],
+    [2, 2, 2, 1],
+    [2, 1, 2, 2],
+    [1, 4, 1, 1],
+    [1, 1, 3, 2],
+    [1, 2, 3, 1],
+    [1, 1, 1, 4],
+    [1, 3, 1, 2],
+    [1, 2, 1, 3],
+    [3, 1, 1, 2],
+];
+/// EAN-13 first digit, from which left-half digits use G codes (bit 5 = first)
+const EAN_FIRST_DIGIT_PARITY: [u8; 10] = [0x00, 0x0B, 0x0D, 0x0E, 0x13, 0x19, 0x1C, 0x15, 0x16, 0x1A];
+
+fn ean_patterns(with_g: bool) -> Vec<[u8; 4]> {
+    let mut patterns = EAN_DIGITS.to_vec();
+    if with_g {
+        patterns.extend(EAN_DIGITS.iter().map(|digit| [digit[3], digit[2], digit[1], digit[0]]));
+    }
+    patterns
+}
+
+/// Check digit rule shared by EAN-13, EAN-8 and UPC-A
+fn ean_checksum_ok(digits: &[u8]) -> bool {
+    let Some((&check, body)) = digits.split_last() else {
+        return false;
+    };
+    let sum: u32 = body
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| digit as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    (10 - sum % 10) % 10 == check as u32
+}
+
+/// EAN-13 (reported as UPC-A when it starts with 0) or EAN-8 starting at `start`
+fn decode_ean(runs: &[u32], start: usize) -> Option<DecodedCode> {
+    decode_ean_digits(runs, start, 6).or_else(|| decode_ean_digits(runs, start, 4))
+}
+
+fn decode_ean_digits(runs: &[u32], start: usize, half: usize) -> Option<DecodedCode> {
+    // Guard, digits, middle guard, digits, guard
+    let end = start + 3 + 4 * half + 5 + 4 * half + 3;
+    if end > runs.len() {
+        return None;
+    }
+    let module = runs[start..start + 3].iter().sum::<u32>() as f32 / 3.0;
+    if deviation(&runs[start..start + 3], &[1, 1, 1], module) >= MAX_MODULE_DEVIATION
+        || !has_quiet_zone(runs, start, module)
+    {
+        return None;
+    }
+
+    let left_patterns = ean_patterns(half == 6);
+    let right_patterns = ean_patterns(false);
+    let read_digit = |pos: usize, patterns: &[[u8; 4]]| -> Option<usize> {
+        let digit_runs = &runs[pos..pos + 4];
+        // Every digit is 7 modules; reject digits of the wrong size
+        let digit_module = digit_runs.iter().sum::<u32>() as f32 / 7.0;
+        if (digit_module - module).abs() > module * 0.3 {
+            return None;
+        }
+        best_match(digit_runs, patterns, 7)
+    };
+
+    let mut digits = Vec::with_capacity(2 * half + 1);
+    let mut parity = 0u8;
+    let mut pos = start + 3;
+    for _ in 0..half {
+        let index = read_digit(pos, &left_patterns)?;
+        parity = (parity << 1) | (index >= 10) as u8;
+        digits.push((index % 10) as u8);
+        pos += 4;
+    }
+    if deviation(&runs[pos..pos + 5], &[1, 1, 1, 1, 1], module) >= MAX_MODULE_DEVIATION {
+        return None;
+    }
+    pos += 5;
+    for _ in 0..half {
+        digits.push(read_digit(pos, &right_patterns)? as u8);
+        pos += 4;
+    }
+    if deviation(&runs[pos..pos + 3], &[1, 1, 1], module) >= MAX_MODULE_DEVIATION {
+        return None;
+    }
+    let after = pos + 3;
+    if after < runs.len() && (runs[after] as f32) < QUIET_ZONE_MODULES * module {
+        return None;
+    }
+
+    let format = if half == 6 {
+        let first = EAN_FIRST_DIGIT_PARITY.iter().position(|&p| p == parity)?;
+        digits.insert(0, first as u8);
+        CodeFormat::Ean13
+    } else if parity == 0 {
+        CodeFormat::Ean8
+    } else {
+        return None;
+    };
+    if !ean_checksum_ok(&digits) {
+        return None;
+    }
+
+    let text: String = digits.iter().map(|d| char::from(b'0' + d)).collect();
+    match text.strip_prefix('0') {
+        Some(upc) if format == CodeFormat::Ean13 => Some(DecodedCode { format: CodeFormat::UpcA, text: upc.to_string() }),
+        _ => Some(DecodedCode { format, text }),
+    }
+}
+
+// Code 128 -----------------------------------
+
+/// Bar and space widths of symbol values 0-105 (103-105 are the start codes)
+const CODE128_PATTERNS: [[u8; 6]; 106] = [
+    [2, 1, 2, 2, 2, 2], [2, 2, 2, 1, 2, 2], [2, 2, 2, 2, 2, 1], [1, 2, 1, 2, 2, 3], [1, 2, 1, 3, 2, 2],
+    [1, 3, 1, 2, 2, 2], [1, 2, 2, 2, 1, 3], [1, 2, 2, 3, 1, 2], [1, 3, 2, 2, 1, 2], [2, 2, 1, 2, 1, 3],
+    [2, 2, 1, 3, 1, 2], [2, 3, 1, 2, 1, 2], [1, 1, 2, 2, 3, 2], [1, 2, 2, 1, 3, 2], [1, 2, 2, 2, 3, 1],
+    [1, 1, 3, 2, 2, 2], [1, 2, 3, 1, 2, 2], [1, 2, 3, 2, 2, 1], [2, 2, 3, 2, 1, 1], [2, 2, 1, 1, 3, 2],
+    [2, 2, 1, 2, 3, 1], [2, 1, 3, 2, 1, 2], [2, 2, 3, 1, 1, 2], [3, 1, 2, 1, 3, 1], [3, 1, 1, 2, 2, 2],
+    [3, 2, 1, 1, 2, 2], [3, 2, 1, 2, 2, 1], [3, 1, 2, 2, 1, 2], [3, 2, 2, 1, 1, 2], [3, 2, 2, 2, 1, 1],
+    [2, 1, 2, 1, 2, 3], [2, 1, 2, 3, 2, 1], [2, 3, 2, 1, 2, 1], [1, 1, 1, 3, 2, 3], [1, 3, 1, 1, 2, 3],
+    [1, 3, 1, 3, 2, 1], [1, 1, 2, 3, 1, 3], [1, 3, 2, 1, 1, 3], [1, 3, 2, 3, 1, 1], [2, 1, 1, 3, 1, 3],
+    [2, 3, 1, 1, 1, 3], [2, 3, 1, 3, 1, 1], [1, 1, 2, 1, 3, 3], [1, 1, 2, 3, 3, 1], [1, 3, 2, 1, 3, 1],
+    [1, 1, 3, 1, 2, 3], [1, 1, 3, 3, 2, 1], [1, 3, 3, 1, 2, 1], [3, 1, 3, 1, 2, 1], [2, 1, 1, 3, 3, 1],
+    [2, 3, 1, 1, 3, 1], [2, 1, 3, 1, 1, 3], [2, 1, 3, 3, 1, 1], [2, 1, 3, 1, 3, 1], [3, 1, 1, 1, 2, 3],
+    [3, 1, 1, 3, 2, 1], [3, 3, 1, 1, 2, 1], [3, 1, 2, 1, 1, 3], [3, 1, 2, 3, 1, 1], [3, 3, 2, 1, 1, 1],
+    [3, 1, 4, 1, 1, 1], [2, 2, 1, 4, 1, 1], [4, 3, 1, 1, 1, 1], [1, 1, 1, 2, 2, 4], [1, 1, 1, 4, 2, 2],
+    [1, 2, 1, 1, 2, 4], [1, 2, 1, 4, 2, 1], [1, 4, 1, 1, 2, 2], [1, 4, 1, 2, 2, 1], [1, 1, 2, 2, 1, 4],
+    [1, 1, 2, 4, 1, 2], [1, 2, 2, 1, 1, 4], [1, 2, 2, 4, 1, 1], [1, 4, 2, 1, 1, 2], [1, 4, 2, 2, 1, 1],
+    [2, 4, 1, 2, 1, 1], [2, 2, 1, 1, 1, 4], [4, 1, 3, 1, 1, 1], [2, 4, 1, 1, 1, 2], [1, 3, 4, 1, 1, 1],
+    [1, 1, 1, 2, 4, 2], [1, 2, 1, 1, 4, 2], [1, 2, 1, 2, 4, 1], [1, 1, 4, 2, 1, 2], [1, 2, 4, 1, 1, 2],
+    [1, 2, 4, 2, 1, 1], [4, 1, 1, 2, 1, 2], [4, 2, 1, 1, 1, 2], [4, 2, 1, 2, 1, 1], [2, 1, 2, 1, 4, 1],
+    [2, 1, 4, 1, 2, 1], [4, 1, 2, 1, 2, 1], [1, 1, 1, 1, 4, 3], [1, 1, 1, 3, 4, 1], [1, 3, 1, 1, 4, 1],
+    [1, 1, 4, 1, 1, 3], [1, 1, 4, 3, 1, 1], [4, 1, 1, 1, 1, 3], [4, 1, 1, 3, 1, 1], [1, 1, 3, 1, 4, 1],
+    [1, 1, 4, 1, 3, 1], [3, 1, 1, 1, 4, 1], [4, 1, 1, 1, 3, 1], [2, 1, 1, 4, 1, 2], [2, 1, 1, 2, 1, 4],
+    [2, 1, 1, 2, 3, 2],
+];
+const CODE128_STOP: [u8; 7] = [2, 3, 3, 1, 1, 1, 2];
+const CODE128_START_A: usize = 103;
+const CODE128_START_B: usize = 104;
+/// Longest symbol run considered (far beyond what fits on a screen)
+const CODE128_MAX_SYMBOLS: usize = 128;
+
+fn decode_code128(runs: &[u32], start: usize) -> Option<String> {
+    if start + 6 > runs.len() {
+        return None;
+    }
+    let module = runs[start..start + 6].iter().sum::<u32>() as f32 / 11.0;
+    if !has_quiet_zone(runs, start, module) {
+        return None;
+    }
+    let first = best_match(&runs[start..start + 6], &CODE128_PATTERNS, 11)?;
+    if first < CODE128_START_A {
+        return None;
+    }
+
+    let mut values = vec![first];
+    let mut pos = start + 6;
+    loop {
+        if pos + 7 <= runs.len() && best_match(&runs[pos..pos + 7], &[CODE128_STOP], 13).is_some() {
+            break;
+        }
+        if pos + 6 > runs.len() || values.len() > CODE128_MAX_SYMBOLS {
+            return None;
+        }
+        let value = best_match(&runs[pos..pos + 6], &CODE128_PATTERNS, 11)?;
+        if value >= CODE128_START_A {
+            return None;
+        }
+        values.push(value);
+        pos += 6;
+    }
+
+    // Start, at least one symbol, check symbol
+    if values.len() < 3 {
+        return None;
+    }
+    let check = values.pop()?;
+    let sum: usize = values[0] + values.iter().enumerate().skip(1).map(|(i, v)| i * v).sum::<usize>();
+    if sum % 103 != check {
+        return None;
+    }
+    code128_text(values[0], &values[1..])
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+/// Text of Code 128 symbol values, following code set switches
+fn code128_text(start: usize, values: &[usize]) -> Option<String> {
+    let mut set = match start {
+        CODE128_START_A => CodeSet::A,
+        CODE128_START_B => CodeSet::B,
+        _ => CodeSet::C,
+    };
+    let mut shifted = false;
+    let mut text = String::new();
+
+    for (i, &value) in values.iter().enumerate() {
+        let current = match (shifted, set) {
+            (true, CodeSet::A) => CodeSet::B,
+            (true, CodeSet::B) => CodeSet::A,
+            _ => set,
+        };
+        shifted = false;
+        match (current, value) {
+            // FNC1 first marks GS1 data; later it separates fields
+            (_, 102) if i > 0 => text.push('\u{1d}'),
+            (_, 102) => {}
+            (CodeSet::C, 0..=99) => text.push_str(&format!("{:02}", value)),
+            (CodeSet::C, 100) | (CodeSet::A, 100) => set = CodeSet::B,
+            (CodeSet::C, 101) | (CodeSet::B, 101) => set = CodeSet::A,
+            (CodeSet::C, _) => return None,
+            (_, 0..=63) => text.push(char::from(value as u8 + 32)),
+            (CodeSet::A, 64..=95) => text.push(char::from(value as u8 - 64)),
+            (CodeSet::B, 64..=95) => text.push(char::from(value as u8 + 32)),
+            (_, 98) => shifted = true,
+            (_, 99) => set = CodeSet::C,
+            // FNC2, FNC3 and FNC4 (reader instructions, extended ASCII) aren't used
+            _ => {}
+        }
+    }
+    Some(text)
+}
+
+// Code 39 ------------------------------------
+
+const CODE39_ALPHABET: &[u8; 43] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%";
+/// Wide elements of each character, first bar = bit 8
+const CODE39_ENCODINGS: [u16; 43] = [
+    0x034, 0x121, 0x061, 0x160, 0x031, 0x130, 0x070, 0x025, 0x124, 0x064, // 0-9
+    0x109, 0x049, 0x148, 0x019, 0x118, 0x058, 0x00D, 0x10C, 0x04C, 0x01C, // A-J
+    0x103, 0x043, 0x142, 0x013, 0x112, 0x052, 0x007, 0x106, 0x046, 0x016, // K-T
+    0x181, 0x0C1, 0x1C0, 0x091, 0x190, 0x0D0, 0x085, 0x184, 0x0C4, 0x0A8, // U-$
+    0x0A2, 0x08A, 0x02A, // / + %
+];
+/// The start/stop character '*'
+const CODE39_ASTERISK: u16 = 0x094;
+const CODE39_MAX_CHARS: usize = 64;
+
+/// One character from 9 runs (5 bars, 4 spaces, 3 of them wide)
+fn code39_char(runs: &[u32]) -> Option<u8> {
+    let mut sorted = runs.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    let (wide, narrow) = (sorted[2], sorted[3]);
+    if (wide as f32) < narrow as f32 * 1.5 {
+        return None;
+    }
+    let bits = runs
+        .iter()
+        .enumerate()
+        .filter(|(_, &run)| run >= wide)
+        .fold(0u16, |bits, (i, _)| bits | 1 << (8 - i));
+    if bits == CODE39_ASTERISK {
+        return Some(b'*');
+    }
+    CODE39_ENCODINGS
+        .iter()
+        .position(|&encoding| encoding == bits)
+        .map(|i| CODE39_ALPHABET[i])
+}
+
+fn decode_code39(runs: &[u32], start: usize) -> Option<String> {
+    if start + 9 > runs.len() || code39_char(&runs[start..start + 9])? != b'*' {
+        return None;
+    }
+    let mut sorted = runs[start..start + 9].to_vec();
+    sorted.sort_unstable();
+    let narrow = sorted[..6].iter().sum::<u32>() as f32 / 6.0;
+    if !has_quiet_zone(runs, start, narrow) {
+        return None;
+    }
+
+    let mut text = String::new();
+    let mut pos = start + 9;
+    loop {
+        // Narrow gap between characters
+        if pos + 10 > runs.len() || runs[pos] as f32 > narrow * 3.0 || text.len() > CODE39_MAX_CHARS {
+            return None;
+        }
+        let c = code39_char(&runs[pos + 1..pos + 10])?;
+        pos += 10;
+        if c == b'*' {
+            break;
+        }
+        text.push(char::from(c));
+    }
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    /// White image with the modules (true = bar) drawn as vertical bars
+    fn render(modules: &[bool], module_px: u32) -> DynamicImage {
+        let quiet = 10;
+        let width = (modules.len() as u32 + 2 * quiet) * module_px;
+        let img = GrayImage::from_fn(width, 40, |x, y| {
+            let module = (x / module_px) as usize;
+            let bar = (5..35).contains(&y)
+                && module >= quiet as usize
+                && modules.get(module - quiet as usize).copied().unwrap_or(false);
+            Luma([if bar { 20 } else { 240 }])
+        });
+        DynamicImage::ImageLuma8(img)
+    }
+
+    /// Modules from run widths, alternating bar/space starting with `bar`
+    fn push_runs(modules: &mut Vec<bool>, widths: &[u8], mut bar: bool) {
+        for &width in widths {
+            modules.extend(std::iter::repeat_n(bar, width as usize));
+            bar = !bar;
+        }
+    }
+
+    fn ean13(code: &str) -> Vec<bool> {
+        let digits: Vec<usize> = code.bytes().map(|b| (b - b'0') as usize).collect();
+        let parity = EAN_FIRST_DIGIT_PARITY[digits[0]];
+        let mut modules = Vec::new();
+        push_runs(&mut modules, &[1, 1, 1], true);
+        for (i, &digit) in digits[1..7].iter().enumerate() {
+            let mut widths = EAN_DIGITS[digit];
+            if parity & (1 << (5 - i)) != 0 {
+                widths.reverse();
+            }
+            push_runs(&mut modules, &widths, false);
+        }
+        push_runs(&mut modules, &[1, 1, 1, 1, 1], false);
+        for &digit in &digits[7..] {
+            push_runs(&mut modules, &EAN_DIGITS[digit], true);
+        }
+        push_runs(&mut modules, &[1, 1, 1], true);
+        modules
+    }
+
+    fn code128_b(text: &str) -> Vec<bool> {
+        let mut values = vec![CODE128_START_B];
+        values.extend(text.bytes().map(|b| (b - 32) as usize));
+        let check = values.iter().enumerate().map(|(i, v)| i.max(1) * v).sum::<usize>() % 103;
+        values.push(check);
+        let mut modules = Vec::new();
+        for value in values {
+            push_runs(&mut modules, &CODE128_PATTERNS[value], true);
+        }
+        push_runs(&mut modules, &CODE128_STOP, true);
+        modules
+    }
+
+    fn code39(text: &str) -> Vec<bool> {
+        let mut modules = Vec::new();
+        for (i, c) in format!("*{}*", text).bytes().enumerate() {
+            let bits = match c {
+                b'*' => CODE39_ASTERISK,
+                _ => CODE39_ENCODINGS[CODE39_ALPHABET.iter().position(|&a| a == c).unwrap()],
+            };
+            if i > 0 {
+                modules.push(false);
+            }
+            let widths: Vec<u8> = (0..9).map(|e| if bits & (1 << (8 - e)) != 0 { 3 } else { 1 }).collect();
+            push_runs(&mut modules, &widths, true);
+        }
+        modules
+    }
+
+    #[test]
+    fn test_pattern_tables() {
+        for pattern in CODE128_PATTERNS {
+            assert_eq!(pattern.iter().map(|&w| w as u32).sum::<u32>(), 11);
+        }
+        for (i, a) in CODE128_PATTERNS.iter().enumerate() {
+            assert!(!CODE128_PATTERNS[i + 1..].contains(a));
+        }
+        // Code 39: three wide elements, either two bars and a space or three spaces
+        for bits in CODE39_ENCODINGS.iter().chain([&CODE39_ASTERISK]) {
+            assert_eq!(bits.count_ones(), 3);
+            let wide_spaces = (bits & 0b010101010).count_ones();
+            assert!(wide_spaces == 1 || wide_spaces == 3);
+        }
+    }
+
+    #[test]
+    fn test_ean_and_upc() {
+        let codes = decode_codes(&render(&ean13("4006381333931"), 2));
+        assert_eq!(codes, vec![DecodedCode { format: CodeFormat::Ean13, text: "4006381333931".into() }]);
+
+        let codes = decode_codes(&render(&ean13("0036000291452"), 1));
+        assert_eq!(codes, vec![DecodedCode { format: CodeFormat::UpcA, text: "036000291452".into() }]);
+
+        // A wrong check digit is rejected
+        assert!(decode_codes(&render(&ean13("4006381333932"), 2)).is_empty());
+    }
+
+    #[test]
+    fn test_code128_and_code39() {
+        let codes = decode_codes(&render(&code128_b("CopyGum-42"), 2));
+        assert_eq!(codes, vec![DecodedCode { format: CodeFormat::Code128, text: "CopyGum-42".into() }]);
+
+        let codes = decode_codes(&render(&code39("COPYGUM 7"), 1));
+        assert_eq!(codes, vec![DecodedCode { format: CodeFormat::Code39, text: "COPYGUM 7".into() }]);
+        assert_eq!(code128_text(105, &[12, 34, 100, 33]), Some("1234A".to_string()));
+    }
+
+    #[test]
+    fn test_mirrored_and_transparent_images() {
+        let mut modules = code128_b("https://example.com");
+        modules.reverse();
+        let codes = decode_codes(&render(&modules, 2));
+        assert_eq!(codes[0].text, "https://example.com");
+
+        // Black bars on a transparent background, as some apps copy them
+        let opaque = render(&ean13("4006381333931"), 2).to_luma8();
+        let transparent = RgbaImage::from_fn(opaque.width(), opaque.height(), |x, y| {
+            if opaque.get_pixel(x, y)[0] < 128 { Rgba([0, 0, 0, 255]) } else { Rgba([0, 0, 0, 0]) }
+        });
+        let codes = decode_codes(&DynamicImage::ImageRgba8(transparent));
+        assert_eq!(codes[0].text, "4006381333931");
+    }
+
+    #[test]
+    fn test_text_is_not_read_as_bars() {
+        // Irregular strokes, like a line of small text
+        let mut seed = 7u32;
+        let img = GrayImage::from_fn(600, 40, |x, _| {
+            if x % 7 == 0 {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            }
+            Luma([if (seed >> 16).is_multiple_of(3) { 30 } else { 230 }])
+        });
+        assert!(decode_codes(&DynamicImage::ImageLuma8(img)).is_empty());
+    }
+
+    #[test]
+    fn test_qr_code_and_text_flavor() {
+        let qr = qrcode::QrCode::new(b"otpauth://totp/CopyGum:me?secret=JBSWY3DPEHPK3PXP").unwrap();
+        let img = qr.render::<Luma<u8>>().module_dimensions(4, 4).build();
+        let codes = decode_codes(&DynamicImage::ImageLuma8(img));
+        assert_eq!(codes[0].format, CodeFormat::QrCode);
+
+        let (text, content_type) = text_flavor(&codes).unwrap();
+        assert_eq!(text, "otpauth://totp/CopyGum:me?secret=JBSWY3DPEHPK3PXP");
+        assert_eq!(content_type, "apikey");
+
+        let url = DecodedCode { format: CodeFormat::QrCode, text: "https://example.com/wifi".into() };
+        let ean = DecodedCode { format: CodeFormat::Ean13, text: "4006381333931".into() };
+        assert_eq!(text_flavor(&[url.clone(), ean, url]).unwrap(), ("https://example.com/wifi\n4006381333931".to_string(), "links"));
+        assert!(text_flavor(&[]).is_none());
+    }
+}
@@ -0,0 +1,448 @@
+// CLI Module - `copygum <command>` for scripts and launchers
+// Reads open the history database read-only, so they work whether or not the
+// app is running; like exports, they leave out passwords, API keys and private
+// items unless given --include-sensitive. Changes (copy, pin, delete, pause)
+// are forwarded to the running instance over the single-instance socket, so
+// the CLI never writes the database itself. Output is JSON on stdout for
+// piping into jq or fzf.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::db::items::{self, HistoryStats, ItemFilter, ItemQuery, SENSITIVE_CATEGORIES};
+use crate::export::{self, ExportFormat};
+use crate::paths;
+use crate::settings::AppSettings;
+use crate::single_instance;
+
+const DEFAULT_LIMIT: usize = 100;
+
+const USAGE: &str = "Usage: copygum <command> [options]
+
+Commands:
+  list [--limit N] [FILTERS] [--jsonl]           List recent items
+  search <query> [--limit N] [FILTERS] [--jsonl] Search item content
+  get <id> [--include-sensitive]                 Show one item
+  copy <id>                                      Copy an item to the clipboard
+  pin <id> [--unpin]                             Pin or unpin an item
+  delete <id>                                    Move an item to the trash
+  pause [minutes]                                Pause monitoring (resume with --resume)
+  export --format json|csv|md|html [--output FILE] [FILTERS]
+                                                 Export history
+  stats                                          Show history statistics
+
+Filters:
+  --since DATE --until DATE (YYYY-MM-DD) --category NAME... --tag NAME...
+  --pinned --include-sensitive (passwords, API keys and private items)
+
+copy, pin, delete and pause need CopyGum running.
+Without a command, CopyGum starts (or shows the running instance).";
+
+/// Options shared by list, search and export
+const FILTER_FLAGS: &[&str] = &["--since", "--until", "--category", "--tag", "--pinned", "--include-sensitive"];
+
+/// A parsed CLI invocation
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    List { query: ItemQuery, jsonl: bool },
+    Search { text: String, query: ItemQuery, jsonl: bool },
+    Get { id: i64, include_sensitive: bool },
+    Copy { id: i64 },
+    Pin { id: i64, pinned: bool },
+    Delete { id: i64 },
+    Pause { minutes: Option<u32> },
+    Export { format: ExportFormat, output: Option<PathBuf>, filter: ItemFilter },
+    Stats,
+    Help,
+}
+
+#[derive(Serialize)]
+struct CliStats {
+    #[serde(flatten)]
+    history: HistoryStats,
+    history_limit: i32,
+    running: bool,
+}
+
+/// Whether the arguments name a CLI command rather than app flags
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    matches!(
+        args.first().map(String::as_str),
+        Some("list" | "search" | "get" | "copy" | "pin" | "delete" | "pause" | "export" | "stats" | "help")
+    )
+}
+
+/// Run a CLI command and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    attach_console();
+
+    match parse(args).and_then(|command| execute(&command)) {
+        Ok(output) => {
+            // Ignore write errors: `copygum list | head` closes the pipe early
+            if !output.is_empty() {
+                let _ = writeln!(std::io::stdout().lock(), "{}", output);
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("copygum: {}", e);
+            1
+        }
+    }
+}
+
+// ============================================
+// PARSING
+// ============================================
+
+/// Parse `args` (without the program name) into a command
+pub fn parse(args: &[String]) -> Result<CliCommand, String> {
+    let (name, rest) = args.split_first().ok_or_else(|| USAGE.to_string())?;
+    let mut positional = Vec::new();
+    let mut limit = None;
+    let mut categories = Vec::new();
+    let mut filter = ItemFilter::default();
+    let mut format = None;
+    let mut output = None;
+    let mut jsonl = false;
+    let mut unpin = false;
+    let mut flags = Vec::new();
+
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        // Values are taken by their flag below, so they never land here
+        if arg.starts_with("--") || arg == "-o" {
+            flags.push(arg.as_str());
+        }
+        let mut value = |flag: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+        match arg.as_str() {
+            "--limit" => limit = Some(parse_number::<usize>(&value("--limit")?, "limit")?),
+            "--category" => categories.push(value("--category")?),
+            "--tag" => filter.tags.push(value("--tag")?),
+            "--since" => filter.since = Some(parse_date(&value("--since")?)?),
+            "--until" => filter.until = Some(parse_date(&value("--until")?)?),
+            "--pinned" => filter.pinned_only = true,
+            "--include-sensitive" => filter.include_sensitive = true,
+            "--format" => format = Some(ExportFormat::parse(&value("--format")?)?),
+            "--output" | "-o" => output = Some(PathBuf::from(value("--output")?)),
+            "--jsonl" => jsonl = true,
+            "--unpin" => unpin = true,
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    // Reject options the command would otherwise ignore
+    let (allowed, takes_filters): (&[&str], bool) = match name.as_str() {
+        "list" | "search" => (&["--limit", "--jsonl"], true),
+        "export" => (&["--format", "--output", "-o"], true),
+        "get" => (&["--include-sensitive"], false),
+        "pin" => (&["--unpin"], false),
+        _ => (&[], false),
+    };
+    let accepts = |flag: &str| allowed.contains(&flag) || (takes_filters && FILTER_FLAGS.contains(&flag));
+    if let Some(flag) = flags.iter().find(|flag| !accepts(flag)) {
+        return Err(format!("{} doesn't take {}", name, flag));
+    }
+    let filter = ItemFilter { categories, ..filter };
+
+    let id = |positional: &[String]| -> Result<i64, String> {
+        match positional {
+            [id] => parse_number(id, "item id"),
+            _ => Err(format!("{} needs exactly one item id", name)),
+        }
+    };
+
+    Ok(match name.as_str() {
+        "list" => {
+            if !positional.is_empty() {
+                return Err("list takes no arguments; use search to look for text".to_string());
+            }
+            CliCommand::List {
+                query: ItemQuery { limit: Some(limit.unwrap_or(DEFAULT_LIMIT)), filter },
+                jsonl,
+            }
+        }
+        "search" => {
+            if positional.is_empty() {
+                return Err("search needs a query".to_string());
+            }
+            CliCommand::Search {
+                text: positional.join(" "),
+                query: ItemQuery { limit: Some(limit.unwrap_or(DEFAULT_LIMIT)), filter },
+                jsonl,
+            }
+        }
+        "get" => CliCommand::Get { id: id(&positional)?, include_sensitive: filter.include_sensitive },
+        "copy" => CliCommand::Copy { id: id(&positional)? },
+        "pin" => CliCommand::Pin { id: id(&positional)?, pinned: !unpin },
+        "delete" => CliCommand::Delete { id: id(&positional)? },
+        "pause" => CliCommand::Pause {
+            minutes: match positional.as_slice() {
+                [] => None,
+                [minutes] => Some(parse_number(minutes, "minutes")?),
+                _ => return Err("pause takes at most one argument".to_string()),
+            },
+        },
+        "export" => CliCommand::Export {
+            format: format.ok_or_else(|| "export needs --format json|csv|md|html".to_string())?,
+            output,
+            filter,
+        },
+        "stats" => CliCommand::Stats,
+        "help" => CliCommand::Help,
+        other => return Err(format!("Unknown command: {}\n\n{}", other, USAGE)),
+    })
+}
+
+/// Local midnight of a YYYY-MM-DD date, in ms
+fn parse_date(value: &str) -> Result<i64, String> {
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| midnight.and_local_timezone(chrono::Local).earliest())
+        .map(|midnight| midnight.timestamp_millis())
+        .ok_or_else(|| format!("Invalid date: {} (expected YYYY-MM-DD)", value))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, what: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid {}: {}", what, value))
+}
+
+// ============================================
+// EXECUTION
+// ============================================
+
+/// Run a command and return what should be printed
+fn execute(command: &CliCommand) -> Result<String, String> {
+    match command {
+        CliCommand::List { query, jsonl } => {
+            let conn = items::open_read_only(&paths::database_path()?)?;
+            print_items(&items::list_items(&conn, query)?, *jsonl)
+        }
+        CliCommand::Search { text, query, jsonl } => {
+            let conn = items::open_read_only(&paths::database_path()?)?;
+            print_items(&items::search_items(&conn, text, query)?, *jsonl)
+        }
+        CliCommand::Get { id, include_sensitive } => {
+            let conn = items::open_read_only(&paths::database_path()?)?;
+            let item = items::get_item(&conn, *id)?.ok_or_else(|| format!("Item {} not found", id))?;
+            if !include_sensitive && SENSITIVE_CATEGORIES.contains(&item.category.as_str()) {
+                return Err(format!("Item {} is a {} item; add --include-sensitive to show it", id, item.category));
+            }
+            to_json(&item)
+        }
+        CliCommand::Copy { id } => {
+            forward_to_app(&["--copy".to_string(), id.to_string()])?;
+            ok_json()
+        }
+        CliCommand::Pin { id, pinned } => {
+            let flag = if *pinned { "--pin" } else { "--unpin" };
+            forward_to_app(&[flag.to_string(), id.to_string()])?;
+            ok_json()
+        }
+        CliCommand::Delete { id } => {
+            forward_to_app(&["--delete".to_string(), id.to_string()])?;
+            ok_json()
+        }
+        CliCommand::Pause { minutes } => {
+            let mut args = vec!["--pause".to_string()];
+            args.extend(minutes.map(|m| m.to_string()));
+            forward_to_app(&args)?;
+            ok_json()
+        }
+        CliCommand::Export { format, output, filter } => {
+            let conn = items::open_read_only(&paths::database_path()?)?;
+            match output {
+                Some(path) => {
+                    let file = std::fs::File::create(path)
+                        .map_err(|e| format!("Failed to save export file: {}", e))?;
+                    let count = export::export_items(&conn, std::io::BufWriter::new(file), *format, filter, |_, _| {})?;
+                    to_json(&serde_json::json!({ "ok": true, "item_count": count, "path": path }))
+                }
+                None => {
+                    export::export_items(&conn, std::io::stdout().lock(), *format, filter, |_, _| {})?;
+                    Ok(String::new())
+                }
+            }
+        }
+        CliCommand::Stats => {
+            let conn = items::open_read_only(&paths::database_path()?)?;
+            let settings = AppSettings::load_from(&paths::settings_path()?)?;
+            to_json(&CliStats {
+                history: items::history_stats(&conn)?,
+                history_limit: settings.history_limit,
+                running: single_instance::is_running(),
+            })
+        }
+        CliCommand::Help => Ok(USAGE.to_string()),
+    }
+}
+
+/// Commands that only the running app can carry out
+fn forward_to_app(args: &[String]) -> Result<(), String> {
+    if !single_instance::is_running() {
+        return Err("CopyGum is not running".to_string());
+    }
+    single_instance::forward(args)
+}
+
+fn print_items(items: &[items::ClipboardItem], jsonl: bool) -> Result<String, String> {
+    if jsonl {
+        items
+            .iter()
+            .map(|item| serde_json::to_string(item).map_err(|e| format!("Failed to serialize item: {}", e)))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|lines| lines.join("\n"))
+    } else {
+        to_json(&items)
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize output: {}", e))
+}
+
+fn ok_json() -> Result<String, String> {
+    Ok(r#"{"ok":true}"#.to_string())
+}
+
+/// Release builds use the Windows GUI subsystem; reuse the terminal's
+/// console so output reaches the shell that ran `copygum`
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_is_cli_invocation() {
+        assert!(is_cli_invocation(&args(&["list"])));
+        assert!(is_cli_invocation(&args(&["stats"])));
+        assert!(!is_cli_invocation(&args(&["--toggle"])));
+        assert!(!is_cli_invocation(&args(&[])));
+    }
+
+    #[test]
+    fn test_parse_list_and_search() {
+        assert_eq!(
+            parse(&args(&["list", "--limit", "5", "--category", "code", "--jsonl"])).unwrap(),
+            CliCommand::List {
+                query: ItemQuery {
+                    limit: Some(5),
+                    filter: ItemFilter { categories: vec!["code".into()], ..ItemFilter::default() },
+                },
+                jsonl: true,
+            }
+        );
+        assert_eq!(
+            parse(&args(&["search", "hello", "world"])).unwrap(),
+            CliCommand::Search {
+                text: "hello world".into(),
+                query: ItemQuery { limit: Some(DEFAULT_LIMIT), filter: ItemFilter::default() },
+                jsonl: false,
+            }
+        );
+        assert!(parse(&args(&["search"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_list_and_search_filters() {
+        match parse(&args(&[
+            "list", "--category", "code", "--category", "links", "--tag", "Work",
+            "--since", "2024-01-15", "--pinned", "--include-sensitive",
+        ]))
+        .unwrap()
+        {
+            CliCommand::List { query, .. } => {
+                assert_eq!(query.filter.categories, vec!["code", "links"]);
+                assert_eq!(query.filter.tags, vec!["Work"]);
+                assert!(query.filter.since.is_some() && query.filter.pinned_only);
+                assert!(query.filter.include_sensitive);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match parse(&args(&["search", "token", "--until", "2024-02-01"])).unwrap() {
+            CliCommand::Search { query, .. } => {
+                assert!(query.filter.until.is_some());
+                assert!(!query.filter.include_sensitive);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_unused_options() {
+        assert!(parse(&args(&["get", "1", "--jsonl"])).is_err());
+        assert!(parse(&args(&["copy", "1", "--include-sensitive"])).is_err());
+        assert!(parse(&args(&["list", "--format", "csv"])).is_err());
+        assert!(parse(&args(&["export", "--format", "csv", "--jsonl"])).is_err());
+        assert!(parse(&args(&["pause", "5", "--unpin"])).is_err());
+        assert!(parse(&args(&["list", "hello"])).is_err());
+        assert_eq!(
+            parse(&args(&["get", "4", "--include-sensitive"])).unwrap(),
+            CliCommand::Get { id: 4, include_sensitive: true }
+        );
+    }
+
+    #[test]
+    fn test_parse_item_commands() {
+        assert_eq!(parse(&args(&["get", "12"])).unwrap(), CliCommand::Get { id: 12, include_sensitive: false });
+        assert_eq!(
+            parse(&args(&["pin", "3", "--unpin"])).unwrap(),
+            CliCommand::Pin { id: 3, pinned: false }
+        );
+        assert!(parse(&args(&["copy"])).is_err());
+        assert!(parse(&args(&["delete", "abc"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_pause_and_export() {
+        assert_eq!(parse(&args(&["pause"])).unwrap(), CliCommand::Pause { minutes: None });
+        assert_eq!(parse(&args(&["pause", "10"])).unwrap(), CliCommand::Pause { minutes: Some(10) });
+        assert_eq!(
+            parse(&args(&["export", "--format", "csv", "-o", "out.csv"])).unwrap(),
+            CliCommand::Export {
+                format: ExportFormat::Csv,
+                output: Some(PathBuf::from("out.csv")),
+                filter: ItemFilter::default(),
+            }
+        );
+        match parse(&args(&[
+            "export", "--format", "md", "--category", "code", "--category", "links",
+            "--tag", "Work", "--since", "2024-01-15", "--pinned",
+        ]))
+        .unwrap()
+        {
+            CliCommand::Export { format, filter, .. } => {
+                assert_eq!(format, ExportFormat::Markdown);
+                assert_eq!(filter.categories, vec!["code", "links"]);
+                assert_eq!(filter.tags, vec!["Work"]);
+                assert!(filter.since.is_some() && filter.pinned_only);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(parse(&args(&["export", "--format", "csv", "--since", "yesterday"])).is_err());
+        assert!(parse(&args(&["export"])).is_err());
+        assert!(parse(&args(&["list", "--bogus"])).is_err());
+    }
+}
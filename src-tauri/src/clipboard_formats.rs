@@ -0,0 +1,324 @@
+// Clipboard Formats Module - Encoded images on the system clipboard
+// arboard hands images over as a single RGBA frame, so an animated GIF copied
+// from a browser lost its animation once re-encoded as PNG. This module reads
+// and writes the encoded bytes under their own clipboard type instead:
+// - read_encoded_image takes the first of IMAGE_TARGETS the clipboard offers
+// - write_encoded_image offers a stored file under its own type, plus its
+//   first frame for apps that only paste static images
+// Clipboard types are NSPasteboard types on macOS, registered clipboard
+// formats on Windows and GTK selection targets on Linux (X11 and Wayland).
+// Anything else falls back to arboard in clipboard_monitor.rs.
+
+use image::{DynamicImage, ImageFormat};
+use tauri::AppHandle;
+
+/// An image format as each platform's clipboard names it
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImageTarget {
+    pub format: ImageFormat,
+    /// MIME type (GTK selection target)
+    pub mime: &'static str,
+    /// Uniform type identifier (NSPasteboard type)
+    pub uti: &'static str,
+    /// Registered clipboard format names (Windows)
+    pub windows_names: &'static [&'static str],
+}
+
+/// Encoded formats taken from the clipboard, preferred first: animation
+/// survives in GIF and WebP, and PNG is lossless
+pub const IMAGE_TARGETS: [ImageTarget; 3] = [
+    ImageTarget {
+        format: ImageFormat::Gif,
+        mime: "image/gif",
+        uti: "com.compuserve.gif",
+        windows_names: &["image/gif", "GIF"],
+    },
+    ImageTarget {
+        format: ImageFormat::WebP,
+        mime: "image/webp",
+        uti: "org.webmproject.webp",
+        windows_names: &["image/webp"],
+    },
+    ImageTarget {
+        format: ImageFormat::Png,
+        mime: "image/png",
+        uti: "public.png",
+        windows_names: &["PNG", "image/png"],
+    },
+];
+
+/// The target for an encoded format, if it is one of IMAGE_TARGETS
+pub fn target_for(format: ImageFormat) -> Option<&'static ImageTarget> {
+    IMAGE_TARGETS.iter().find(|target| target.format == format)
+}
+
+/// The preferred target among offered MIME types
+pub fn pick_target(offered: &[String]) -> Option<&'static ImageTarget> {
+    IMAGE_TARGETS
+        .iter()
+        .find(|target| offered.iter().any(|mime| mime.eq_ignore_ascii_case(target.mime)))
+}
+
+/// Encoded image on the clipboard, if it offers one of IMAGE_TARGETS
+/// (bytes that don't match the type they were offered as are ignored)
+pub async fn read_encoded_image(app: &AppHandle) -> Option<Vec<u8>> {
+    let (target, bytes) = platform::read(app).await?;
+    (image::guess_format(&bytes).ok() == Some(target.format)).then_some(bytes)
+}
+
+/// Put encoded image bytes on the clipboard under their own type, with
+/// `first_frame` as PNG (and as a bitmap on Windows) for other apps
+pub async fn write_encoded_image(app: &AppHandle, bytes: Vec<u8>, first_frame: &DynamicImage) -> Result<(), String> {
+    let format = image::guess_format(&bytes).map_err(|e| format!("Failed to read image format: {}", e))?;
+    let target = target_for(format).ok_or_else(|| format!("{:?} images can't be put on the clipboard as they are", format))?;
+
+    let mut offers = vec![(target, bytes)];
+    if format != ImageFormat::Png {
+        offers.push((target_for(ImageFormat::Png).expect("PNG is an image target"), encode(first_frame, ImageFormat::Png)?));
+    }
+    platform::write(app, offers, first_frame).await
+}
+
+fn encode(img: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut bytes), format)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(bytes)
+}
+
+// ============================================
+// PLATFORMS
+// ============================================
+
+#[cfg(target_os = "macos")]
+#[allow(deprecated)]
+mod platform {
+    use super::{ImageTarget, IMAGE_TARGETS};
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+    use image::DynamicImage;
+    use objc::{class, msg_send, sel, sel_impl};
+    use tauri::AppHandle;
+
+    // Runs on a tokio worker with no autorelease pool of its own, so each
+    // call drains one; otherwise the pasteboard's NSData leaks every poll
+    pub async fn read(_app: &AppHandle) -> Option<(&'static ImageTarget, Vec<u8>)> {
+        unsafe {
+            let pool = NSAutoreleasePool::new(nil);
+            let found = read_pasteboard();
+            pool.drain();
+            found
+        }
+    }
+
+    unsafe fn read_pasteboard() -> Option<(&'static ImageTarget, Vec<u8>)> {
+        let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+        if pasteboard == nil {
+            return None;
+        }
+        IMAGE_TARGETS.iter().find_map(|target| {
+            let uti = NSString::alloc(nil).init_str(target.uti).autorelease();
+            let data: id = msg_send![pasteboard, dataForType: uti];
+            if data == nil {
+                return None;
+            }
+            let length: usize = msg_send![data, length];
+            let bytes: *const u8 = msg_send![data, bytes];
+            if bytes.is_null() || length == 0 {
+                return None;
+            }
+            Some((target, std::slice::from_raw_parts(bytes, length).to_vec()))
+        })
+    }
+
+    pub async fn write(
+        _app: &AppHandle,
+        offers: Vec<(&'static ImageTarget, Vec<u8>)>,
+        _first_frame: &DynamicImage,
+    ) -> Result<(), String> {
+        unsafe {
+            let pool = NSAutoreleasePool::new(nil);
+            let result = write_pasteboard(offers);
+            pool.drain();
+            result
+        }
+    }
+
+    unsafe fn write_pasteboard(offers: Vec<(&'static ImageTarget, Vec<u8>)>) -> Result<(), String> {
+        let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+        if pasteboard == nil {
+            return Err("Failed to access clipboard".to_string());
+        }
+        let _: isize = msg_send![pasteboard, clearContents];
+        for (target, bytes) in offers {
+            let data: id = msg_send![class!(NSData), dataWithBytes: bytes.as_ptr() length: bytes.len()];
+            let uti = NSString::alloc(nil).init_str(target.uti).autorelease();
+            let written: bool = msg_send![pasteboard, setData: data forType: uti];
+            if !written {
+                return Err(format!("Failed to put {} on the clipboard", target.mime));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{encode, ImageTarget, IMAGE_TARGETS};
+    use clipboard_win::{options::NoClear, raw, Clipboard};
+    use image::{DynamicImage, ImageFormat};
+    use tauri::AppHandle;
+
+    /// Attempts at opening the clipboard while another app holds it
+    const OPEN_ATTEMPTS: usize = 10;
+
+    pub async fn read(_app: &AppHandle) -> Option<(&'static ImageTarget, Vec<u8>)> {
+        let _clipboard = Clipboard::new_attempts(OPEN_ATTEMPTS).ok()?;
+        IMAGE_TARGETS.iter().find_map(|target| {
+            target.windows_names.iter().find_map(|name| {
+                let format = raw::register_format(name)?.get();
+                if !raw::is_format_avail(format) {
+                    return None;
+                }
+                let mut bytes = Vec::new();
+                raw::get_vec(format, &mut bytes).ok()?;
+                (!bytes.is_empty()).then_some((target, bytes))
+            })
+        })
+    }
+
+    pub async fn write(
+        _app: &AppHandle,
+        offers: Vec<(&'static ImageTarget, Vec<u8>)>,
+        first_frame: &DynamicImage,
+    ) -> Result<(), String> {
+        // Most Windows apps only paste bitmaps
+        let bitmap = encode(first_frame, ImageFormat::Bmp)?;
+        let _clipboard = Clipboard::new_attempts(OPEN_ATTEMPTS)
+            .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+        raw::empty().map_err(|e| format!("Failed to clear clipboard: {}", e))?;
+        raw::set_bitmap_with(&bitmap, NoClear).map_err(|e| format!("Failed to set clipboard image: {}", e))?;
+        for (target, bytes) in offers {
+            for name in target.windows_names {
+                let format = raw::register_format(name)
+                    .ok_or_else(|| format!("Failed to register clipboard format {}", name))?;
+                raw::set_without_clear(format.get(), &bytes)
+                    .map_err(|e| format!("Failed to put {} on the clipboard: {}", target.mime, e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{pick_target, ImageTarget};
+    use gtk::gdk;
+    use image::DynamicImage;
+    use std::time::Duration;
+    use tauri::AppHandle;
+    use tokio::sync::oneshot;
+    use tokio::time::timeout;
+
+    // GTK's clipboard lives on the main thread; requests are answered there
+    // asynchronously and the result is sent back
+
+    /// How long to wait for the main thread and the clipboard owner
+    const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn clipboard() -> gtk::Clipboard {
+        gtk::Clipboard::get(&gdk::SELECTION_CLIPBOARD)
+    }
+
+    pub async fn read(app: &AppHandle) -> Option<(&'static ImageTarget, Vec<u8>)> {
+        let (sender, receiver) = oneshot::channel();
+        app.run_on_main_thread(move || {
+            clipboard().request_contents(&gdk::Atom::intern("TARGETS"), move |clipboard, targets| {
+                let offered: Vec<String> = targets
+                    .targets()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|atom| atom.name().to_string())
+                    .collect();
+                let Some(target) = pick_target(&offered) else {
+                    let _ = sender.send(None);
+                    return;
+                };
+                clipboard.request_contents(&gdk::Atom::intern(target.mime), move |_, contents| {
+                    let bytes = contents.data();
+                    let _ = sender.send((!bytes.is_empty()).then_some((target, bytes)));
+                });
+            });
+        })
+        .ok()?;
+        timeout(REPLY_TIMEOUT, receiver).await.ok()?.ok().flatten()
+    }
+
+    pub async fn write(
+        app: &AppHandle,
+        offers: Vec<(&'static ImageTarget, Vec<u8>)>,
+        _first_frame: &DynamicImage,
+    ) -> Result<(), String> {
+        let (sender, receiver) = oneshot::channel();
+        app.run_on_main_thread(move || {
+            let entries: Vec<gtk::TargetEntry> = offers
+                .iter()
+                .enumerate()
+                .map(|(index, (target, _))| gtk::TargetEntry::new(target.mime, gtk::TargetFlags::empty(), index as u32))
+                .collect();
+            // Served from memory for as long as CopyGum owns the clipboard
+            let owned = clipboard().set_with_data(&entries, move |_, selection, index| {
+                if let Some((target, bytes)) = offers.get(index as usize) {
+                    selection.set(&gdk::Atom::intern(target.mime), 8, bytes);
+                }
+            });
+            let _ = sender.send(owned);
+        })
+        .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+        match timeout(REPLY_TIMEOUT, receiver).await {
+            Ok(Ok(true)) => Ok(()),
+            _ => Err("Failed to set clipboard image".to_string()),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::ImageTarget;
+    use image::DynamicImage;
+    use tauri::AppHandle;
+
+    pub async fn read(_app: &AppHandle) -> Option<(&'static ImageTarget, Vec<u8>)> {
+        None
+    }
+
+    pub async fn write(
+        _app: &AppHandle,
+        _offers: Vec<(&'static ImageTarget, Vec<u8>)>,
+        _first_frame: &DynamicImage,
+    ) -> Result<(), String> {
+        Err("Encoded clipboard images are not supported on this platform".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_target_prefers_animation_formats() {
+        let offered = |types: &[&str]| types.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        // Browsers offer the original next to a PNG conversion
+        let target = pick_target(&offered(&["text/html", "image/png", "image/gif"])).unwrap();
+        assert_eq!(target.format, ImageFormat::Gif);
+        let target = pick_target(&offered(&["image/png", "IMAGE/WEBP"])).unwrap();
+        assert_eq!(target.format, ImageFormat::WebP);
+        assert_eq!(pick_target(&offered(&["image/png", "image/bmp"])).unwrap().format, ImageFormat::Png);
+
+        // JPEG and BMP are left to arboard
+        assert!(pick_target(&offered(&["image/jpeg", "image/bmp", "text/plain"])).is_none());
+        assert!(target_for(ImageFormat::Jpeg).is_none());
+        assert_eq!(target_for(ImageFormat::Gif).unwrap().mime, "image/gif");
+    }
+}
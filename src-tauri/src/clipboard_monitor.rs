@@ -1,8 +1,10 @@
 // Clipboard Monitoring Module
 // Monitors clipboard changes and captures content
 
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use tauri::{AppHandle, Manager, Emitter};
 use tokio::sync::Mutex;
 use tokio::time::sleep;
@@ -11,12 +13,23 @@ use crate::app_detector::get_frontmost_app;
 use crate::app_icons::get_app_icon;
 use crate::settings::AppSettings;
 
+/// An image found on the clipboard
+enum ClipboardImage {
+    Data(Vec<u8>),
+    /// A copied image file that was already handled, or is over the size limit
+    Skipped,
+}
+
 #[derive(Debug, Clone)]
 pub struct ClipboardMonitor {
     is_running: Arc<Mutex<bool>>,
     last_content: Arc<Mutex<String>>,
     last_image_hash: Arc<Mutex<String>>,
+    // Path and modification time of the last copied image file, so it isn't re-read every poll
+    last_image_file: Arc<Mutex<Option<(PathBuf, Option<SystemTime>)>>>,
     last_timestamp: Arc<Mutex<i64>>,
+    // Bumped on every start/stop so a timed pause only resumes if nothing changed since
+    state_generation: Arc<AtomicU64>,
     debounce_ms: i64,
 }
 
@@ -26,12 +39,15 @@ impl ClipboardMonitor {
             is_running: Arc::new(Mutex::new(false)),
             last_content: Arc::new(Mutex::new(String::new())),
             last_image_hash: Arc::new(Mutex::new(String::new())),
+            last_image_file: Arc::new(Mutex::new(None)),
             last_timestamp: Arc::new(Mutex::new(0)),
+            state_generation: Arc::new(AtomicU64::new(0)),
             debounce_ms: 1000, // 1 second debounce window (prevents accidental double-copy)
         }
     }
 
     pub async fn start(&self, app: AppHandle) {
+        self.state_generation.fetch_add(1, Ordering::SeqCst);
         let mut is_running = self.is_running.lock().await;
         if *is_running {
             return;
@@ -48,10 +64,28 @@ impl ClipboardMonitor {
     }
 
     pub async fn stop(&self) {
+        self.state_generation.fetch_add(1, Ordering::SeqCst);
         let mut is_running = self.is_running.lock().await;
         *is_running = false;
     }
 
+    /// Stop monitoring and resume after `duration`, unless monitoring is
+    /// started or stopped again in the meantime
+    pub async fn pause_for(&self, app: AppHandle, duration: Duration) {
+        self.stop().await;
+        let generation = self.state_generation.load(Ordering::SeqCst);
+        let monitor = self.clone();
+
+        tokio::spawn(async move {
+            sleep(duration).await;
+            if monitor.state_generation.load(Ordering::SeqCst) == generation {
+                println!("[CopyGum] Timed pause ended, resuming monitoring");
+                monitor.start(app.clone()).await;
+                emit_monitoring_state(&app, true);
+            }
+        });
+    }
+
     pub async fn is_running(&self) -> bool {
         *self.is_running.lock().await
     }
@@ -66,8 +100,10 @@ impl ClipboardMonitor {
 
             // Check for images first (higher priority) - only if save_images is enabled
             if save_images {
-                if let Some(image_data) = self.read_clipboard_image().await {
-                    self.handle_clipboard_image(&app, image_data).await;
+                if let Some(image) = self.read_clipboard_image(&app).await {
+                    if let ClipboardImage::Data(image_data) = image {
+                        self.handle_clipboard_image(&app, image_data).await;
+                    }
                     sleep(Duration::from_millis(500)).await;
                     continue;
                 }
@@ -123,13 +159,25 @@ impl ClipboardMonitor {
         }
     }
 
-    async fn read_clipboard_image(&self) -> Option<Vec<u8>> {
+    async fn read_clipboard_image(&self, app: &AppHandle) -> Option<ClipboardImage> {
         use arboard::Clipboard;
         use image::{DynamicImage, ImageFormat};
         use std::io::Cursor;
 
-        // Try to get clipboard image
         let mut clipboard = Clipboard::new().ok()?;
+
+        // A copied image file: keep its bytes, metadata included
+        if let Some(path) = Self::copied_image_file(&mut clipboard) {
+            return Some(self.read_image_file(app, &path).await);
+        }
+        *self.last_image_file.lock().await = None;
+
+        // GIF, WebP or PNG bytes as the source app copied them (keeps animation)
+        if let Some(encoded) = crate::clipboard_formats::read_encoded_image(app).await {
+            return Some(ClipboardImage::Data(encoded));
+        }
+
+        // Try to get clipboard image
         let image_data = clipboard.get_image().ok()?;
 
         // Convert arboard::ImageData to PNG bytes
@@ -144,7 +192,41 @@ impl ClipboardMonitor {
             .write_to(&mut Cursor::new(&mut png_bytes), ImageFormat::Png)
             .ok()?;
 
-        Some(png_bytes)
+        Some(ClipboardImage::Data(png_bytes))
+    }
+
+    /// The image file on the clipboard, when exactly one file was copied and
+    /// it is an image
+    fn copied_image_file(clipboard: &mut arboard::Clipboard) -> Option<PathBuf> {
+        let files = clipboard.get().file_list().ok()?;
+        let [path] = files.as_slice() else {
+            return None;
+        };
+        image::ImageFormat::from_path(path).ok()?;
+        Some(path.clone())
+    }
+
+    /// Read a copied image file, unless it was handled already (same path and
+    /// modification time) or is over the max_image_size_mb setting
+    async fn read_image_file(&self, app: &AppHandle, path: &Path) -> ClipboardImage {
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return ClipboardImage::Skipped;
+        };
+        let file = (path.to_path_buf(), metadata.modified().ok());
+        let mut last_file = self.last_image_file.lock().await;
+        if last_file.as_ref() == Some(&file) {
+            return ClipboardImage::Skipped;
+        }
+        *last_file = Some(file);
+
+        let settings = AppSettings::load(app).unwrap_or_default();
+        if metadata.len() > (settings.max_image_size_mb as u64) * 1024 * 1024 {
+            return ClipboardImage::Skipped;
+        }
+        match std::fs::read(path) {
+            Ok(data) => ClipboardImage::Data(data),
+            Err(_) => ClipboardImage::Skipped,
+        }
     }
 
     #[allow(dead_code)]
@@ -216,7 +298,7 @@ impl ClipboardMonitor {
         let content_type_str = content_type.as_str().to_string();
 
         // Auto-assign category based on content type
-        let category = self.map_content_type_to_category(&content_type);
+        let category = category_for_content_type(&content_type);
 
         // Detect source application
         let app_info = get_frontmost_app();
@@ -243,26 +325,9 @@ impl ClipboardMonitor {
         Ok(0)
     }
 
-    /// Map content type to appropriate category
-    /// This ensures content is automatically organized
-    fn map_content_type_to_category(&self, content_type: &crate::content_detector::ContentType) -> &'static str {
-        use crate::content_detector::ContentType;
-
-        match content_type {
-            ContentType::Password => "password",  // Passwords → password category
-            ContentType::ApiKey => "apikey",      // API keys → apikey category
-            ContentType::Color => "color",        // Hex colors → color category
-            ContentType::Url => "links",          // URLs → links category
-            ContentType::Email => "email",        // Emails → email category
-            ContentType::Phone => "phone",        // Phone numbers → phone category
-            ContentType::Number => "number",      // Numbers → number category
-            ContentType::Code => "code",          // Code → code category
-            ContentType::Text => "text",          // Default → text category
-        }
-    }
-
     async fn handle_clipboard_image(&self, app: &AppHandle, image_data: Vec<u8>) {
-        use crate::image_handler::save_clipboard_image;
+        use crate::image_handler::{save_clipboard_image, thumbnail_path};
+        use crate::similar_images::Placement;
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
 
@@ -278,11 +343,11 @@ impl ClipboardMonitor {
             return;
         }
 
+        // Defaults (10MB limit, no OCR) if settings can't be loaded
+        let settings = AppSettings::load(app).unwrap_or_default();
+
         // Check image size against max_image_size_mb setting
-        let max_size_bytes = match AppSettings::load(app) {
-            Ok(settings) => (settings.max_image_size_mb as u64) * 1024 * 1024,
-            Err(_) => 10 * 1024 * 1024, // Default to 10MB if settings can't be loaded
-        };
+        let max_size_bytes = (settings.max_image_size_mb as u64) * 1024 * 1024;
 
         let image_size = image_data.len() as u64;
         if image_size > max_size_bytes {
@@ -298,12 +363,33 @@ impl ClipboardMonitor {
         };
 
         // Save image and generate thumbnail
-        match save_clipboard_image(&app_data_dir, &image_data).await {
-            Ok(metadata) => {
+        match save_clipboard_image(&app_data_dir, &image_data, settings.strip_image_metadata).await {
+            Ok(mut metadata) => {
+                // Near-duplicates of a recent image are stacked or merged (similar_images.rs)
+                let placement = crate::similar_images::placement_for(&settings, metadata.perceptual_hash);
+                let stack_id = match &placement {
+                    Placement::Stack(stack_id) => Some(*stack_id),
+                    Placement::Merge(existing_path) => {
+                        // The earlier image is kept; the frontend moves it to the top
+                        let _ = std::fs::remove_file(&metadata.image_path);
+                        let _ = std::fs::remove_file(&metadata.thumbnail_path);
+                        metadata.image_path = existing_path.clone();
+                        metadata.thumbnail_path = thumbnail_path(Path::new(existing_path))
+                            .to_string_lossy()
+                            .to_string();
+                        None
+                    }
+                    Placement::New => None,
+                };
+                let image_path = metadata.image_path.clone();
                 // Save to database
-                if self.save_image_to_database(app, metadata).await.is_ok() {
+                if self.save_image_to_database(app, metadata, stack_id).await.is_ok() {
                     // Update last hash only after successful save
                     *last_hash = image_hash;
+                    // Recognize text in the background (ocr.rs); merged images already were
+                    if settings.ocr_enabled && crate::ocr::is_available() && !matches!(placement, Placement::Merge(_)) {
+                        crate::ocr::enqueue(&image_path);
+                    }
                 }
             }
             Err(_) => {
@@ -316,6 +402,7 @@ impl ClipboardMonitor {
         &self,
         app: &AppHandle,
         metadata: crate::image_handler::ImageMetadata,
+        stack_id: Option<i64>,
     ) -> Result<i64, String> {
         use serde_json::json;
 
@@ -328,6 +415,14 @@ impl ClipboardMonitor {
             &app_info.name
         ).to_string();
 
+        // Decoded QR/barcode text is searchable unless it is a secret
+        let search_text = match metadata.decoded_type.as_deref() {
+            Some(decoded_type) if !crate::db::items::SENSITIVE_CATEGORIES.contains(&decoded_type) => {
+                metadata.decoded_text.clone()
+            }
+            _ => None,
+        };
+
         // Emit event to frontend with image metadata
         // Frontend will save to database using @tauri-apps/plugin-sql
         let _ = app.emit("clipboard-changed", json!({
@@ -340,7 +435,18 @@ impl ClipboardMonitor {
             "imageWidth": metadata.width,
             "imageHeight": metadata.height,
             "imageSize": metadata.file_size,
+            "imageFormat": metadata.details.format,
+            "imageColorSpace": metadata.details.color_space,
+            "imageDpi": metadata.details.dpi,
+            "imageHasAlpha": metadata.details.has_alpha,
+            "imageAnimated": metadata.details.animated,
             "dominantColor": metadata.dominant_color,
+            "palette": metadata.palette.as_ref().and_then(|palette| serde_json::to_string(palette).ok()),
+            "decodedText": metadata.decoded_text,
+            "decodedType": metadata.decoded_type,
+            "searchText": search_text,
+            "perceptualHash": crate::similar_images::format_hash(metadata.perceptual_hash),
+            "stackId": stack_id,
             "sourceAppName": source_app_name,
             "sourceAppIcon": source_app_icon,
             "sourceBundleId": source_bundle_id
@@ -351,6 +457,41 @@ impl ClipboardMonitor {
     }
 }
 
+/// Map content type to appropriate category
+/// This ensures content is automatically organized
+pub fn category_for_content_type(content_type: &crate::content_detector::ContentType) -> &'static str {
+    use crate::content_detector::ContentType;
+
+    match content_type {
+        ContentType::Password => "password",  // Passwords → password category
+        ContentType::ApiKey => "apikey",      // API keys → apikey category
+        ContentType::Color => "color",        // Hex colors → color category
+        ContentType::Url => "links",          // URLs → links category
+        ContentType::Email => "email",        // Emails → email category
+        ContentType::Phone => "phone",        // Phone numbers → phone category
+        ContentType::Number => "number",      // Numbers → number category
+        ContentType::Code => "code",          // Code → code category
+        ContentType::Text => "text",          // Default → text category
+    }
+}
+
+/// Put a history item back on the system clipboard (CLI and RPC `copy`)
+pub async fn copy_history_item(app: &AppHandle, id: i64) -> Result<(), String> {
+    use crate::db::items;
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let conn = items::open_read_only(&crate::paths::database_path()?)?;
+    let item = items::get_item(&conn, id)?.ok_or_else(|| format!("Item {} not found", id))?;
+
+    match item.image_path {
+        Some(path) if item.is_image => copy_image_to_clipboard(app.clone(), path).await,
+        _ => app
+            .clipboard()
+            .write_text(item.content)
+            .map_err(|e| format!("Failed to write clipboard: {}", e)),
+    }
+}
+
 // ============================================
 // TAURI COMMANDS
 // ============================================
@@ -362,6 +503,7 @@ pub async fn start_clipboard_monitoring(
     // Get or create monitor instance from app state
     let monitor = app.state::<ClipboardMonitor>();
     monitor.start(app.clone()).await;
+    emit_monitoring_state(&app, true);
     Ok(())
 }
 
@@ -371,6 +513,7 @@ pub async fn stop_clipboard_monitoring(
 ) -> Result<(), String> {
     let monitor = app.state::<ClipboardMonitor>();
     monitor.stop().await;
+    emit_monitoring_state(&app, false);
     Ok(())
 }
 
@@ -388,6 +531,7 @@ pub async fn pause_clipboard_monitoring(
 ) -> Result<(), String> {
     let monitor = app.state::<ClipboardMonitor>();
     monitor.stop().await;
+    emit_monitoring_state(&app, false);
     Ok(())
 }
 
@@ -397,16 +541,30 @@ pub async fn resume_clipboard_monitoring(
 ) -> Result<(), String> {
     let monitor = app.state::<ClipboardMonitor>();
     monitor.start(app.clone()).await;
+    emit_monitoring_state(&app, true);
     Ok(())
 }
 
+/// Pause monitoring for a number of minutes (from `copygum pause 10`)
+pub async fn pause_clipboard_monitoring_for(app: AppHandle, minutes: u32) -> Result<(), String> {
+    let monitor = app.state::<ClipboardMonitor>();
+    monitor.pause_for(app.clone(), Duration::from_secs(u64::from(minutes) * 60)).await;
+    emit_monitoring_state(&app, false);
+    Ok(())
+}
+
+/// Let the tray and frontend know monitoring was started or stopped
+fn emit_monitoring_state(app: &AppHandle, is_running: bool) {
+    let _ = app.emit("monitoring-state-changed", serde_json::json!({ "isRunning": is_running }));
+}
+
 /// Copy an image file to the system clipboard
 /// This allows users to paste the image into other applications
+/// GIF, WebP and PNG files are offered as they are, so animations survive
 #[tauri::command]
-pub async fn copy_image_to_clipboard(image_path: String) -> Result<(), String> {
+pub async fn copy_image_to_clipboard(app: AppHandle, image_path: String) -> Result<(), String> {
     use arboard::Clipboard;
-    use image::ImageReader;
-    use std::path::Path;
+    use crate::image_handler::{decode_image, image_bytes_for_clipboard};
 
     println!("[CopyGum] copy_image_to_clipboard called with path: {}", image_path);
 
@@ -420,22 +578,32 @@ pub async fn copy_image_to_clipboard(image_path: String) -> Result<(), String> {
 
     println!("[CopyGum] Image file exists, loading...");
 
-    // Load the image
-    let img = ImageReader::open(path)
+    // Load the image, without location and camera metadata unless the user keeps it
+    let strip_metadata = crate::paths::settings_path()
+        .and_then(|path| AppSettings::load_from(&path))
+        .map(|settings| settings.strip_image_metadata)
+        .unwrap_or(true);
+    let (bytes, img) = image_bytes_for_clipboard(path, strip_metadata)
+        .and_then(|bytes| decode_image(&bytes).map(|img| (bytes, img)))
         .map_err(|e| {
-            let err = format!("Failed to open image file: {}", e);
-            println!("[CopyGum] Error: {}", err);
-            err
-        })?
-        .decode()
-        .map_err(|e| {
-            let err = format!("Failed to decode image: {}", e);
-            println!("[CopyGum] Error: {}", err);
-            err
+            println!("[CopyGum] Error: {}", e);
+            e
         })?;
 
     println!("[CopyGum] Image decoded successfully");
 
+    // Offer the encoded file under its own type when the platform allows
+    let encoded_target = image::guess_format(&bytes).ok().and_then(crate::clipboard_formats::target_for);
+    if let Some(target) = encoded_target {
+        match crate::clipboard_formats::write_encoded_image(&app, bytes, &img).await {
+            Ok(()) => {
+                println!("[CopyGum] Image copied to clipboard as {}", target.mime);
+                return Ok(());
+            }
+            Err(e) => println!("[CopyGum] {}; copying image as bitmap", e),
+        }
+    }
+
     // Convert to RGBA8
     let rgba_img = img.to_rgba8();
     let (width, height) = rgba_img.dimensions();
@@ -38,6 +38,7 @@ impl ContentType {
 // Lazy-initialized regex patterns (compiled once, reused many times)
 static HEX_COLOR_REGEX: OnceLock<Regex> = OnceLock::new();
 static API_KEY_REGEX: OnceLock<Regex> = OnceLock::new();
+static WIFI_REGEX: OnceLock<Regex> = OnceLock::new();
 static PASSWORD_REGEX: OnceLock<Regex> = OnceLock::new();
 static URL_REGEX: OnceLock<Regex> = OnceLock::new();
 static EMAIL_REGEX: OnceLock<Regex> = OnceLock::new();
@@ -53,9 +54,15 @@ fn init_regexes() {
     });
 
     // API keys: sk-xxx, pk-xxx, AKIA-xxx, ghp_xxx, xoxb-xxx, key-xxx,
+    // 2FA enrollment URIs (otpauth://, they carry the shared secret),
     // or long alphanumeric tokens (32+ chars with mixed case/digits)
     API_KEY_REGEX.get_or_init(|| {
-        Regex::new(r"(?i)^(sk[-_]|pk[-_]|AKIA|ghp_|gho_|xox[baprs]-|key[-_]|token[-_]|api[-_]?key|bearer\s+|secret[-_]|access[-_]?key).+|^[A-Za-z0-9/+=]{32,}$").unwrap()
+        Regex::new(r"(?i)^(sk[-_]|pk[-_]|AKIA|ghp_|gho_|xox[baprs]-|key[-_]|token[-_]|api[-_]?key|bearer\s+|secret[-_]|access[-_]?key|otpauth(-migration)?://).+|^[A-Za-z0-9/+=]{32,}$").unwrap()
+    });
+
+    // Wi-Fi network QR payloads with a password: WIFI:S:name;T:WPA;P:secret;;
+    WIFI_REGEX.get_or_init(|| {
+        Regex::new(r"(?i)^WIFI:(.*;)?P:[^;]").unwrap()
     });
 
     // Passwords: basic pattern - no whitespace, 8-64 chars (actual validation done in code)
@@ -93,7 +100,8 @@ fn init_regexes() {
 ///
 /// Detection order (priority):
 /// 1. Hex Color (#RRGGBB) - most specific
-/// 2. API Key (sk-xxx, AKIA-xxx, long tokens) - before URL since keys can look like URLs
+/// 2. API Key (sk-xxx, AKIA-xxx, otpauth://, long tokens) - before URL since keys can look like URLs
+///    and Wi-Fi credentials (WIFI:...;P:...) as Password
 /// 3. Email (contains @) - checked before URL to avoid TLD false positives
 /// 4. URL (http://, https://, ftp://, file://, www.)
 /// 5. Phone (phone number patterns)
@@ -127,6 +135,13 @@ pub fn detect_content_type(content: &str) -> ContentType {
         }
     }
 
+    // Wi-Fi credentials from a QR code hold the network password
+    if let Some(regex) = WIFI_REGEX.get() {
+        if regex.is_match(trimmed) {
+            return ContentType::Password;
+        }
+    }
+
     // 3. Check for email (before URL to avoid false positives with .co, .io TLDs)
     if let Some(regex) = EMAIL_REGEX.get() {
         if regex.is_match(trimmed) {
@@ -267,6 +282,9 @@ mod tests {
         // Long alphanumeric tokens (32+ chars)
         assert_eq!(detect_content_type("abcdefghijklmnopqrstuvwxyz123456"), ContentType::ApiKey);
 
+        // 2FA enrollment URIs, e.g. decoded from a QR code
+        assert_eq!(detect_content_type("otpauth://totp/GitHub:me?secret=JBSWY3DPEHPK3PXP&issuer=GitHub"), ContentType::ApiKey);
+
         // Not API keys
         assert_ne!(detect_content_type("Hello World"), ContentType::ApiKey);
         assert_ne!(detect_content_type("short"), ContentType::ApiKey);
@@ -278,6 +296,7 @@ mod tests {
         assert_eq!(detect_content_type("P@ssw0rd!2024"), ContentType::Password);
         assert_eq!(detect_content_type("MyStr0ng!Pass"), ContentType::Password);
         assert_eq!(detect_content_type("Abc123!@#"), ContentType::Password);
+        assert_eq!(detect_content_type("WIFI:S:Home Network;T:WPA;P:correct horse;;"), ContentType::Password);
 
         // Not passwords
         assert_ne!(detect_content_type("password"), ContentType::Password); // No uppercase, digit, special
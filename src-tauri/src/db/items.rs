@@ -0,0 +1,608 @@
+// Clipboard Items - Direct SQLite access from Rust
+// The app UI reads and writes through @tauri-apps/plugin-sql; this module
+// opens the same database file for the CLI and for commands forwarded to
+// the running instance. Queries mirror src/lib/services/database.ts
+// (soft delete, tags from clipboard_items_with_tags, timestamps in ms).
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Categories whose items are kept out of exports unless asked for
+pub const SENSITIVE_CATEGORIES: &[&str] = &["password", "apikey", "private"];
+
+/// A clipboard history entry as exposed outside the UI
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClipboardItem {
+    pub id: i64,
+    pub content: String,
+    pub content_type: String,
+    pub category: String,
+    pub app_name: Option<String>,
+    pub is_image: bool,
+    pub image_path: Option<String>,
+    pub image_width: Option<i64>,
+    pub image_height: Option<i64>,
+    pub timestamp: i64,
+    pub is_pinned: bool,
+    pub tags: Vec<String>,
+}
+
+/// Filters and limit for listing and searching items
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemQuery {
+    pub limit: Option<usize>,
+    pub filter: ItemFilter,
+}
+
+/// Filters for listing, searching and exporting (all optional; timestamps in ms)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ItemFilter {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub categories: Vec<String>,
+    pub tags: Vec<String>,
+    pub pinned_only: bool,
+    pub include_sensitive: bool,
+}
+
+/// Counts and sizes of the items matching a filter
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FilteredTotals {
+    pub item_count: usize,
+    pub image_count: usize,
+    pub content_bytes: u64,
+    pub image_bytes: u64,
+}
+
+/// History totals for `copygum stats`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryStats {
+    pub total_items: i64,
+    pub pinned_items: i64,
+    pub image_items: i64,
+    pub deleted_items: i64,
+    pub by_category: BTreeMap<String, i64>,
+    pub oldest_timestamp: Option<i64>,
+    pub newest_timestamp: Option<i64>,
+}
+
+pub const ITEM_COLUMNS: &str = "id, content, content_type, category, app_name, is_image, image_path, \
+     image_width, image_height, timestamp, is_pinned, tag_names";
+
+// ============================================
+// CONNECTIONS
+// ============================================
+
+/// Open the database for reading only (safe while the app is running)
+pub fn open_read_only(path: &Path) -> Result<Connection, String> {
+    if !path.exists() {
+        return Err(format!("No clipboard history found at {}", path.display()));
+    }
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Failed to configure database: {}", e))?;
+    Ok(conn)
+}
+
+/// Open the database for writing
+pub fn open_read_write(path: &Path) -> Result<Connection, String> {
+    if !path.exists() {
+        return Err(format!("No clipboard history found at {}", path.display()));
+    }
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Failed to configure database: {}", e))?;
+    Ok(conn)
+}
+
+// ============================================
+// QUERIES
+// ============================================
+
+pub fn item_from_row(row: &Row) -> rusqlite::Result<ClipboardItem> {
+    let tag_names: Option<String> = row.get(11)?;
+    Ok(ClipboardItem {
+        id: row.get(0)?,
+        content: row.get(1)?,
+        content_type: row.get(2)?,
+        category: row.get(3)?,
+        app_name: row.get(4)?,
+        is_image: row.get(5)?,
+        image_path: row.get(6)?,
+        image_width: row.get(7)?,
+        image_height: row.get(8)?,
+        timestamp: row.get(9)?,
+        is_pinned: row.get(10)?,
+        tags: tag_names
+            .map(|names| names.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+    })
+}
+
+/// ITEM_COLUMNS prefixed with a table alias, for queries that join the view
+pub fn qualified_columns(alias: &str) -> String {
+    ITEM_COLUMNS
+        .split(", ")
+        .map(|column| format!("{}.{}", alias, column.trim()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Active items matching the query, pinned first then newest (same order as the UI)
+pub fn list_items(conn: &Connection, query: &ItemQuery) -> Result<Vec<ClipboardItem>, String> {
+    let (where_sql, mut values) = query.filter.where_clause();
+    let sql = format!(
+        "SELECT {} FROM clipboard_items_with_tags v WHERE {}
+         ORDER BY v.is_pinned DESC, v.timestamp DESC
+         LIMIT ?",
+        qualified_columns("v"),
+        where_sql
+    );
+    values.push(Value::Integer(limit_param(query.limit)));
+    query_items(conn, &sql, values)
+}
+
+/// The newest active items, pinned or not (the tray's recent list)
+pub fn recent_items(conn: &Connection, limit: usize) -> Result<Vec<ClipboardItem>, String> {
+    let sql = format!(
+        "SELECT {} FROM clipboard_items_with_tags
+         WHERE is_deleted = 0
+         ORDER BY timestamp DESC
+         LIMIT ?1",
+        ITEM_COLUMNS
+    );
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to query items: {}", e))?;
+    let rows = stmt
+        .query_map(params![limit_param(Some(limit))], item_from_row)
+        .map_err(|e| format!("Failed to query items: {}", e))?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read items: {}", e))
+}
+
+/// Substring search over the content of items matching the query
+pub fn search_items(conn: &Connection, text: &str, query: &ItemQuery) -> Result<Vec<ClipboardItem>, String> {
+    let (where_sql, mut values) = query.filter.where_clause();
+    let sql = format!(
+        "SELECT {} FROM clipboard_items_with_tags v WHERE {} AND v.content LIKE ? ESCAPE '\\'
+         ORDER BY v.is_pinned DESC, v.timestamp DESC
+         LIMIT ?",
+        qualified_columns("v"),
+        where_sql
+    );
+    values.push(Value::Text(format!("%{}%", escape_like(text))));
+    values.push(Value::Integer(limit_param(query.limit)));
+    query_items(conn, &sql, values)
+}
+
+fn query_items(conn: &Connection, sql: &str, values: Vec<Value>) -> Result<Vec<ClipboardItem>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to query items: {}", e))?;
+    let rows = stmt
+        .query_map(params_from_iter(values), item_from_row)
+        .map_err(|e| format!("Failed to query items: {}", e))?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read items: {}", e))
+}
+
+/// A single active item
+pub fn get_item(conn: &Connection, id: i64) -> Result<Option<ClipboardItem>, String> {
+    let sql = format!(
+        "SELECT {} FROM clipboard_items_with_tags WHERE is_deleted = 0 AND id = ?1",
+        ITEM_COLUMNS
+    );
+    conn.query_row(&sql, params![id], item_from_row)
+        .optional()
+        .map_err(|e| format!("Failed to get item {}: {}", id, e))
+}
+
+impl ItemFilter {
+    /// WHERE clause over clipboard_items_with_tags (aliased `v`) and its parameters
+    pub fn where_clause(&self) -> (String, Vec<Value>) {
+        let mut conditions = vec!["v.is_deleted = 0".to_string()];
+        let mut values = Vec::new();
+
+        if let Some(since) = self.since {
+            conditions.push("v.timestamp >= ?".to_string());
+            values.push(Value::Integer(since));
+        }
+        if let Some(until) = self.until {
+            conditions.push("v.timestamp < ?".to_string());
+            values.push(Value::Integer(until));
+        }
+        // "all" is the UI's name for no category filter
+        let categories: Vec<&String> = self.categories.iter().filter(|c| *c != "all").collect();
+        if !categories.is_empty() {
+            conditions.push(format!("v.category IN ({})", placeholders(categories.len())));
+            values.extend(categories.into_iter().cloned().map(Value::Text));
+        }
+        if !self.tags.is_empty() {
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM item_tags it JOIN tags t ON t.id = it.tag_id
+                         WHERE it.item_id = v.id AND t.name IN ({}))",
+                placeholders(self.tags.len())
+            ));
+            values.extend(self.tags.iter().cloned().map(Value::Text));
+        }
+        if self.pinned_only {
+            conditions.push("v.is_pinned = 1".to_string());
+        }
+        if !self.include_sensitive {
+            conditions.push(format!("v.category NOT IN ({})", placeholders(SENSITIVE_CATEGORIES.len())));
+            values.extend(SENSITIVE_CATEGORIES.iter().map(|c| Value::Text(c.to_string())));
+        }
+
+        (conditions.join(" AND "), values)
+    }
+}
+
+/// Counts and sizes for an export preview
+pub fn filtered_totals(conn: &Connection, filter: &ItemFilter) -> Result<FilteredTotals, String> {
+    let (where_sql, values) = filter.where_clause();
+    let sql = format!(
+        "SELECT COUNT(*), COALESCE(SUM(v.is_image), 0),
+                COALESCE(SUM(LENGTH(CAST(v.content AS BLOB))), 0), COALESCE(SUM(v.image_size), 0)
+         FROM clipboard_items_with_tags v WHERE {}",
+        where_sql
+    );
+    conn.query_row(&sql, params_from_iter(values), |row| {
+        Ok(FilteredTotals {
+            item_count: row.get::<_, i64>(0)? as usize,
+            image_count: row.get::<_, i64>(1)? as usize,
+            content_bytes: row.get::<_, i64>(2)? as u64,
+            image_bytes: row.get::<_, i64>(3)? as u64,
+        })
+    })
+    .map_err(|e| format!("Failed to count items: {}", e))
+}
+
+/// Visit the items matching a filter, newest first, without loading them all
+pub fn for_each_filtered(
+    conn: &Connection,
+    filter: &ItemFilter,
+    mut visit: impl FnMut(ClipboardItem) -> Result<(), String>,
+) -> Result<(), String> {
+    let (where_sql, values) = filter.where_clause();
+    let sql = format!(
+        "SELECT {} FROM clipboard_items_with_tags v WHERE {} ORDER BY v.timestamp DESC",
+        qualified_columns("v"),
+        where_sql
+    );
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to query items: {}", e))?;
+    let mut rows = stmt
+        .query(params_from_iter(values))
+        .map_err(|e| format!("Failed to query items: {}", e))?;
+    while let Some(row) = rows.next().map_err(|e| format!("Failed to read items: {}", e))? {
+        visit(item_from_row(row).map_err(|e| format!("Failed to read items: {}", e))?)?;
+    }
+    Ok(())
+}
+
+/// Pin or unpin an item; returns false if it doesn't exist
+pub fn set_pinned(conn: &Connection, id: i64, pinned: bool) -> Result<bool, String> {
+    let changed = conn
+        .execute(
+            "UPDATE clipboard_items SET is_pinned = ?1 WHERE id = ?2 AND is_deleted = 0",
+            params![pinned, id],
+        )
+        .map_err(|e| format!("Failed to update item {}: {}", id, e))?;
+    Ok(changed > 0)
+}
+
+/// Move an item to the trash (soft delete, like the UI)
+pub fn soft_delete(conn: &Connection, id: i64) -> Result<bool, String> {
+    let changed = conn
+        .execute(
+            "UPDATE clipboard_items SET is_deleted = 1, deleted_at = ?1 WHERE id = ?2 AND is_deleted = 0",
+            params![now_millis(), id],
+        )
+        .map_err(|e| format!("Failed to delete item {}: {}", id, e))?;
+    Ok(changed > 0)
+}
+
+/// Save a text item, or bump the existing copy of the same content to the
+/// top (same duplicate handling as saveClipboardItem in database.ts)
+///
+/// Returns the item id and whether a new row was inserted.
+pub fn add_text_item(
+    conn: &Connection,
+    content: &str,
+    content_type: &str,
+    category: &str,
+    app_name: Option<&str>,
+) -> Result<(i64, bool), String> {
+    let timestamp = now_millis();
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM clipboard_items WHERE content = ?1 AND is_deleted = 0
+             ORDER BY timestamp DESC LIMIT 1",
+            params![content],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to check for duplicates: {}", e))?;
+
+    if let Some(id) = existing {
+        conn.execute(
+            "UPDATE clipboard_items SET timestamp = ?1, updated_at = ?1, content_type = ?2, category = ?3
+             WHERE id = ?4",
+            params![timestamp, content_type, category, id],
+        )
+        .map_err(|e| format!("Failed to update item {}: {}", id, e))?;
+        return Ok((id, false));
+    }
+
+    conn.execute(
+        "INSERT INTO clipboard_items (content, content_type, category, app_name, is_image, timestamp, is_pinned, is_deleted)
+         VALUES (?1, ?2, ?3, ?4, 0, ?5, 0, 0)",
+        params![content, content_type, category, app_name, timestamp],
+    )
+    .map_err(|e| format!("Failed to save item: {}", e))?;
+    Ok((conn.last_insert_rowid(), true))
+}
+
+/// Counts over the whole history
+pub fn history_stats(conn: &Connection) -> Result<HistoryStats, String> {
+    let (total_items, pinned_items, image_items, deleted_items, oldest_timestamp, newest_timestamp) = conn
+        .query_row(
+            "SELECT
+                 COALESCE(SUM(is_deleted = 0), 0),
+                 COALESCE(SUM(is_deleted = 0 AND is_pinned = 1), 0),
+                 COALESCE(SUM(is_deleted = 0 AND is_image = 1), 0),
+                 COALESCE(SUM(is_deleted = 1), 0),
+                 MIN(CASE WHEN is_deleted = 0 THEN timestamp END),
+                 MAX(CASE WHEN is_deleted = 0 THEN timestamp END)
+             FROM clipboard_items",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
+        )
+        .map_err(|e| format!("Failed to compute stats: {}", e))?;
+
+    let mut stmt = conn
+        .prepare("SELECT category, COUNT(*) FROM clipboard_items WHERE is_deleted = 0 GROUP BY category")
+        .map_err(|e| format!("Failed to compute stats: {}", e))?;
+    let by_category = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Failed to compute stats: {}", e))?
+        .collect::<rusqlite::Result<BTreeMap<String, i64>>>()
+        .map_err(|e| format!("Failed to read stats: {}", e))?;
+
+    Ok(HistoryStats {
+        total_items,
+        pinned_items,
+        image_items,
+        deleted_items,
+        by_category,
+        oldest_timestamp,
+        newest_timestamp,
+    })
+}
+
+// ============================================
+// HELPERS
+// ============================================
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// SQLite treats a negative LIMIT as "no limit"
+fn limit_param(limit: Option<usize>) -> i64 {
+    limit.map(|l| l as i64).unwrap_or(-1)
+}
+
+pub fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Milliseconds since the epoch, matching the frontend's Date.now()
+pub fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// In-memory database with the app's migrations applied
+#[cfg(test)]
+pub fn test_connection() -> Connection {
+    let conn = Connection::open_in_memory().expect("Failed to open in-memory database");
+    for migration in super::init_database() {
+        conn.execute_batch(migration.sql).expect("Failed to apply migration");
+    }
+    conn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(conn: &Connection, content: &str, category: &str, timestamp: i64, pinned: bool) -> i64 {
+        conn.execute(
+            "INSERT INTO clipboard_items (content, content_type, category, is_image, timestamp, is_pinned, is_deleted)
+             VALUES (?1, 'text', ?2, 0, ?3, ?4, 0)",
+            params![content, category, timestamp, pinned],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    #[test]
+    fn test_list_orders_pinned_first_then_newest() {
+        let conn = test_connection();
+        insert(&conn, "old", "text", 1_000, false);
+        insert(&conn, "new", "text", 3_000, false);
+        insert(&conn, "pinned", "code", 2_000, true);
+
+        let items = list_items(&conn, &ItemQuery::default()).unwrap();
+        let contents: Vec<_> = items.iter().map(|i| i.content.as_str()).collect();
+        assert_eq!(contents, vec!["pinned", "new", "old"]);
+
+        let code = ItemFilter { categories: vec!["code".into()], ..ItemFilter::default() };
+        let code = list_items(&conn, &ItemQuery { limit: None, filter: code }).unwrap();
+        assert_eq!(code.len(), 1);
+
+        let limited = list_items(&conn, &ItemQuery { limit: Some(1), filter: ItemFilter::default() }).unwrap();
+        assert_eq!(limited.len(), 1);
+
+        // The tray's recent list ignores pinning
+        let recent = recent_items(&conn, 2).unwrap();
+        let contents: Vec<_> = recent.iter().map(|i| i.content.as_str()).collect();
+        assert_eq!(contents, vec!["new", "pinned"]);
+    }
+
+    #[test]
+    fn test_search_escapes_like_wildcards() {
+        let conn = test_connection();
+        insert(&conn, "100% done", "text", 1_000, false);
+        insert(&conn, "1000 items", "number", 2_000, false);
+
+        let results = search_items(&conn, "100%", &ItemQuery::default()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].content, "100% done");
+    }
+
+    #[test]
+    fn test_list_and_search_apply_filter() {
+        let conn = test_connection();
+        insert(&conn, "note one", "text", 1_000, false);
+        insert(&conn, "note two", "code", 2_000, true);
+        insert(&conn, "note hunter2", "password", 3_000, false);
+
+        // Sensitive items are held back unless asked for
+        assert_eq!(list_items(&conn, &ItemQuery::default()).unwrap().len(), 2);
+        assert_eq!(search_items(&conn, "hunter", &ItemQuery::default()).unwrap().len(), 0);
+        let sensitive = ItemQuery {
+            limit: None,
+            filter: ItemFilter { include_sensitive: true, ..ItemFilter::default() },
+        };
+        assert_eq!(search_items(&conn, "hunter", &sensitive).unwrap().len(), 1);
+
+        let query = ItemQuery {
+            limit: None,
+            filter: ItemFilter { since: Some(1_500), pinned_only: true, ..ItemFilter::default() },
+        };
+        let found = search_items(&conn, "note", &query).unwrap();
+        assert_eq!(found.iter().map(|i| i.content.as_str()).collect::<Vec<_>>(), vec!["note two"]);
+
+        let categories = ItemFilter { categories: vec!["text".into(), "code".into()], ..ItemFilter::default() };
+        assert_eq!(list_items(&conn, &ItemQuery { limit: None, filter: categories }).unwrap().len(), 2);
+        let all = ItemFilter { categories: vec!["all".into()], ..ItemFilter::default() };
+        assert_eq!(list_items(&conn, &ItemQuery { limit: None, filter: all }).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_pin_and_soft_delete() {
+        let conn = test_connection();
+        let id = insert(&conn, "hello", "text", 1_000, false);
+
+        assert!(set_pinned(&conn, id, true).unwrap());
+        assert!(get_item(&conn, id).unwrap().unwrap().is_pinned);
+
+        assert!(soft_delete(&conn, id).unwrap());
+        assert!(get_item(&conn, id).unwrap().is_none());
+        assert!(!soft_delete(&conn, id).unwrap());
+        assert!(!set_pinned(&conn, 9999, true).unwrap());
+    }
+
+    #[test]
+    fn test_add_text_item_bumps_duplicates() {
+        let conn = test_connection();
+        let (id, is_new) = add_text_item(&conn, "hello", "text", "text", Some("Editor")).unwrap();
+        assert!(is_new);
+
+        let (again, is_new) = add_text_item(&conn, "hello", "code", "code", None).unwrap();
+        assert_eq!(again, id);
+        assert!(!is_new);
+
+        let item = get_item(&conn, id).unwrap().unwrap();
+        assert_eq!(item.category, "code");
+        assert_eq!(item.app_name.as_deref(), Some("Editor"));
+        assert_eq!(list_items(&conn, &ItemQuery::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_item_tags_come_from_view() {
+        let conn = test_connection();
+        let id = insert(&conn, "tagged", "text", 1_000, false);
+        conn.execute(
+            "INSERT INTO item_tags (item_id, tag_id) SELECT ?1, id FROM tags WHERE name = 'Work'",
+            params![id],
+        )
+        .unwrap();
+
+        assert_eq!(get_item(&conn, id).unwrap().unwrap().tags, vec!["Work".to_string()]);
+    }
+
+    #[test]
+    fn test_filtered_items() {
+        let conn = test_connection();
+        insert(&conn, "old note", "text", 1_000, false);
+        let tagged = insert(&conn, "pinned code", "code", 2_000, true);
+        insert(&conn, "hunter2", "password", 3_000, false);
+        conn.execute(
+            "INSERT INTO item_tags (item_id, tag_id) SELECT ?1, id FROM tags WHERE name = 'Work'",
+            params![tagged],
+        )
+        .unwrap();
+
+        let collect = |filter: &ItemFilter| {
+            let mut contents = Vec::new();
+            for_each_filtered(&conn, filter, |item| {
+                contents.push(item.content);
+                Ok(())
+            })
+            .unwrap();
+            contents
+        };
+
+        // Sensitive categories are left out by default
+        assert_eq!(collect(&ItemFilter::default()), vec!["pinned code", "old note"]);
+        assert_eq!(
+            collect(&ItemFilter { include_sensitive: true, ..Default::default() }),
+            vec!["hunter2", "pinned code", "old note"]
+        );
+        assert_eq!(collect(&ItemFilter { since: Some(1_500), ..Default::default() }), vec!["pinned code"]);
+        assert_eq!(collect(&ItemFilter { until: Some(1_500), ..Default::default() }), vec!["old note"]);
+        assert_eq!(collect(&ItemFilter { tags: vec!["Work".into()], ..Default::default() }), vec!["pinned code"]);
+        assert_eq!(collect(&ItemFilter { pinned_only: true, ..Default::default() }), vec!["pinned code"]);
+        assert_eq!(
+            collect(&ItemFilter { categories: vec!["text".into()], ..Default::default() }),
+            vec!["old note"]
+        );
+
+        let totals = filtered_totals(&conn, &ItemFilter::default()).unwrap();
+        assert_eq!(totals.item_count, 2);
+        assert_eq!(totals.content_bytes, ("old note".len() + "pinned code".len()) as u64);
+    }
+
+    #[test]
+    fn test_history_stats() {
+        let conn = test_connection();
+        insert(&conn, "a", "text", 1_000, true);
+        insert(&conn, "b", "code", 2_000, false);
+        let deleted = insert(&conn, "c", "code", 3_000, false);
+        soft_delete(&conn, deleted).unwrap();
+
+        let stats = history_stats(&conn).unwrap();
+        assert_eq!(stats.total_items, 2);
+        assert_eq!(stats.pinned_items, 1);
+        assert_eq!(stats.deleted_items, 1);
+        assert_eq!(stats.by_category.get("code"), Some(&1));
+        assert_eq!(stats.oldest_timestamp, Some(1_000));
+        assert_eq!(stats.newest_timestamp, Some(2_000));
+    }
+}
@@ -4,6 +4,11 @@
 
 use tauri_plugin_sql::{Migration, MigrationKind};
 
+pub mod items;
+
+/// Reindexes the search tables from clipboard_items (see rebuild_search_index)
+pub const REBUILD_SEARCH_INDEX: &str = include_str!("search_index_rebuild.sql");
+
 /// Initialize the database with schema and migrations
 pub fn init_database() -> Vec<Migration> {
     vec![
@@ -21,6 +26,61 @@ pub fn init_database() -> Vec<Migration> {
             sql: "ALTER TABLE clipboard_items ADD COLUMN app_bundle_id TEXT;",
             kind: MigrationKind::Up,
         },
+        // Migration 3: Trigram index for fuzzy search (see fuzzy.rs)
+        Migration {
+            version: 3,
+            description: "create_trigram_index",
+            sql: include_str!("trigram.sql"),
+            kind: MigrationKind::Up,
+        },
+        // Migration 4: Stop indexing trashed and sensitive items, index search_text and tags
+        Migration {
+            version: 4,
+            description: "rebuild_search_index",
+            sql: concat!(include_str!("search_index.sql"), include_str!("search_index_rebuild.sql")),
+            kind: MigrationKind::Up,
+        },
+        // Migration 5: Text decoded from QR codes and barcodes in images (see barcode.rs)
+        Migration {
+            version: 5,
+            description: "add_decoded_text_columns",
+            sql: "ALTER TABLE clipboard_items ADD COLUMN decoded_text TEXT;
+                  ALTER TABLE clipboard_items ADD COLUMN decoded_type TEXT;",
+            kind: MigrationKind::Up,
+        },
+        // Migration 6: Perceptual hashes and stacks of near-duplicate images (see similar_images.rs)
+        Migration {
+            version: 6,
+            description: "add_image_hash_and_stacks",
+            sql: "ALTER TABLE clipboard_items ADD COLUMN image_phash TEXT;
+                  ALTER TABLE clipboard_items ADD COLUMN stack_id INTEGER;
+                  CREATE INDEX IF NOT EXISTS idx_clipboard_items_stack_id ON clipboard_items(stack_id);",
+            kind: MigrationKind::Up,
+        },
+        // Migration 7: Color palettes of images, as JSON (see palette.rs)
+        Migration {
+            version: 7,
+            description: "add_image_palette_column",
+            sql: "ALTER TABLE clipboard_items ADD COLUMN image_palette TEXT;",
+            kind: MigrationKind::Up,
+        },
+        // Migration 8: What image files say about themselves (see image_metadata.rs)
+        Migration {
+            version: 8,
+            description: "add_image_details_columns",
+            sql: "ALTER TABLE clipboard_items ADD COLUMN image_format TEXT;
+                  ALTER TABLE clipboard_items ADD COLUMN image_color_space TEXT;
+                  ALTER TABLE clipboard_items ADD COLUMN image_dpi INTEGER;
+                  ALTER TABLE clipboard_items ADD COLUMN image_has_alpha INTEGER;",
+            kind: MigrationKind::Up,
+        },
+        // Migration 9: Animated GIF, WebP and PNG images (see clipboard_formats.rs)
+        Migration {
+            version: 9,
+            description: "add_image_animated_column",
+            sql: "ALTER TABLE clipboard_items ADD COLUMN image_animated INTEGER;",
+            kind: MigrationKind::Up,
+        },
     ]
 }
 
@@ -36,8 +96,15 @@ mod tests {
     #[test]
     fn test_init_database() {
         let migrations = init_database();
-        assert_eq!(migrations.len(), 2);
+        assert_eq!(migrations.len(), 9);
         assert_eq!(migrations[0].version, 1);
         assert_eq!(migrations[1].version, 2);
+        assert_eq!(migrations[2].version, 3);
+        assert_eq!(migrations[3].version, 4);
+        assert_eq!(migrations[4].version, 5);
+        assert_eq!(migrations[5].version, 6);
+        assert_eq!(migrations[6].version, 7);
+        assert_eq!(migrations[7].version, 8);
+        assert_eq!(migrations[8].version, 9);
     }
 }
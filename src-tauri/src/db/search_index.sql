@@ -0,0 +1,96 @@
+-- ============================================
+-- SEARCH INDEX: ONLY ACTIVE, NON-SENSITIVE ITEMS
+-- ============================================
+-- Replaces the original clipboard_items_fts, which indexed every row
+-- (trashed items, passwords and API keys included) and whose delete and
+-- update triggers never removed the old terms from the external-content
+-- index. The new table keeps its own copy of the text so it can also hold
+-- search_text and tag names, which aren't columns of clipboard_items.
+-- The trigram index (trigram.sql) follows the same rules.
+-- Sensitive categories match SENSITIVE_CATEGORIES in db/items.rs.
+
+DROP TRIGGER IF EXISTS clipboard_items_fts_insert;
+DROP TRIGGER IF EXISTS clipboard_items_fts_delete;
+DROP TRIGGER IF EXISTS clipboard_items_fts_update;
+DROP TABLE IF EXISTS clipboard_items_fts;
+
+DROP TRIGGER IF EXISTS clipboard_items_trigram_insert;
+DROP TRIGGER IF EXISTS clipboard_items_trigram_delete;
+DROP TRIGGER IF EXISTS clipboard_items_trigram_update;
+
+CREATE VIRTUAL TABLE clipboard_items_fts USING fts5(
+    content,
+    app_name,
+    category,
+    search_text,
+    tags
+);
+
+-- Items: (re)index a row only while it is active and not sensitive
+CREATE TRIGGER clipboard_items_fts_insert AFTER INSERT ON clipboard_items
+WHEN new.is_deleted = 0 AND new.category NOT IN ('password', 'apikey', 'private')
+BEGIN
+    INSERT INTO clipboard_items_fts(rowid, content, app_name, category, search_text, tags)
+    VALUES (new.id, new.content, new.app_name, new.category, new.search_text,
+            (SELECT group_concat(t.name, ' ') FROM item_tags it JOIN tags t ON t.id = it.tag_id
+             WHERE it.item_id = new.id));
+END;
+
+CREATE TRIGGER clipboard_items_fts_delete AFTER DELETE ON clipboard_items BEGIN
+    DELETE FROM clipboard_items_fts WHERE rowid = old.id;
+END;
+
+CREATE TRIGGER clipboard_items_fts_update AFTER UPDATE ON clipboard_items BEGIN
+    DELETE FROM clipboard_items_fts WHERE rowid = old.id;
+    INSERT INTO clipboard_items_fts(rowid, content, app_name, category, search_text, tags)
+    SELECT new.id, new.content, new.app_name, new.category, new.search_text,
+           (SELECT group_concat(t.name, ' ') FROM item_tags it JOIN tags t ON t.id = it.tag_id
+            WHERE it.item_id = new.id)
+    WHERE new.is_deleted = 0 AND new.category NOT IN ('password', 'apikey', 'private');
+END;
+
+-- Tags: keep the tags column current as tags are added, removed or renamed
+CREATE TRIGGER clipboard_items_fts_tag_insert AFTER INSERT ON item_tags BEGIN
+    UPDATE clipboard_items_fts
+    SET tags = (SELECT group_concat(t.name, ' ') FROM item_tags it JOIN tags t ON t.id = it.tag_id
+                WHERE it.item_id = new.item_id)
+    WHERE rowid = new.item_id;
+END;
+
+CREATE TRIGGER clipboard_items_fts_tag_delete AFTER DELETE ON item_tags BEGIN
+    UPDATE clipboard_items_fts
+    SET tags = (SELECT group_concat(t.name, ' ') FROM item_tags it JOIN tags t ON t.id = it.tag_id
+                WHERE it.item_id = old.item_id)
+    WHERE rowid = old.item_id;
+END;
+
+CREATE TRIGGER clipboard_items_fts_tag_rename AFTER UPDATE OF name ON tags BEGIN
+    UPDATE clipboard_items_fts
+    SET tags = (SELECT group_concat(t.name, ' ') FROM item_tags it JOIN tags t ON t.id = it.tag_id
+                WHERE it.item_id = clipboard_items_fts.rowid)
+    WHERE rowid IN (SELECT item_id FROM item_tags WHERE tag_id = new.id);
+END;
+
+-- Trigram index: external content, so a row is removed with the exact values
+-- it was indexed with, and only if it was indexed at all
+CREATE TRIGGER clipboard_items_trigram_insert AFTER INSERT ON clipboard_items
+WHEN new.is_deleted = 0 AND new.category NOT IN ('password', 'apikey', 'private')
+BEGIN
+    INSERT INTO clipboard_items_trigram(rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER clipboard_items_trigram_delete AFTER DELETE ON clipboard_items
+WHEN old.is_deleted = 0 AND old.category NOT IN ('password', 'apikey', 'private')
+BEGIN
+    INSERT INTO clipboard_items_trigram(clipboard_items_trigram, rowid, content)
+    VALUES ('delete', old.id, old.content);
+END;
+
+CREATE TRIGGER clipboard_items_trigram_update AFTER UPDATE OF content, category, is_deleted ON clipboard_items BEGIN
+    INSERT INTO clipboard_items_trigram(clipboard_items_trigram, rowid, content)
+    SELECT 'delete', old.id, old.content
+    WHERE old.is_deleted = 0 AND old.category NOT IN ('password', 'apikey', 'private');
+    INSERT INTO clipboard_items_trigram(rowid, content)
+    SELECT new.id, new.content
+    WHERE new.is_deleted = 0 AND new.category NOT IN ('password', 'apikey', 'private');
+END;
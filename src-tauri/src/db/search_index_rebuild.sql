@@ -0,0 +1,15 @@
+-- Reindex every active, non-sensitive item from scratch
+-- Run by migration 4 and by the rebuild_search_index command.
+
+DELETE FROM clipboard_items_fts;
+INSERT INTO clipboard_items_fts(rowid, content, app_name, category, search_text, tags)
+SELECT c.id, c.content, c.app_name, c.category, c.search_text,
+       (SELECT group_concat(t.name, ' ') FROM item_tags it JOIN tags t ON t.id = it.tag_id
+        WHERE it.item_id = c.id)
+FROM clipboard_items c
+WHERE c.is_deleted = 0 AND c.category NOT IN ('password', 'apikey', 'private');
+
+INSERT INTO clipboard_items_trigram(clipboard_items_trigram) VALUES ('delete-all');
+INSERT INTO clipboard_items_trigram(rowid, content)
+SELECT id, content FROM clipboard_items
+WHERE is_deleted = 0 AND category NOT IN ('password', 'apikey', 'private');
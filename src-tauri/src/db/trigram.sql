@@ -0,0 +1,31 @@
+-- ============================================
+-- TRIGRAM INDEX FOR FUZZY SEARCH
+-- ============================================
+-- Every 3-character sequence of item content, so misspelled queries
+-- ("kubctl") still find candidates that share fragments ("kub", "ctl")
+
+CREATE VIRTUAL TABLE IF NOT EXISTS clipboard_items_trigram USING fts5(
+    content,
+    content='clipboard_items',
+    content_rowid='id',
+    tokenize='trigram'
+);
+
+-- External-content tables must be told the old values to remove them
+CREATE TRIGGER IF NOT EXISTS clipboard_items_trigram_insert AFTER INSERT ON clipboard_items BEGIN
+    INSERT INTO clipboard_items_trigram(rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS clipboard_items_trigram_delete AFTER DELETE ON clipboard_items BEGIN
+    INSERT INTO clipboard_items_trigram(clipboard_items_trigram, rowid, content)
+    VALUES ('delete', old.id, old.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS clipboard_items_trigram_update AFTER UPDATE OF content ON clipboard_items BEGIN
+    INSERT INTO clipboard_items_trigram(clipboard_items_trigram, rowid, content)
+    VALUES ('delete', old.id, old.content);
+    INSERT INTO clipboard_items_trigram(rowid, content) VALUES (new.id, new.content);
+END;
+
+-- Index the existing history
+INSERT INTO clipboard_items_trigram(clipboard_items_trigram) VALUES ('rebuild');
@@ -0,0 +1,373 @@
+// Encryption Module - Passphrase-protected exports, archives and backups
+// File layout:
+//   "COPYGUM-ENCRYPTED\n"   magic, so imports can tell they need a passphrase
+//   version (u8)
+//   Argon2id memory KiB, iterations, lanes (u32 LE each)
+//   salt (16 bytes), stream nonce (19 bytes)
+//   XChaCha20-Poly1305 STREAM chunks of 64 KiB plaintext + 16-byte tag
+// The header is authenticated with every chunk and the last chunk is marked,
+// so tampering, a wrong passphrase and truncation are all detected.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{KeyInit, Payload};
+use chacha20poly1305::XChaCha20Poly1305;
+use zeroize::Zeroize;
+
+/// First bytes of every encrypted file
+pub const MAGIC: &[u8] = b"COPYGUM-ENCRYPTED\n";
+const FORMAT_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+/// XChaCha20 nonce (24) minus the STREAM counter and last-chunk flag (5)
+const NONCE_LEN: usize = 19;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 12 + SALT_LEN + NONCE_LEN;
+
+const CHUNK_LEN: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+
+/// Error returned when an encrypted file is opened without a passphrase
+/// The frontend matches on it to prompt for one.
+pub const PASSPHRASE_REQUIRED: &str = "This file is encrypted; a passphrase is required";
+const WRONG_PASSPHRASE: &str = "Wrong passphrase or damaged file";
+
+/// Argon2id cost, stored in the header so it can be raised later
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub lanes: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self { memory_kib: 64 * 1024, iterations: 3, lanes: 1 }
+    }
+}
+
+impl KdfParams {
+    /// Reject headers that would make us allocate or spin unreasonably
+    fn check(&self) -> Result<(), String> {
+        if self.memory_kib > 1024 * 1024 || self.iterations > 16 || self.lanes == 0 || self.lanes > 16 {
+            return Err("Unsupported encryption parameters".to_string());
+        }
+        Ok(())
+    }
+
+    fn derive_key(&self, passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+        self.check()?;
+        let params = Params::new(self.memory_kib, self.iterations, self.lanes, Some(32))
+            .map_err(|e| format!("Invalid encryption parameters: {}", e))?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("Failed to derive key: {}", e))?;
+        Ok(key)
+    }
+}
+
+/// Whether the bytes start with the encrypted file header
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Whether the file at `path` is encrypted
+pub fn is_encrypted_file(path: &Path) -> Result<bool, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut start = vec![0u8; MAGIC.len()];
+    match file.read_exact(&mut start) {
+        Ok(()) => Ok(is_encrypted(&start)),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+fn cipher(key: &mut [u8; 32]) -> XChaCha20Poly1305 {
+    let cipher = XChaCha20Poly1305::new(&(*key).into());
+    key.zeroize();
+    cipher
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+    Ok(bytes)
+}
+
+// ============================================
+// WRITING
+// ============================================
+
+/// Encrypts everything written to it; call `finish` to write the last chunk
+pub struct EncryptWriter<W: Write> {
+    inner: W,
+    encryptor: EncryptorBE32<XChaCha20Poly1305>,
+    header: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    pub fn new(inner: W, passphrase: &str) -> Result<Self, String> {
+        Self::with_params(inner, passphrase, KdfParams::default())
+    }
+
+    pub fn with_params(mut inner: W, passphrase: &str, params: KdfParams) -> Result<Self, String> {
+        if passphrase.is_empty() {
+            return Err("Passphrase must not be empty".to_string());
+        }
+        let salt = random_bytes::<SALT_LEN>()?;
+        let nonce = random_bytes::<NONCE_LEN>()?;
+        let mut key = params.derive_key(passphrase, &salt)?;
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.push(FORMAT_VERSION);
+        header.extend_from_slice(&params.memory_kib.to_le_bytes());
+        header.extend_from_slice(&params.iterations.to_le_bytes());
+        header.extend_from_slice(&params.lanes.to_le_bytes());
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&nonce);
+        inner
+            .write_all(&header)
+            .map_err(|e| format!("Failed to write encrypted file: {}", e))?;
+
+        Ok(Self {
+            inner,
+            encryptor: EncryptorBE32::from_aead(cipher(&mut key), (&nonce).into()),
+            header,
+            buffer: Vec::with_capacity(CHUNK_LEN * 2),
+        })
+    }
+
+    /// Encrypt the remaining data as the final chunk and return the inner writer
+    pub fn finish(mut self) -> Result<W, String> {
+        let last = self
+            .encryptor
+            .encrypt_last(Payload { msg: &self.buffer, aad: &self.header })
+            .map_err(|_| "Failed to encrypt export".to_string())?;
+        self.buffer.zeroize();
+        self.inner
+            .write_all(&last)
+            .and_then(|_| self.inner.flush())
+            .map_err(|e| format!("Failed to write encrypted file: {}", e))?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        // A full chunk is only written once more data follows; `finish` writes the last one
+        while self.buffer.len() > CHUNK_LEN {
+            let chunk = self
+                .encryptor
+                .encrypt_next(Payload { msg: &self.buffer[..CHUNK_LEN], aad: &self.header })
+                .map_err(|_| io::Error::other("Failed to encrypt export"))?;
+            self.inner.write_all(&chunk)?;
+            self.buffer.drain(..CHUNK_LEN);
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// ============================================
+// READING
+// ============================================
+
+/// Decrypts a file written by EncryptWriter
+/// The first chunk is checked on creation, so a wrong passphrase fails early.
+pub struct DecryptReader<R: Read> {
+    inner: R,
+    decryptor: Option<DecryptorBE32<XChaCha20Poly1305>>,
+    header: Vec<u8>,
+    plain: Vec<u8>,
+    pos: usize,
+    /// First byte of the next chunk, read to find out whether the current one is last
+    lookahead: Option<u8>,
+}
+
+impl<R: Read> DecryptReader<R> {
+    pub fn new(mut inner: R, passphrase: &str) -> Result<Self, String> {
+        let mut header = vec![0u8; HEADER_LEN];
+        inner
+            .read_exact(&mut header)
+            .map_err(|_| "Not an encrypted CopyGum file".to_string())?;
+        if !is_encrypted(&header) {
+            return Err("Not an encrypted CopyGum file".to_string());
+        }
+        let mut fields = &header[MAGIC.len()..];
+        if fields[0] != FORMAT_VERSION {
+            return Err(format!("Unsupported encryption version {}", fields[0]));
+        }
+        fields = &fields[1..];
+        let u32_at = |offset: usize| u32::from_le_bytes(fields[offset..offset + 4].try_into().unwrap());
+        let params = KdfParams { memory_kib: u32_at(0), iterations: u32_at(4), lanes: u32_at(8) };
+        let salt = &fields[12..12 + SALT_LEN];
+        let nonce: [u8; NONCE_LEN] = fields[12 + SALT_LEN..].try_into().unwrap();
+
+        let mut key = params.derive_key(passphrase, salt)?;
+        let mut reader = Self {
+            inner,
+            decryptor: Some(DecryptorBE32::from_aead(cipher(&mut key), (&nonce).into())),
+            header,
+            plain: Vec::new(),
+            pos: 0,
+            lookahead: None,
+        };
+        reader.next_chunk().map_err(|e| e.to_string())?;
+        Ok(reader)
+    }
+
+    fn next_chunk(&mut self) -> io::Result<()> {
+        let Some(decryptor) = self.decryptor.as_mut() else {
+            self.plain.clear();
+            self.pos = 0;
+            return Ok(());
+        };
+
+        let mut chunk = Vec::with_capacity(CHUNK_LEN + TAG_LEN);
+        chunk.extend(self.lookahead.take());
+        (&mut self.inner).take((CHUNK_LEN + TAG_LEN - chunk.len()) as u64).read_to_end(&mut chunk)?;
+
+        let mut next = [0u8; 1];
+        let is_last = chunk.len() < CHUNK_LEN + TAG_LEN || self.inner.read(&mut next)? == 0;
+        let payload = Payload { msg: &chunk, aad: &self.header };
+
+        self.plain.zeroize();
+        self.plain = if is_last {
+            let decryptor = self.decryptor.take().unwrap();
+            decryptor.decrypt_last(payload)
+        } else {
+            self.lookahead = Some(next[0]);
+            decryptor.decrypt_next(payload)
+        }
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, WRONG_PASSPHRASE))?;
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.plain.len() {
+            if self.decryptor.is_none() {
+                return Ok(0);
+            }
+            self.next_chunk()?;
+        }
+        let len = out.len().min(self.plain.len() - self.pos);
+        out[..len].copy_from_slice(&self.plain[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+impl<R: Read> Drop for DecryptReader<R> {
+    fn drop(&mut self) {
+        self.plain.zeroize();
+    }
+}
+
+// ============================================
+// FILE HELPERS
+// ============================================
+
+/// Decrypt file contents if they are encrypted, otherwise return them unchanged
+/// Fails with PASSPHRASE_REQUIRED when they are encrypted and no passphrase was given.
+pub fn decrypt_if_encrypted(bytes: Vec<u8>, passphrase: Option<&str>) -> Result<Vec<u8>, String> {
+    if !is_encrypted(&bytes) {
+        return Ok(bytes);
+    }
+    let passphrase = passphrase.ok_or_else(|| PASSPHRASE_REQUIRED.to_string())?;
+    let mut plain = Vec::with_capacity(bytes.len());
+    DecryptReader::new(bytes.as_slice(), passphrase)?
+        .read_to_end(&mut plain)
+        .map_err(|e| e.to_string())?;
+    Ok(plain)
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
+/// Lets the frontend ask for a passphrase before importing
+#[tauri::command]
+pub fn is_encrypted_export(file_path: String) -> Result<bool, String> {
+    is_encrypted_file(Path::new(&file_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap parameters so the tests don't spend seconds in Argon2
+    const TEST_PARAMS: KdfParams = KdfParams { memory_kib: 64, iterations: 1, lanes: 1 };
+
+    fn encrypt(data: &[u8], passphrase: &str) -> Vec<u8> {
+        let mut writer = EncryptWriter::with_params(Vec::new(), passphrase, TEST_PARAMS).unwrap();
+        // Uneven writes to exercise chunk boundaries
+        for piece in data.chunks(1000) {
+            writer.write_all(piece).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+        let mut plain = Vec::new();
+        DecryptReader::new(data, passphrase)?
+            .read_to_end(&mut plain)
+            .map_err(|e| e.to_string())?;
+        Ok(plain)
+    }
+
+    #[test]
+    fn test_round_trip_across_chunk_sizes() {
+        for len in [0, 1, CHUNK_LEN - 1, CHUNK_LEN, CHUNK_LEN + 1, 3 * CHUNK_LEN] {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let encrypted = encrypt(&data, "correct horse");
+            assert!(is_encrypted(&encrypted));
+            assert_eq!(decrypt(&encrypted, "correct horse").unwrap(), data, "length {}", len);
+        }
+    }
+
+    #[test]
+    fn test_wrong_passphrase_tampering_and_truncation_fail() {
+        let data = vec![7u8; 2 * CHUNK_LEN + 10];
+        let encrypted = encrypt(&data, "secret");
+
+        assert_eq!(decrypt(&encrypted, "Secret").unwrap_err(), WRONG_PASSPHRASE);
+
+        let mut tampered = encrypted.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(decrypt(&tampered, "secret").is_err());
+
+        // Cut exactly at a chunk boundary: the remaining chunk isn't marked last
+        let truncated = &encrypted[..HEADER_LEN + CHUNK_LEN + TAG_LEN];
+        assert!(decrypt(truncated, "secret").is_err());
+    }
+
+    #[test]
+    fn test_passphrase_required_only_when_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("plain.json");
+        let locked = dir.path().join("locked.json");
+        fs::write(&plain, b"{}").unwrap();
+        fs::write(&locked, encrypt(b"{\"a\":1}", "pw")).unwrap();
+
+        assert!(!is_encrypted_file(&plain).unwrap());
+        assert!(is_encrypted_file(&locked).unwrap());
+        assert_eq!(decrypt_if_encrypted(b"{}".to_vec(), None).unwrap(), b"{}");
+        let locked = fs::read(&locked).unwrap();
+        assert_eq!(decrypt_if_encrypted(locked.clone(), None).unwrap_err(), PASSPHRASE_REQUIRED);
+        assert_eq!(decrypt_if_encrypted(locked, Some("pw")).unwrap(), b"{\"a\":1}");
+    }
+}
@@ -1,8 +1,22 @@
 // Export/Import Functionality
-// Handles exporting clipboard history to JSON/CSV and importing from JSON
+// Handles exporting clipboard history and importing from JSON
+// Exports read copygum.db directly and stream items to the output file, so
+// large histories never have to be held in memory (or passed through the
+// frontend). Formats: JSON and CSV (same layout as the original frontend
+// export), Markdown, and a self-contained HTML report with images inlined.
 
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io::{BufWriter, Write};
+use tauri::{AppHandle, Emitter};
+
+use crate::db::items::{self, ClipboardItem, FilteredTotals, ItemFilter};
+use crate::encryption::{self, EncryptWriter};
+use crate::paths::database_path;
+
+/// Emit an export-progress event every this many items
+const PROGRESS_INTERVAL: usize = 200;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExportData {
@@ -11,40 +25,438 @@ pub struct ExportData {
     pub item_count: usize,
 }
 
+/// Preview shown before exporting
+#[derive(Debug, Serialize)]
+pub struct ExportStats {
+    #[serde(flatten)]
+    pub info: ExportData,
+    pub image_count: usize,
+    pub content_bytes: u64,
+    pub image_bytes: u64,
+}
+
+/// Result of a finished export
+#[derive(Debug, Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub item_count: usize,
+    pub bytes: u64,
+}
+
+/// Export file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            other => Err(format!(
+                "Unsupported export format: {} (expected json, csv, markdown or html)",
+                other
+            )),
+        }
+    }
+}
+
+fn export_info(item_count: usize) -> ExportData {
+    ExportData {
+        version: "1.0".to_string(),
+        export_date: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        item_count,
+    }
+}
+
+// ============================================
+// WRITER
+// ============================================
+
+/// Writes an export one item at a time
+pub struct ExportWriter<W: Write> {
+    out: W,
+    format: ExportFormat,
+    written: usize,
+}
+
+impl<W: Write> ExportWriter<W> {
+    pub fn new(out: W, format: ExportFormat) -> Self {
+        Self { out, format, written: 0 }
+    }
+
+    /// Write the header; `item_count` goes into the JSON header and report title
+    pub fn begin(&mut self, item_count: usize) -> Result<(), String> {
+        let info = export_info(item_count);
+        let header = match self.format {
+            ExportFormat::Json => format!(
+                "{{\n  \"version\": {},\n  \"export_date\": {},\n  \"item_count\": {},\n  \"items\": [",
+                json_string(&info.version),
+                json_string(&info.export_date),
+                item_count
+            ),
+            ExportFormat::Csv => "Content,Type,Category,Date,Pinned,Tags,App Name".to_string(),
+            ExportFormat::Markdown => format!(
+                "# CopyGum Export\n\n_Exported {} · {} items_\n",
+                local_date(chrono::Utc::now().timestamp_millis()),
+                item_count
+            ),
+            ExportFormat::Html => html_header(item_count),
+        };
+        self.write(&header)
+    }
+
+    pub fn item(&mut self, item: &ClipboardItem) -> Result<(), String> {
+        let entry = match self.format {
+            ExportFormat::Json => {
+                let separator = if self.written == 0 { "" } else { "," };
+                let json = serde_json::to_string(item)
+                    .map_err(|e| format!("Failed to serialize item {}: {}", item.id, e))?;
+                format!("{}\n    {}", separator, json)
+            }
+            ExportFormat::Csv => format!("\n{}", csv_row(item)),
+            ExportFormat::Markdown => markdown_entry(item),
+            ExportFormat::Html => html_entry(item),
+        };
+        self.written += 1;
+        self.write(&entry)
+    }
+
+    /// Close the document and flush; returns the number of items written
+    pub fn finish(mut self) -> Result<usize, String> {
+        let footer = match self.format {
+            ExportFormat::Json if self.written == 0 => "]\n}\n",
+            ExportFormat::Json => "\n  ]\n}\n",
+            ExportFormat::Csv => "",
+            ExportFormat::Markdown => "",
+            ExportFormat::Html => "</main>\n</body>\n</html>\n",
+        };
+        self.write(footer)?;
+        self.out
+            .flush()
+            .map_err(|e| format!("Failed to write export: {}", e))?;
+        Ok(self.written)
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        self.out
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to write export: {}", e))
+    }
+}
+
+/// Stream every item matching `filter` to `out`
+///
+/// `progress` is called with (done, total) periodically and once at the end.
+pub fn export_items<W: Write>(
+    conn: &rusqlite::Connection,
+    out: W,
+    format: ExportFormat,
+    filter: &ItemFilter,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<usize, String> {
+    let total = items::filtered_totals(conn, filter)?.item_count;
+    let mut writer = ExportWriter::new(out, format);
+    writer.begin(total)?;
+
+    let mut done = 0;
+    items::for_each_filtered(conn, filter, |item| {
+        writer.item(&item)?;
+        done += 1;
+        if done % PROGRESS_INTERVAL == 0 {
+            progress(done, total);
+        }
+        Ok(())
+    })?;
+
+    let written = writer.finish()?;
+    progress(written, total);
+    Ok(written)
+}
+
+// ============================================
+// FORMATS
+// ============================================
+
+fn json_string(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+fn iso_date(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp)
+        .map(|d| d.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+        .unwrap_or_default()
+}
+
+fn local_date(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp)
+        .map(|d| d.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// CSV row with the same columns as the frontend export
+fn csv_row(item: &ClipboardItem) -> String {
+    [
+        // Content is always quoted, with newlines flattened to spaces
+        format!("\"{}\"", item.content.replace('"', "\"\"").replace('\n', " ")),
+        csv_field(&item.content_type),
+        csv_field(&item.category),
+        iso_date(item.timestamp),
+        if item.is_pinned { "Yes" } else { "No" }.to_string(),
+        csv_field(&item.tags.join(", ")),
+        csv_field(item.app_name.as_deref().unwrap_or("")),
+    ]
+    .join(",")
+}
+
+/// Quote a CSV field only when it needs it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn markdown_entry(item: &ClipboardItem) -> String {
+    let mut entry = format!(
+        "\n## {} · {}{}\n\n",
+        local_date(item.timestamp),
+        item.category,
+        if item.is_pinned { " · 📌" } else { "" }
+    );
+
+    let mut details = Vec::new();
+    if !item.tags.is_empty() {
+        details.push(format!("Tags: {}", item.tags.join(", ")));
+    }
+    if let Some(app_name) = &item.app_name {
+        details.push(format!("From: {}", app_name));
+    }
+    if !details.is_empty() {
+        entry.push_str(&format!("_{}_\n\n", details.join(" · ")));
+    }
+
+    if item.is_image {
+        let size = match (item.image_width, item.image_height) {
+            (Some(w), Some(h)) => format!(" ({}×{})", w, h),
+            _ => String::new(),
+        };
+        entry.push_str(&format!("Image{}: `{}`\n", size, item.image_path.as_deref().unwrap_or("")));
+    } else {
+        // The fence must be longer than any backtick run inside the content
+        let fence = "`".repeat(longest_run(&item.content, '`').max(2) + 1);
+        entry.push_str(&format!("{}\n{}\n{}\n", fence, item.content, fence));
+    }
+    entry
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    let (mut longest, mut current) = (0, 0);
+    for ch in text.chars() {
+        current = if ch == c { current + 1 } else { 0 };
+        longest = longest.max(current);
+    }
+    longest
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_header(item_count: usize) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>CopyGum Export</title>
+<style>
+body {{ font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; margin: 0; background: #f5f5f7; color: #1d1d1f; }}
+header {{ padding: 24px 32px; background: #fff; border-bottom: 1px solid #ddd; }}
+h1 {{ margin: 0 0 4px; font-size: 22px; }}
+.meta {{ color: #666; font-size: 13px; }}
+main {{ padding: 16px 32px; display: grid; gap: 12px; }}
+article {{ background: #fff; border-radius: 10px; padding: 12px 16px; box-shadow: 0 1px 3px rgba(0,0,0,.08); }}
+article .meta span + span::before {{ content: " · "; }}
+pre {{ white-space: pre-wrap; word-break: break-word; font-size: 13px; margin: 8px 0 0; }}
+img {{ max-width: 100%; max-height: 400px; margin-top: 8px; border-radius: 6px; }}
+</style>
+</head>
+<body>
+<header>
+<h1>CopyGum Export</h1>
+<div class="meta">Exported {} · {} items</div>
+</header>
+<main>
+"#,
+        escape_html(&local_date(chrono::Utc::now().timestamp_millis())),
+        item_count
+    )
+}
+
+fn html_entry(item: &ClipboardItem) -> String {
+    let mut meta = vec![
+        escape_html(&local_date(item.timestamp)),
+        escape_html(&item.category),
+    ];
+    if item.is_pinned {
+        meta.push("📌 Pinned".to_string());
+    }
+    if !item.tags.is_empty() {
+        meta.push(escape_html(&item.tags.join(", ")));
+    }
+    if let Some(app_name) = &item.app_name {
+        meta.push(escape_html(app_name));
+    }
+    let meta: String = meta.iter().map(|m| format!("<span>{}</span>", m)).collect();
+
+    let body = if item.is_image {
+        match item.image_path.as_deref().and_then(image_data_uri) {
+            Some(uri) => format!("<img src=\"{}\" alt=\"Clipboard image\">", uri),
+            None => format!("<pre>{}</pre>", escape_html(item.image_path.as_deref().unwrap_or("Image"))),
+        }
+    } else {
+        format!("<pre>{}</pre>", escape_html(&item.content))
+    };
+
+    format!("<article>\n<div class=\"meta\">{}</div>\n{}\n</article>\n", meta, body)
+}
+
+/// Inline an image so the report is a single file
+fn image_data_uri(path: &str) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let mime = match std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        _ => "image/png",
+    };
+    Some(format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
 /// Save export data to a file
 /// The actual data is prepared by the frontend and passed as a JSON string
 #[tauri::command]
 pub async fn save_export_file(
     file_path: String,
     data: String,
+    passphrase: Option<String>,
 ) -> Result<String, String> {
-    fs::write(&file_path, data)
-        .map_err(|e| format!("Failed to save export file: {}", e))?;
+    match passphrase {
+        Some(passphrase) => {
+            let file = fs::File::create(&file_path)
+                .map_err(|e| format!("Failed to save export file: {}", e))?;
+            let mut writer = EncryptWriter::new(BufWriter::new(file), &passphrase)?;
+            writer
+                .write_all(data.as_bytes())
+                .map_err(|e| format!("Failed to save export file: {}", e))?;
+            writer.finish()?;
+        }
+        None => fs::write(&file_path, data)
+            .map_err(|e| format!("Failed to save export file: {}", e))?,
+    }
 
     Ok(file_path)
 }
 
+/// Read a text file to import, tolerating a BOM and invalid UTF-8
+/// Encrypted exports are decrypted with `passphrase`.
+/// Shared with the importers for other clipboard managers
+pub fn read_import_text(path: &std::path::Path, passphrase: Option<&str>) -> Result<String, String> {
+    let bytes = fs::read(path)
+        .map_err(|e| format!("Failed to read import file: {}", e))?;
+    let bytes = encryption::decrypt_if_encrypted(bytes, passphrase)?;
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
 /// Read import file contents
 /// Returns the file contents as a string for frontend to parse
 #[tauri::command]
 pub async fn read_import_file(
     file_path: String,
+    passphrase: Option<String>,
 ) -> Result<String, String> {
-    let contents = fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read import file: {}", e))?;
-
-    Ok(contents)
+    read_import_text(std::path::Path::new(&file_path), passphrase.as_deref())
 }
 
 /// Get export statistics (called before export to show preview)
 #[tauri::command]
-pub async fn get_export_stats() -> Result<ExportData, String> {
-    // This will be called by frontend with actual item counts
-    Ok(ExportData {
-        version: "1.0".to_string(),
-        export_date: chrono::Local::now().to_rfc3339(),
-        item_count: 0,
+pub async fn get_export_stats(filter: Option<ItemFilter>) -> Result<ExportStats, String> {
+    let conn = items::open_read_only(&database_path()?)?;
+    Ok(export_stats(items::filtered_totals(&conn, &filter.unwrap_or_default())?))
+}
+
+fn export_stats(totals: FilteredTotals) -> ExportStats {
+    ExportStats {
+        info: export_info(totals.item_count),
+        image_count: totals.image_count,
+        content_bytes: totals.content_bytes,
+        image_bytes: totals.image_bytes,
+    }
+}
+
+/// Export history straight from the database to `file_path`
+/// Emits `export-progress` ({ done, total }) while writing
+#[tauri::command]
+pub async fn export_history(
+    app: AppHandle,
+    file_path: String,
+    format: String,
+    filter: Option<ItemFilter>,
+    passphrase: Option<String>,
+) -> Result<ExportSummary, String> {
+    let format = ExportFormat::parse(&format)?;
+    let filter = filter.unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = items::open_read_only(&database_path()?)?;
+        let file = fs::File::create(&file_path)
+            .map_err(|e| format!("Failed to save export file: {}", e))?;
+
+        let progress = |done, total| {
+            let _ = app.emit("export-progress", serde_json::json!({ "done": done, "total": total }));
+        };
+        let item_count = match passphrase {
+            Some(passphrase) => {
+                let mut writer = EncryptWriter::new(BufWriter::new(file), &passphrase)?;
+                let count = export_items(&conn, &mut writer, format, &filter, progress)?;
+                writer.finish()?;
+                count
+            }
+            None => export_items(&conn, BufWriter::new(file), format, &filter, progress)?,
+        };
+        let bytes = fs::metadata(&file_path).map(|m| m.len()).unwrap_or(0);
+
+        println!("[CopyGum] Exported {} items to {}", item_count, file_path);
+        Ok(ExportSummary { path: file_path, item_count, bytes })
     })
+    .await
+    .map_err(|e| format!("Export failed: {}", e))?
 }
 
 #[cfg(test)]
@@ -88,30 +500,167 @@ mod tests {
         let test_data = r#"{"items": [{"id": 1, "content": "test"}]}"#.to_string();
 
         // Save
-        let result = save_export_file(file_path.clone(), test_data.clone()).await;
+        let result = save_export_file(file_path.clone(), test_data.clone(), None).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), file_path);
 
         // Read back
-        let read_result = read_import_file(file_path).await;
+        let read_result = read_import_file(file_path, None).await;
         assert!(read_result.is_ok());
         assert_eq!(read_result.unwrap(), test_data);
     }
 
+    #[tokio::test]
+    async fn test_encrypted_export_needs_passphrase() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        let file_path = temp_file.path().to_string_lossy().to_string();
+        let test_data = r#"{"items": [{"content": "sk-secret"}]}"#.to_string();
+
+        save_export_file(file_path.clone(), test_data.clone(), Some("passphrase".to_string()))
+            .await
+            .unwrap();
+        assert!(!fs::read_to_string(&file_path).unwrap_or_default().contains("sk-secret"));
+
+        let err = read_import_file(file_path.clone(), None).await.unwrap_err();
+        assert_eq!(err, encryption::PASSPHRASE_REQUIRED);
+        let read = read_import_file(file_path, Some("passphrase".to_string())).await.unwrap();
+        assert_eq!(read, test_data);
+    }
+
     #[tokio::test]
     async fn test_read_nonexistent_file() {
-        let result = read_import_file("/nonexistent/path/file.json".to_string()).await;
+        let result = read_import_file("/nonexistent/path/file.json".to_string(), None).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Failed to read import file"));
     }
 
-    #[tokio::test]
-    async fn test_get_export_stats() {
-        let stats = get_export_stats().await.expect("Failed to get export stats");
+    fn render_export(items: &[ClipboardItem], format: ExportFormat) -> Result<String, String> {
+        let mut out = Vec::new();
+        let mut writer = ExportWriter::new(&mut out, format);
+        writer.begin(items.len())?;
+        for item in items {
+            writer.item(item)?;
+        }
+        writer.finish()?;
+        String::from_utf8(out).map_err(|e| format!("Failed to render export: {}", e))
+    }
+
+    fn sample_item() -> ClipboardItem {
+        ClipboardItem {
+            id: 7,
+            content: "say \"hi\"\nthere".to_string(),
+            content_type: "text".to_string(),
+            category: "text".to_string(),
+            app_name: Some("Terminal, Dev".to_string()),
+            is_image: false,
+            image_path: None,
+            image_width: None,
+            image_height: None,
+            timestamp: 1_705_314_600_000,
+            is_pinned: true,
+            tags: vec!["Work".to_string(), "Ideas".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_render_csv_matches_frontend_format() {
+        let csv = render_export(&[sample_item()], ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "Content,Type,Category,Date,Pinned,Tags,App Name");
+        assert_eq!(
+            lines[1],
+            r#""say ""hi"" there",text,text,2024-01-15T10:30:00.000Z,Yes,"Work, Ideas","Terminal, Dev""#
+        );
+    }
+
+    #[test]
+    fn test_render_json_includes_header_and_items() {
+        let json = render_export(&[sample_item(), sample_item()], ExportFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["version"], "1.0");
+        assert_eq!(value["item_count"], 2);
+        assert_eq!(value["items"][0]["id"], 7);
+        assert_eq!(value["items"][1]["tags"][1], "Ideas");
+
+        let empty: serde_json::Value =
+            serde_json::from_str(&render_export(&[], ExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(empty["items"].as_array().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_render_markdown_fences_content() {
+        let mut item = sample_item();
+        item.content = "run ```code```".to_string();
+        let markdown = render_export(&[item], ExportFormat::Markdown).unwrap();
 
-        assert_eq!(stats.version, "1.0");
-        assert_eq!(stats.item_count, 0);
+        assert!(markdown.starts_with("# CopyGum Export\n"));
+        assert!(markdown.contains("_Tags: Work, Ideas · From: Terminal, Dev_"));
+        assert!(markdown.contains("\n````\nrun ```code```\n````\n"));
+    }
+
+    #[test]
+    fn test_render_html_escapes_content() {
+        let mut item = sample_item();
+        item.content = "<script>alert(1)</script>".to_string();
+        let html = render_export(&[item], ExportFormat::Html).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<pre>&lt;script&gt;alert(1)&lt;/script&gt;</pre>"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_export_items_streams_filtered_rows_with_progress() {
+        let conn = items::test_connection();
+        for i in 0..3 {
+            conn.execute(
+                "INSERT INTO clipboard_items (content, content_type, category, is_image, timestamp, is_pinned, is_deleted)
+                 VALUES (?1, 'text', ?2, 0, ?3, 0, 0)",
+                rusqlite::params![format!("item {}", i), if i == 2 { "password" } else { "text" }, i],
+            )
+            .unwrap();
+        }
+
+        let mut calls = Vec::new();
+        let mut out = Vec::new();
+        let count = export_items(&conn, &mut out, ExportFormat::Csv, &ItemFilter::default(), |done, total| {
+            calls.push((done, total))
+        })
+        .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(calls.last(), Some(&(2, 2)));
+        let csv = String::from_utf8(out).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(!csv.contains("item 2"));
+    }
+
+    #[test]
+    fn test_export_stats_reports_real_counts() {
+        let stats = export_stats(FilteredTotals {
+            item_count: 12,
+            image_count: 2,
+            content_bytes: 300,
+            image_bytes: 4096,
+        });
+
+        assert_eq!(stats.info.version, "1.0");
+        assert_eq!(stats.info.item_count, 12);
         // Date should be a valid RFC3339 string
-        assert!(stats.export_date.contains("T"));
+        assert!(stats.info.export_date.contains("T"));
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["item_count"], 12);
+        assert_eq!(json["image_bytes"], 4096);
+    }
+
+    #[test]
+    fn test_export_format_parse() {
+        assert_eq!(ExportFormat::parse("JSON").unwrap(), ExportFormat::Json);
+        assert_eq!(ExportFormat::parse("csv").unwrap(), ExportFormat::Csv);
+        assert_eq!(ExportFormat::parse("md").unwrap(), ExportFormat::Markdown);
+        assert_eq!(ExportFormat::parse("html").unwrap(), ExportFormat::Html);
+        assert!(ExportFormat::parse("xml").is_err());
     }
 }
@@ -0,0 +1,412 @@
+// Fuzzy Module - Typo-tolerant second search tier
+// Runs behind the exact fts5 search (search.rs) and finds what it misses:
+// - candidates come from the trigram index (db/trigram.sql), so "kubctl"
+//   reaches "kubectl" through the shared "kub" and "ctl", plus the newest
+//   items for misspellings that share no trigram at all
+// - candidates are scored in memory the way fzf does: query characters in
+//   order, with bonuses for word starts, camelCase and consecutive runs
+// - a term that isn't a subsequence may still match a word one or two
+//   edits away ("dokcer" -> "docker"), scored below real subsequences
+
+use std::collections::HashSet;
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+
+use crate::db::items::{self, ClipboardItem};
+use crate::query;
+use crate::search::{SearchHit, SearchQuery, SnippetPart};
+
+// Scores as in fzf's algo v1
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = -3;
+const SCORE_GAP_EXTENSION: i64 = -1;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CAMEL: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 4;
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+/// Cost of each edit when a term only matches with a typo
+const TYPO_PENALTY: i64 = 24;
+/// Terms shorter than this must match as a subsequence
+const MIN_TYPO_TERM: usize = 4;
+
+/// Only the start of long items is scored
+const MAX_SCORED_CHARS: usize = 4096;
+/// Best trigram matches to score
+const TRIGRAM_CANDIDATES: usize = 500;
+/// Newest items to score as well
+const RECENT_CANDIDATES: usize = 2000;
+/// Characters of context before the first matched character in a snippet
+const SNIPPET_LEAD: usize = 30;
+const SNIPPET_CHARS: usize = 120;
+
+/// Score and matched character positions of a text
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CharClass {
+    NonWord,
+    Lower,
+    Upper,
+    Number,
+    Letter,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_lowercase() {
+        CharClass::Lower
+    } else if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_numeric() {
+        CharClass::Number
+    } else if c.is_alphabetic() {
+        CharClass::Letter
+    } else {
+        CharClass::NonWord
+    }
+}
+
+/// Bonus for matching a character of class `class` right after `prev`
+fn bonus_for(prev: CharClass, class: CharClass) -> i64 {
+    match (prev, class) {
+        (_, CharClass::NonWord) => 0,
+        (CharClass::NonWord, _) => BONUS_BOUNDARY,
+        (CharClass::Lower, CharClass::Upper) => BONUS_CAMEL,
+        (CharClass::Number, CharClass::Number) => 0,
+        (_, CharClass::Number) => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+// ============================================
+// SCORING
+// ============================================
+
+/// fzf v1: find the first window containing the term in order, shrink it
+/// from the end, then score the characters matched inside it
+fn subsequence_match(text: &[char], hay: &[char], term: &[char]) -> Option<FuzzyMatch> {
+    let mut ti = 0;
+    let mut end = None;
+    for (i, &c) in hay.iter().enumerate() {
+        if c == term[ti] {
+            ti += 1;
+            if ti == term.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    let mut ti = term.len();
+    let mut start = end;
+    for i in (0..=end).rev() {
+        if hay[i] == term[ti - 1] {
+            ti -= 1;
+            if ti == 0 {
+                start = i;
+                break;
+            }
+        }
+    }
+
+    let mut score = 0;
+    let mut positions = Vec::with_capacity(term.len());
+    let mut prev_class = if start > 0 { char_class(text[start - 1]) } else { CharClass::NonWord };
+    let (mut in_gap, mut consecutive, mut first_bonus) = (false, 0, 0);
+    let mut ti = 0;
+    for i in start..=end {
+        let class = char_class(text[i]);
+        if ti < term.len() && hay[i] == term[ti] {
+            let mut bonus = bonus_for(prev_class, class);
+            if consecutive == 0 {
+                first_bonus = bonus;
+            } else {
+                if bonus >= BONUS_BOUNDARY && bonus > first_bonus {
+                    first_bonus = bonus;
+                }
+                bonus = bonus.max(first_bonus).max(BONUS_CONSECUTIVE);
+            }
+            score += SCORE_MATCH + if ti == 0 { bonus * BONUS_FIRST_CHAR_MULTIPLIER } else { bonus };
+            positions.push(i);
+            in_gap = false;
+            consecutive += 1;
+            ti += 1;
+        } else {
+            score += if in_gap { SCORE_GAP_EXTENSION } else { SCORE_GAP_START };
+            in_gap = true;
+            consecutive = 0;
+            first_bonus = 0;
+        }
+        prev_class = class;
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+/// Optimal string alignment distance (Levenshtein plus transpositions)
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// The closest word within one edit (two for terms of 8+ characters)
+fn typo_match(hay: &[char], term: &[char]) -> Option<FuzzyMatch> {
+    if term.len() < MIN_TYPO_TERM || term.iter().any(|c| !c.is_alphanumeric()) {
+        return None;
+    }
+    let allowed = if term.len() >= 8 { 2 } else { 1 };
+
+    let mut best: Option<(usize, usize, usize)> = None;
+    let mut i = 0;
+    while i < hay.len() {
+        if !hay[i].is_alphanumeric() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < hay.len() && hay[i].is_alphanumeric() {
+            i += 1;
+        }
+        let word = &hay[start..i];
+        if word.len().abs_diff(term.len()) > allowed {
+            continue;
+        }
+        let distance = edit_distance(word, term);
+        if distance <= allowed && best.is_none_or(|(d, _, _)| distance < d) {
+            best = Some((distance, start, i));
+        }
+    }
+
+    let (distance, start, end) = best?;
+    Some(FuzzyMatch {
+        score: SCORE_MATCH * term.len() as i64 + BONUS_BOUNDARY - TYPO_PENALTY * distance as i64,
+        positions: (start..end).collect(),
+    })
+}
+
+/// Score `text` against every term; None unless all of them match
+/// Terms are case-insensitive unless they contain an uppercase letter.
+pub fn fuzzy_match(text: &str, terms: &[String]) -> Option<FuzzyMatch> {
+    let text: Vec<char> = text.chars().take(MAX_SCORED_CHARS).collect();
+    let lower: Vec<char> = text.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+
+    let mut total = FuzzyMatch { score: 0, positions: Vec::new() };
+    for term in terms {
+        let case_sensitive = term.chars().any(char::is_uppercase);
+        let term: Vec<char> = if case_sensitive {
+            term.chars().collect()
+        } else {
+            term.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect()
+        };
+        let hay = if case_sensitive { &text } else { &lower };
+        let found = subsequence_match(&text, hay, &term).or_else(|| typo_match(hay, &term))?;
+        total.score += found.score;
+        total.positions.extend(found.positions);
+    }
+    total.positions.sort_unstable();
+    total.positions.dedup();
+    Some(total)
+}
+
+/// Snippet around the first matched character, matched runs highlighted
+fn snippet(text: &str, positions: &[usize]) -> Vec<SnippetPart> {
+    let chars: Vec<char> = text.chars().collect();
+    let start = positions.first().map_or(0, |first| first.saturating_sub(SNIPPET_LEAD));
+    let end = (start + SNIPPET_CHARS).min(chars.len());
+    let matched: HashSet<usize> = positions.iter().copied().collect();
+
+    let mut parts: Vec<SnippetPart> = Vec::new();
+    for (i, &c) in chars.iter().enumerate().take(end).skip(start) {
+        let is_match = matched.contains(&i);
+        match parts.last_mut() {
+            Some(part) if part.matched == is_match => part.text.push(c),
+            _ => parts.push(SnippetPart { text: c.to_string(), matched: is_match }),
+        }
+    }
+    if start > 0 {
+        parts.insert(0, SnippetPart { text: "…".to_string(), matched: false });
+    }
+    if end < chars.len() {
+        parts.push(SnippetPart { text: "…".to_string(), matched: false });
+    }
+    parts
+}
+
+// ============================================
+// SEARCH
+// ============================================
+
+/// fts5 expression matching any trigram of the terms
+fn trigram_expression(terms: &[String]) -> Option<String> {
+    let mut trigrams = Vec::new();
+    for term in terms {
+        let chars: Vec<char> = term.to_lowercase().chars().collect();
+        for window in chars.windows(3) {
+            let trigram: String = window.iter().collect();
+            if !trigrams.contains(&trigram) {
+                trigrams.push(trigram);
+            }
+        }
+    }
+    if trigrams.is_empty() {
+        return None;
+    }
+    Some(
+        trigrams
+            .iter()
+            .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" OR "),
+    )
+}
+
+fn query_candidates(conn: &Connection, sql: &str, values: Vec<Value>) -> Result<Vec<ClipboardItem>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to search items: {}", e))?;
+    let rows = stmt
+        .query_map(params_from_iter(values), items::item_from_row)
+        .map_err(|e| format!("Failed to search items: {}", e))?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read search results: {}", e))
+}
+
+/// Fuzzy matches for the query's text, best first
+/// Items matching the exact fts5 expression `exclude_exact` are left out so
+/// they aren't listed twice when both tiers are shown together.
+pub fn fuzzy_search(
+    conn: &Connection,
+    query: &SearchQuery,
+    exclude_exact: Option<&str>,
+) -> Result<Vec<SearchHit>, String> {
+    let terms = query::parse(&query.query).text_terms();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (mut where_sql, mut values) = query.where_clause();
+    // Like the fts5 index, never match against secrets
+    where_sql.push_str(&format!(
+        " AND v.category NOT IN ({})",
+        vec!["?"; items::SENSITIVE_CATEGORIES.len()].join(", ")
+    ));
+    values.extend(items::SENSITIVE_CATEGORIES.iter().map(|c| Value::Text(c.to_string())));
+    if let Some(expression) = exclude_exact {
+        where_sql.push_str(" AND v.id NOT IN (SELECT rowid FROM clipboard_items_fts WHERE clipboard_items_fts MATCH ?)");
+        values.push(Value::Text(expression.to_string()));
+    }
+    let columns = items::qualified_columns("v");
+
+    let mut candidates = Vec::new();
+    if let Some(expression) = trigram_expression(&terms) {
+        let sql = format!(
+            "SELECT {} FROM clipboard_items_trigram
+             JOIN clipboard_items_with_tags v ON v.id = clipboard_items_trigram.rowid
+             WHERE clipboard_items_trigram MATCH ? AND {}
+             ORDER BY clipboard_items_trigram.rank LIMIT {}",
+            columns, where_sql, TRIGRAM_CANDIDATES
+        );
+        let mut trigram_values = vec![Value::Text(expression)];
+        trigram_values.extend(values.iter().cloned());
+        candidates = query_candidates(conn, &sql, trigram_values)?;
+    }
+    let sql = format!(
+        "SELECT {} FROM clipboard_items_with_tags v WHERE {} ORDER BY v.timestamp DESC LIMIT {}",
+        columns, where_sql, RECENT_CANDIDATES
+    );
+    candidates.extend(query_candidates(conn, &sql, values)?);
+
+    let mut seen = HashSet::new();
+    let mut scored: Vec<(FuzzyMatch, ClipboardItem)> = candidates
+        .into_iter()
+        .filter(|item| seen.insert(item.id))
+        .filter_map(|item| fuzzy_match(&item.content, &terms).map(|found| (found, item)))
+        .collect();
+    // Best score, then shorter text, then newer (as fzf breaks ties)
+    scored.sort_by(|(a, x), (b, y)| {
+        b.score
+            .cmp(&a.score)
+            .then(x.content.len().cmp(&y.content.len()))
+            .then(y.timestamp.cmp(&x.timestamp))
+    });
+
+    Ok(scored
+        .into_iter()
+        .map(|(found, item)| SearchHit {
+            snippet: snippet(&item.content, &found.positions),
+            // Lower is better, like the bm25 scores of exact hits
+            score: -(found.score as f64),
+            fuzzy: true,
+            item,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(text: &str) -> Vec<String> {
+        query::parse(text).text_terms()
+    }
+
+    fn score(text: &str, query: &str) -> Option<i64> {
+        fuzzy_match(text, &terms(query)).map(|found| found.score)
+    }
+
+    #[test]
+    fn test_subsequence_scoring_prefers_word_starts_and_runs() {
+        assert!(score("kubectl get pods", "kubctl").is_some());
+        assert!(score("kubectl get pods", "kgp").is_some());
+        assert!(score("kubectl", "xyz").is_none());
+
+        // Matching at word starts beats matching mid-word
+        assert!(score("git push", "gp").unwrap() > score("laptop grip", "gp").unwrap());
+        // A consecutive run beats scattered characters
+        assert!(score("deploy.sh", "deploy").unwrap() > score("d-e-p-l-o-y", "deploy").unwrap());
+        // Smart case: an uppercase query only matches uppercase
+        assert!(score("readme.md", "README").is_none());
+        assert!(score("README.md", "README").is_some());
+    }
+
+    #[test]
+    fn test_typos_within_a_word() {
+        assert_eq!(edit_distance(&['a', 'b'], &['b', 'a']), 1);
+        let found = fuzzy_match("sudo systemctl restart docker", &terms("dokcer")).unwrap();
+        let matched: String = found.positions.iter().map(|&i| "sudo systemctl restart docker".chars().nth(i).unwrap()).collect();
+        assert_eq!(matched, "docker");
+        // Short terms and two typos in a short word are too loose
+        assert!(score("docker", "dkr1").is_none());
+        assert!(score("docker", "dcokre").is_none());
+    }
+
+    #[test]
+    fn test_snippet_highlights_matched_runs() {
+        let found = fuzzy_match("kubectl get pods", &terms("kubctl")).unwrap();
+        let parts = snippet("kubectl get pods", &found.positions);
+        let text: String = parts.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(text, "kubectl get pods");
+        let highlighted: Vec<_> = parts.iter().filter(|p| p.matched).map(|p| p.text.as_str()).collect();
+        assert_eq!(highlighted, vec!["kub", "ctl"]);
+    }
+}
@@ -0,0 +1,619 @@
+// Window Geometry Module
+// Platform-independent placement math for the overlay window.
+// Platform adapters in window_manager.rs collect monitor, work area and
+// dock/taskbar data and feed it here; nothing in this module touches the OS.
+
+/// Axis-aligned rectangle in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Build a rectangle from its edges, collapsing inverted edges to zero size
+    pub fn from_edges(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        Self::new(left, top, (right - left).max(0) as u32, (bottom - top).max(0) as u32)
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x as f64 && x < self.right() as f64 && y >= self.y as f64 && y < self.bottom() as f64
+    }
+
+    /// Overlapping part of two rectangles (empty if they don't overlap)
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        Rect::from_edges(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.right().min(other.right()),
+            self.bottom().min(other.bottom()),
+        )
+    }
+
+    /// Squared distance from a point to the closest point of the rectangle
+    fn distance_sq(&self, x: f64, y: f64) -> f64 {
+        let dx = (self.x as f64 - x).max(0.0).max(x - self.right() as f64);
+        let dy = (self.y as f64 - y).max(0.0).max(y - self.bottom() as f64);
+        dx * dx + dy * dy
+    }
+}
+
+/// Screen edge a window, dock or panel is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Bottom,
+    Top,
+    Left,
+    Right,
+}
+
+impl Edge {
+    /// Parse the `overlay_edge` setting, falling back to the bottom edge
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "top" => Edge::Top,
+            "left" => Edge::Left,
+            "right" => Edge::Right,
+            _ => Edge::Bottom,
+        }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Edge::Bottom | Edge::Top)
+    }
+}
+
+/// Dock or taskbar attached to one edge of a monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DockInfo {
+    pub edge: Edge,
+    /// Space the dock reserves along its edge, in physical pixels
+    pub thickness: u32,
+    /// Auto-hiding docks only appear on hover, so the overlay may cover them
+    pub auto_hide: bool,
+}
+
+/// Everything the placement math needs to know about one monitor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayInfo {
+    /// Full monitor bounds
+    pub bounds: Rect,
+    /// Part of the monitor not reserved by menu bars, panels, docks or taskbars
+    pub work_area: Rect,
+    /// Physical pixels per logical pixel
+    pub scale_factor: f64,
+    pub dock: Option<DockInfo>,
+}
+
+/// User preferences for the overlay size and position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlacementPrefs {
+    pub edge: Edge,
+    /// Share of the usable area the overlay covers, measured across the docked edge
+    pub height_percent: u32,
+    /// Logical pixels, scaled by the monitor's scale factor
+    pub min_height: u32,
+    /// Logical pixels, scaled by the monitor's scale factor
+    pub max_height: u32,
+}
+
+impl Default for PlacementPrefs {
+    fn default() -> Self {
+        Self {
+            edge: Edge::Bottom,
+            height_percent: 35,
+            min_height: 350,
+            max_height: 450,
+        }
+    }
+}
+
+/// Area of the monitor the overlay is allowed to cover
+///
+/// Starts from the work area (falling back to the full bounds if the work
+/// area is bogus). An auto-hiding dock gives its strip back; a visible dock
+/// is kept clear even if the work area doesn't account for it.
+pub fn usable_area(display: &DisplayInfo) -> Rect {
+    let mut area = display.work_area.intersect(&display.bounds);
+    if area.is_empty() {
+        area = display.bounds;
+    }
+
+    let Some(dock) = display.dock else {
+        return area;
+    };
+
+    let bounds = display.bounds;
+    let (left, top, right, bottom) = if dock.auto_hide {
+        match dock.edge {
+            Edge::Bottom => (area.x, area.y, area.right(), bounds.bottom()),
+            Edge::Top => (area.x, bounds.y, area.right(), area.bottom()),
+            Edge::Left => (bounds.x, area.y, area.right(), area.bottom()),
+            Edge::Right => (area.x, area.y, bounds.right(), area.bottom()),
+        }
+    } else {
+        let inset = dock.thickness as i32;
+        match dock.edge {
+            Edge::Bottom => (area.x, area.y, area.right(), area.bottom().min(bounds.bottom() - inset)),
+            Edge::Top => (area.x, area.y.max(bounds.y + inset), area.right(), area.bottom()),
+            Edge::Left => (area.x.max(bounds.x + inset), area.y, area.right(), area.bottom()),
+            Edge::Right => (area.x, area.y, area.right().min(bounds.right() - inset), area.bottom()),
+        }
+    };
+
+    Rect::from_edges(left, top, right, bottom)
+}
+
+/// Compute the overlay window rectangle for a monitor
+///
+/// The overlay spans the full length of the chosen edge of the usable area.
+/// Its thickness is `height_percent` of that area, clamped to the min/max
+/// preferences (scaled to physical pixels) and never larger than the area.
+pub fn compute_window_rect(display: &DisplayInfo, prefs: &PlacementPrefs) -> Rect {
+    let area = usable_area(display);
+    let extent = if prefs.edge.is_horizontal() { area.height } else { area.width };
+
+    let scale = if display.scale_factor.is_finite() && display.scale_factor > 0.0 {
+        display.scale_factor
+    } else {
+        1.0
+    };
+    let min = (prefs.min_height.min(prefs.max_height) as f64 * scale).round() as u32;
+    let max = (prefs.max_height.max(prefs.min_height) as f64 * scale).round() as u32;
+
+    let percent = prefs.height_percent.clamp(1, 100) as u64;
+    let base = (extent as u64 * percent / 100) as u32;
+    let thickness = base.clamp(min, max).min(extent);
+
+    match prefs.edge {
+        Edge::Bottom => Rect::new(area.x, area.bottom() - thickness as i32, area.width, thickness),
+        Edge::Top => Rect::new(area.x, area.y, area.width, thickness),
+        Edge::Left => Rect::new(area.x, area.y, thickness, area.height),
+        Edge::Right => Rect::new(area.right() - thickness as i32, area.y, thickness, area.height),
+    }
+}
+
+/// Find the monitor containing a point, or the nearest one if the point
+/// falls into a gap between monitors
+pub fn monitor_at_point(monitors: &[Rect], x: f64, y: f64) -> Option<usize> {
+    if let Some(index) = monitors.iter().position(|m| m.contains(x, y)) {
+        return Some(index);
+    }
+
+    monitors
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.distance_sq(x, y).total_cmp(&b.distance_sq(x, y)))
+        .map(|(index, _)| index)
+}
+
+/// Infer which side a dock sits on from the space it reserves
+///
+/// Only the bottom, left and right edges are considered because the top of
+/// the screen is reserved by the menu bar on macOS. Returns `None` when
+/// nothing is reserved on those edges.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn dock_from_insets(bounds: &Rect, work_area: &Rect, auto_hide: bool) -> Option<DockInfo> {
+    let insets = [
+        (Edge::Bottom, bounds.bottom() - work_area.bottom()),
+        (Edge::Left, work_area.x - bounds.x),
+        (Edge::Right, bounds.right() - work_area.right()),
+    ];
+
+    insets
+        .into_iter()
+        .filter(|(_, inset)| *inset > 0)
+        .max_by_key(|(_, inset)| *inset)
+        .map(|(edge, inset)| DockInfo { edge, thickness: inset as u32, auto_hide })
+}
+
+/// Space reserved by an X11 panel through `_NET_WM_STRUT_PARTIAL`
+///
+/// Widths are measured from the edges of the whole X screen (all monitors
+/// combined); the start/end pairs limit which part of that edge is reserved.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Strut {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+    pub left_start_y: i32,
+    pub left_end_y: i32,
+    pub right_start_y: i32,
+    pub right_end_y: i32,
+    pub top_start_x: i32,
+    pub top_end_x: i32,
+    pub bottom_start_x: i32,
+    pub bottom_end_x: i32,
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl Strut {
+    /// Parse the property value: 12 cardinals for `_NET_WM_STRUT_PARTIAL`,
+    /// or 4 for the legacy `_NET_WM_STRUT` which covers whole edges
+    pub fn from_cardinals(values: &[u32], screen: &Rect) -> Option<Strut> {
+        match values.len() {
+            12 => Some(Strut {
+                left: values[0],
+                right: values[1],
+                top: values[2],
+                bottom: values[3],
+                left_start_y: values[4] as i32,
+                left_end_y: values[5] as i32,
+                right_start_y: values[6] as i32,
+                right_end_y: values[7] as i32,
+                top_start_x: values[8] as i32,
+                top_end_x: values[9] as i32,
+                bottom_start_x: values[10] as i32,
+                bottom_end_x: values[11] as i32,
+            }),
+            4 => Some(Strut {
+                left: values[0],
+                right: values[1],
+                top: values[2],
+                bottom: values[3],
+                left_start_y: screen.y,
+                left_end_y: screen.bottom() - 1,
+                right_start_y: screen.y,
+                right_end_y: screen.bottom() - 1,
+                top_start_x: screen.x,
+                top_end_x: screen.right() - 1,
+                bottom_start_x: screen.x,
+                bottom_end_x: screen.right() - 1,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Work area of one monitor after removing the panels that overlap it
+///
+/// `_NET_WORKAREA` only describes the whole X screen, which is wrong as soon
+/// as a panel sits on one monitor of a multi-monitor setup. Struts tell us
+/// exactly which strip each panel reserves, so we clip monitor by monitor.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn work_area_from_struts(monitor: &Rect, screen: &Rect, struts: &[Strut]) -> Rect {
+    let (mut left, mut top) = (monitor.x, monitor.y);
+    let (mut right, mut bottom) = (monitor.right(), monitor.bottom());
+
+    for strut in struts {
+        if strut.left > 0 {
+            let reserved = Rect::from_edges(screen.x, strut.left_start_y, screen.x + strut.left as i32, strut.left_end_y + 1);
+            if !reserved.intersect(monitor).is_empty() {
+                left = left.max(reserved.right());
+            }
+        }
+        if strut.right > 0 {
+            let reserved = Rect::from_edges(screen.right() - strut.right as i32, strut.right_start_y, screen.right(), strut.right_end_y + 1);
+            if !reserved.intersect(monitor).is_empty() {
+                right = right.min(reserved.x);
+            }
+        }
+        if strut.top > 0 {
+            let reserved = Rect::from_edges(strut.top_start_x, screen.y, strut.top_end_x + 1, screen.y + strut.top as i32);
+            if !reserved.intersect(monitor).is_empty() {
+                top = top.max(reserved.bottom());
+            }
+        }
+        if strut.bottom > 0 {
+            let reserved = Rect::from_edges(strut.bottom_start_x, screen.bottom() - strut.bottom as i32, strut.bottom_end_x + 1, screen.bottom());
+            if !reserved.intersect(monitor).is_empty() {
+                bottom = bottom.min(reserved.y);
+            }
+        }
+    }
+
+    let area = Rect::from_edges(left, top, right, bottom);
+    if area.is_empty() {
+        *monitor
+    } else {
+        area
+    }
+}
+
+/// Invisible border GTK draws around client-side decorated windows
+/// (`_GTK_FRAME_EXTENTS`), in physical pixels
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameExtents {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+/// Grow a window rectangle so its visible part, not its shadow, lands on `rect`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn expand_by_frame_extents(rect: Rect, extents: FrameExtents) -> Rect {
+    Rect::new(
+        rect.x - extents.left as i32,
+        rect.y - extents.top as i32,
+        rect.width + extents.left + extents.right,
+        rect.height + extents.top + extents.bottom,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(bounds: Rect, scale_factor: f64) -> DisplayInfo {
+        DisplayInfo { bounds, work_area: bounds, scale_factor, dock: None }
+    }
+
+    fn prefs(edge: Edge) -> PlacementPrefs {
+        PlacementPrefs { edge, ..PlacementPrefs::default() }
+    }
+
+    #[test]
+    fn test_edge_from_setting() {
+        assert_eq!(Edge::from_setting("top"), Edge::Top);
+        assert_eq!(Edge::from_setting("left"), Edge::Left);
+        assert_eq!(Edge::from_setting("right"), Edge::Right);
+        assert_eq!(Edge::from_setting("bottom"), Edge::Bottom);
+        assert_eq!(Edge::from_setting("diagonal"), Edge::Bottom);
+    }
+
+    #[test]
+    fn test_default_placement() {
+        // 35% of 1080 = 378, within 350..=450
+        let display = plain(Rect::new(0, 0, 1920, 1080), 1.0);
+        let rect = compute_window_rect(&display, &PlacementPrefs::default());
+        assert_eq!(rect, Rect::new(0, 702, 1920, 378));
+    }
+
+    #[test]
+    fn test_height_is_clamped() {
+        // 35% of 2160 = 756, clamped to 450
+        let tall = plain(Rect::new(0, 0, 3840, 2160), 1.0);
+        assert_eq!(compute_window_rect(&tall, &PlacementPrefs::default()).height, 450);
+
+        // 35% of 768 = 268, raised to 350
+        let short = plain(Rect::new(0, 0, 1366, 768), 1.0);
+        assert_eq!(compute_window_rect(&short, &PlacementPrefs::default()).height, 350);
+
+        // Never taller than the screen
+        let tiny = plain(Rect::new(0, 0, 800, 300), 1.0);
+        assert_eq!(compute_window_rect(&tiny, &PlacementPrefs::default()), Rect::new(0, 0, 800, 300));
+    }
+
+    #[test]
+    fn test_hidpi_scales_clamps() {
+        // Retina 2560x1600 logical = 5120x3200 physical at 2x
+        // 35% of 3200 = 1120, max 450 logical = 900 physical
+        let display = plain(Rect::new(0, 0, 5120, 3200), 2.0);
+        let rect = compute_window_rect(&display, &PlacementPrefs::default());
+        assert_eq!(rect, Rect::new(0, 2300, 5120, 900));
+
+        // 1.5x laptop: 35% of 1620 = 567, within 525..=675
+        let display = plain(Rect::new(0, 0, 2880, 1620), 1.5);
+        assert_eq!(compute_window_rect(&display, &PlacementPrefs::default()).height, 567);
+    }
+
+    #[test]
+    fn test_invalid_scale_factor_falls_back_to_one() {
+        let display = plain(Rect::new(0, 0, 3840, 2160), f64::NAN);
+        assert_eq!(compute_window_rect(&display, &PlacementPrefs::default()).height, 450);
+    }
+
+    #[test]
+    fn test_negative_monitor_origin() {
+        // Secondary monitor left of and above the primary one
+        let display = plain(Rect::new(-2560, -400, 2560, 1440), 1.0);
+        let rect = compute_window_rect(&display, &PlacementPrefs::default());
+        // 35% of 1440 = 504, clamped to 450; bottom edge at -400 + 1440 = 1040
+        assert_eq!(rect, Rect::new(-2560, 590, 2560, 450));
+
+        let rect = compute_window_rect(&display, &prefs(Edge::Right));
+        assert_eq!(rect, Rect::new(-450, -400, 450, 1440));
+    }
+
+    #[test]
+    fn test_each_edge() {
+        let display = plain(Rect::new(100, 50, 1920, 1080), 1.0);
+
+        let top = compute_window_rect(&display, &prefs(Edge::Top));
+        assert_eq!(top, Rect::new(100, 50, 1920, 378));
+
+        let bottom = compute_window_rect(&display, &prefs(Edge::Bottom));
+        assert_eq!(bottom, Rect::new(100, 752, 1920, 378));
+
+        // Left/right measure the area width: 35% of 1920 = 672, clamped to 450
+        let left = compute_window_rect(&display, &prefs(Edge::Left));
+        assert_eq!(left, Rect::new(100, 50, 450, 1080));
+
+        let right = compute_window_rect(&display, &prefs(Edge::Right));
+        assert_eq!(right, Rect::new(1570, 50, 450, 1080));
+    }
+
+    #[test]
+    fn test_taskbar_at_bottom() {
+        let display = DisplayInfo {
+            bounds: Rect::new(0, 0, 1920, 1080),
+            work_area: Rect::new(0, 0, 1920, 1040),
+            scale_factor: 1.0,
+            dock: None,
+        };
+        // 35% of 1040 = 364, sits right above the taskbar
+        let rect = compute_window_rect(&display, &PlacementPrefs::default());
+        assert_eq!(rect, Rect::new(0, 676, 1920, 364));
+    }
+
+    #[test]
+    fn test_panel_at_left() {
+        // 64px panel on the left (GNOME/Ubuntu dock, Windows taskbar on the left)
+        let display = DisplayInfo {
+            bounds: Rect::new(0, 0, 1920, 1080),
+            work_area: Rect::new(64, 0, 1856, 1080),
+            scale_factor: 1.0,
+            dock: None,
+        };
+
+        let bottom = compute_window_rect(&display, &PlacementPrefs::default());
+        assert_eq!(bottom, Rect::new(64, 702, 1856, 378));
+
+        let left = compute_window_rect(&display, &prefs(Edge::Left));
+        assert_eq!(left, Rect::new(64, 0, 450, 1080));
+    }
+
+    #[test]
+    fn test_auto_hide_dock_is_covered() {
+        // macOS: 25pt menu bar, auto-hidden Dock reserving a 4px strip at the bottom
+        let bounds = Rect::new(0, 0, 1440, 900);
+        let work_area = Rect::new(0, 25, 1440, 871);
+        let visible_dock = DisplayInfo {
+            bounds,
+            work_area,
+            scale_factor: 1.0,
+            dock: dock_from_insets(&bounds, &work_area, false),
+        };
+        let hidden_dock = DisplayInfo {
+            dock: dock_from_insets(&bounds, &work_area, true),
+            ..visible_dock
+        };
+
+        // Visible dock: stay above it
+        assert_eq!(compute_window_rect(&visible_dock, &PlacementPrefs::default()).bottom(), 896);
+        // Auto-hidden dock: float over it at the absolute bottom, keep the menu bar
+        assert_eq!(usable_area(&hidden_dock), Rect::new(0, 25, 1440, 875));
+        assert_eq!(compute_window_rect(&hidden_dock, &PlacementPrefs::default()).bottom(), 900);
+    }
+
+    #[test]
+    fn test_visible_dock_missing_from_work_area() {
+        // Work area not reported, dock known from elsewhere
+        let bounds = Rect::new(0, 0, 1920, 1080);
+        let display = DisplayInfo {
+            dock: Some(DockInfo { edge: Edge::Bottom, thickness: 48, auto_hide: false }),
+            ..plain(bounds, 1.0)
+        };
+        assert_eq!(usable_area(&display), Rect::new(0, 0, 1920, 1032));
+        assert_eq!(compute_window_rect(&display, &PlacementPrefs::default()).bottom(), 1032);
+    }
+
+    #[test]
+    fn test_work_area_outside_bounds_is_ignored() {
+        let display = DisplayInfo {
+            bounds: Rect::new(0, 0, 1920, 1080),
+            work_area: Rect::new(5000, 5000, 100, 100),
+            scale_factor: 1.0,
+            dock: None,
+        };
+        assert_eq!(usable_area(&display), display.bounds);
+    }
+
+    #[test]
+    fn test_inverted_clamps_are_tolerated() {
+        let p = PlacementPrefs { min_height: 500, max_height: 300, ..PlacementPrefs::default() };
+        let display = plain(Rect::new(0, 0, 1920, 1080), 1.0);
+        assert_eq!(compute_window_rect(&display, &p).height, 378);
+    }
+
+    #[test]
+    fn test_dock_from_insets() {
+        let bounds = Rect::new(0, 0, 1440, 900);
+
+        let left = dock_from_insets(&bounds, &Rect::new(80, 25, 1360, 875), false).unwrap();
+        assert_eq!(left, DockInfo { edge: Edge::Left, thickness: 80, auto_hide: false });
+
+        let right = dock_from_insets(&bounds, &Rect::new(0, 25, 1370, 875), true).unwrap();
+        assert_eq!(right, DockInfo { edge: Edge::Right, thickness: 70, auto_hide: true });
+
+        // Only the menu bar is reserved
+        assert!(dock_from_insets(&bounds, &Rect::new(0, 25, 1440, 875), false).is_none());
+    }
+
+    #[test]
+    fn test_monitor_at_point() {
+        let monitors = [
+            Rect::new(0, 0, 1920, 1080),
+            Rect::new(1920, 0, 2560, 1440),
+            Rect::new(-1280, -200, 1280, 1024),
+        ];
+
+        assert_eq!(monitor_at_point(&monitors, 10.0, 10.0), Some(0));
+        assert_eq!(monitor_at_point(&monitors, 1920.0, 500.0), Some(1));
+        assert_eq!(monitor_at_point(&monitors, -5.0, -100.0), Some(2));
+        // Below the primary monitor, in the gap next to the taller one
+        assert_eq!(monitor_at_point(&monitors, 100.0, 1200.0), Some(0));
+        assert_eq!(monitor_at_point(&[], 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_strut_from_cardinals() {
+        let screen = Rect::new(0, 0, 3840, 1080);
+
+        let partial = Strut::from_cardinals(&[0, 0, 0, 48, 0, 0, 0, 0, 0, 0, 0, 1919], &screen).unwrap();
+        assert_eq!(partial.bottom, 48);
+        assert_eq!(partial.bottom_end_x, 1919);
+
+        let full = Strut::from_cardinals(&[0, 0, 32, 0], &screen).unwrap();
+        assert_eq!(full.top, 32);
+        assert_eq!((full.top_start_x, full.top_end_x), (0, 3839));
+
+        assert!(Strut::from_cardinals(&[1, 2, 3], &screen).is_none());
+    }
+
+    #[test]
+    fn test_work_area_from_struts_single_monitor() {
+        // GNOME: 32px top bar, Ubuntu dock 64px on the left
+        let screen = Rect::new(0, 0, 1920, 1080);
+        let struts = [
+            Strut::from_cardinals(&[0, 0, 32, 0, 0, 0, 0, 0, 0, 1919, 0, 0], &screen).unwrap(),
+            Strut::from_cardinals(&[64, 0, 0, 0, 32, 1079, 0, 0, 0, 0, 0, 0], &screen).unwrap(),
+        ];
+
+        let area = work_area_from_struts(&screen, &screen, &struts);
+        assert_eq!(area, Rect::new(64, 32, 1856, 1048));
+    }
+
+    #[test]
+    fn test_work_area_from_struts_only_clips_overlapping_monitor() {
+        // KDE panel at the bottom of the left monitor only
+        let screen = Rect::new(0, 0, 3840, 1080);
+        let left_monitor = Rect::new(0, 0, 1920, 1080);
+        let right_monitor = Rect::new(1920, 0, 1920, 1080);
+        let struts = [Strut::from_cardinals(&[0, 0, 0, 44, 0, 0, 0, 0, 0, 0, 0, 1919], &screen).unwrap()];
+
+        assert_eq!(work_area_from_struts(&left_monitor, &screen, &struts), Rect::new(0, 0, 1920, 1036));
+        assert_eq!(work_area_from_struts(&right_monitor, &screen, &struts), right_monitor);
+    }
+
+    #[test]
+    fn test_work_area_from_struts_shorter_monitor() {
+        // Bottom struts are measured from the bottom of the whole X screen:
+        // a 1080p monitor next to a 1440p one needs a 404px strut for a 44px panel
+        let screen = Rect::new(0, 0, 4480, 1440);
+        let short_monitor = Rect::new(2560, 0, 1920, 1080);
+        let struts = [Strut::from_cardinals(&[0, 0, 0, 404, 0, 0, 0, 0, 0, 0, 2560, 4479], &screen).unwrap()];
+
+        assert_eq!(work_area_from_struts(&short_monitor, &screen, &struts), Rect::new(2560, 0, 1920, 1036));
+    }
+
+    #[test]
+    fn test_expand_by_frame_extents() {
+        let rect = Rect::new(0, 702, 1920, 378);
+        let extents = FrameExtents { left: 10, right: 10, top: 8, bottom: 12 };
+        assert_eq!(expand_by_frame_extents(rect, extents), Rect::new(-10, 694, 1940, 398));
+        assert_eq!(expand_by_frame_extents(rect, FrameExtents::default()), rect);
+    }
+}
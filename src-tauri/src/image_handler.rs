@@ -1,11 +1,15 @@
 // Image Handler Module
 // Handles image clipboard capture, storage, and thumbnail generation
 
-use image::{DynamicImage, ImageFormat, imageops::FilterType, GenericImageView};
-use std::path::Path;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, imageops::FilterType, GenericImageView};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::image_metadata::{self, ImageDetails};
+use crate::palette::{extract_palette, ColorPalette};
+
 /// Image metadata extracted from captured clipboard image
 #[derive(Debug, Clone)]
 pub struct ImageMetadata {
@@ -14,7 +18,17 @@ pub struct ImageMetadata {
     pub width: u32,
     pub height: u32,
     pub file_size: u64,
+    /// Format, color space, DPI and transparency (see image_metadata.rs)
+    pub details: ImageDetails,
     pub dominant_color: Option<String>,
+    /// Main colors and their proportions (see palette.rs)
+    pub palette: Option<ColorPalette>,
+    /// Text read from QR codes and barcodes in the image (see barcode.rs)
+    pub decoded_text: Option<String>,
+    /// Content type of decoded_text ("links", "apikey", ...)
+    pub decoded_type: Option<String>,
+    /// Perceptual hash, for spotting near-duplicates (see similar_images.rs)
+    pub perceptual_hash: u64,
 }
 
 /// Save image from clipboard to filesystem
@@ -22,17 +36,41 @@ pub struct ImageMetadata {
 /// Steps:
 /// 1. Create image storage directory if it doesn't exist
 /// 2. Generate unique filename based on timestamp
-/// 3. Save original image
-/// 4. Generate and save thumbnail
-/// 5. Extract metadata (dimensions, file size)
-/// 6. Return ImageMetadata
+/// 3. Save original image (JPEG, PNG, WebP and GIF files as they are, without
+///    location and camera metadata if `strip_metadata`; others as PNG)
+/// 4. Generate and save thumbnail (a still of the first frame for animations)
+/// 5. Extract metadata (dimensions, file size, format, color palette)
+/// 6. Decode QR codes and barcodes
+/// 7. Compute perceptual hash
+/// 8. Return ImageMetadata
 pub async fn save_clipboard_image(
     app_data_dir: &Path,
     image_data: &[u8],
+    strip_metadata: bool,
 ) -> Result<ImageMetadata, String> {
-    // Load image from bytes
-    let img = image::load_from_memory(image_data)
-        .map_err(|e| format!("Failed to load image from clipboard: {}", e))?;
+    // Load image from bytes, turned upright
+    let img = decode_image(image_data)?;
+    let details = image_metadata::inspect(image_data, &img);
+
+    // Files that can be kept as they are, and their extension
+    let original = match image::guess_format(image_data) {
+        Ok(ImageFormat::Png) => Some(("png", image_data.to_vec())),
+        Ok(ImageFormat::Jpeg) => Some(("jpg", image_data.to_vec())),
+        Ok(ImageFormat::WebP) => Some(("webp", image_data.to_vec())),
+        Ok(ImageFormat::Gif) => Some(("gif", image_data.to_vec())),
+        _ => None,
+    };
+    // A file whose metadata can't be removed is re-encoded instead
+    let original = match original {
+        Some((extension, bytes)) if strip_metadata => match image_metadata::strip_metadata(&bytes) {
+            Ok(stripped) => Some((extension, stripped)),
+            Err(e) => {
+                println!("[CopyGum] {}; saving image as PNG", e);
+                None
+            }
+        },
+        original => original,
+    };
 
     // Create images directory
     let images_dir = app_data_dir.join("CopyGum").join("images");
@@ -45,16 +83,20 @@ pub async fn save_clipboard_image(
         .unwrap()
         .as_millis();
     let random_suffix = fastrand::u32(1000..9999);
-    let filename = format!("{}_{}.png", timestamp, random_suffix);
+    let extension = original.as_ref().map_or("png", |(extension, _)| *extension);
+    let filename = format!("{}_{}.{}", timestamp, random_suffix, extension);
 
     // Save original image
     let image_path = images_dir.join(&filename);
-    img.save_with_format(&image_path, ImageFormat::Png)
-        .map_err(|e| format!("Failed to save image: {}", e))?;
+    match &original {
+        Some((_, bytes)) => fs::write(&image_path, bytes)
+            .map_err(|e| format!("Failed to save image: {}", e))?,
+        None => img.save_with_format(&image_path, ImageFormat::Png)
+            .map_err(|e| format!("Failed to save image: {}", e))?,
+    }
 
     // Generate thumbnail
-    let thumbnail_filename = format!("{}_{}_thumb.png", timestamp, random_suffix);
-    let thumbnail_path = images_dir.join(&thumbnail_filename);
+    let thumbnail_path = thumbnail_path(&image_path);
     let thumbnail = generate_thumbnail(&img);
     thumbnail.save_with_format(&thumbnail_path, ImageFormat::Png)
         .map_err(|e| format!("Failed to save thumbnail: {}", e))?;
@@ -67,8 +109,18 @@ pub async fn save_clipboard_image(
     // Extract dimensions
     let (width, height) = img.dimensions();
 
-    // Extract dominant color (optional - basic implementation)
-    let dominant_color = extract_dominant_color(&img);
+    // Extract color palette; its most common color is the dominant one
+    let palette = extract_palette(&img);
+    let dominant_color = palette.as_ref().map(|palette| palette.dominant().to_string());
+
+    // Decode QR codes and barcodes into a text flavor
+    let codes = crate::barcode::decode_codes(&img);
+    let (decoded_text, decoded_type) = match crate::barcode::text_flavor(&codes) {
+        Some((text, content_type)) => (Some(text), Some(content_type.to_string())),
+        None => (None, None),
+    };
+
+    let perceptual_hash = perceptual_hash(&img);
 
     Ok(ImageMetadata {
         image_path: image_path.to_string_lossy().to_string(),
@@ -76,10 +128,54 @@ pub async fn save_clipboard_image(
         width,
         height,
         file_size,
+        details,
         dominant_color,
+        palette,
+        decoded_text,
+        decoded_type,
+        perceptual_hash,
     })
 }
 
+/// Decode an encoded image, applying its EXIF orientation
+pub fn decode_image(bytes: &[u8]) -> Result<DynamicImage, String> {
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image: {}", e))?
+        .into_decoder()
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    let orientation = decoder.orientation()
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    let mut img = DynamicImage::from_decoder(decoder)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
+
+/// Bytes of a stored image to put back on the clipboard, without location
+/// and camera metadata if `strip_metadata`
+pub fn image_bytes_for_clipboard(image_path: &Path, strip_metadata: bool) -> Result<Vec<u8>, String> {
+    let bytes = fs::read(image_path)
+        .map_err(|e| format!("Failed to read image: {}", e))?;
+    if !strip_metadata {
+        return Ok(bytes);
+    }
+    image_metadata::strip_metadata(&bytes).or_else(|_| {
+        // Metadata that can't be read is dropped by re-encoding
+        let mut png = Vec::new();
+        decode_image(&bytes)?
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode image: {}", e))?;
+        Ok(png)
+    })
+}
+
+/// The thumbnail saved next to an image: `<name>.<ext>` has `<name>_thumb.png`
+pub fn thumbnail_path(image_path: &Path) -> PathBuf {
+    let stem = image_path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    image_path.with_file_name(format!("{}_thumb.png", stem))
+}
+
 /// Generate thumbnail for image
 ///
 /// Resizes image to fit within 400x400 while maintaining aspect ratio
@@ -105,48 +201,44 @@ fn generate_thumbnail(img: &DynamicImage) -> DynamicImage {
     img.resize(new_width, new_height, FilterType::Lanczos3)
 }
 
-/// Extract dominant color from image
+/// Perceptual hash (dHash) of an image
 ///
-/// Simple implementation: samples pixels and calculates average color
-fn extract_dominant_color(img: &DynamicImage) -> Option<String> {
-    let rgb_img = img.to_rgb8();
-    let (width, height) = rgb_img.dimensions();
-
-    // Sample every 10th pixel for performance
-    let sample_rate = 10;
-    let mut r_sum: u64 = 0;
-    let mut g_sum: u64 = 0;
-    let mut b_sum: u64 = 0;
-    let mut count: u64 = 0;
-
-    for y in (0..height).step_by(sample_rate) {
-        for x in (0..width).step_by(sample_rate) {
-            let pixel = rgb_img.get_pixel(x, y);
-            r_sum += pixel[0] as u64;
-            g_sum += pixel[1] as u64;
-            b_sum += pixel[2] as u64;
-            count += 1;
+/// Shrinks the image to 9x8 grayscale and sets one bit per pixel that is
+/// brighter than its right neighbour. Images that look alike (re-encoded,
+/// resized, shifted a few pixels) get hashes that differ in only a few bits.
+pub fn perceptual_hash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
         }
     }
+    hash
+}
 
-    if count == 0 {
-        return None;
-    }
-
-    let r_avg = (r_sum / count) as u8;
-    let g_avg = (g_sum / count) as u8;
-    let b_avg = (b_sum / count) as u8;
-
-    Some(format!("#{:02X}{:02X}{:02X}", r_avg, g_avg, b_avg))
+/// Number of differing bits between two perceptual hashes (0-64)
+pub fn hash_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
 }
 
 /// Read image file and return as base64 data URL
 /// This bypasses asset protocol issues on Windows
+/// With `thumbnail`, the still thumbnail is returned when there is one
+/// (cards show animated images that way)
 #[tauri::command]
-pub fn get_image_base64(image_path: String) -> Result<String, String> {
+pub fn get_image_base64(image_path: String, thumbnail: Option<bool>) -> Result<String, String> {
     use base64::{Engine as _, engine::general_purpose::STANDARD};
 
-    let path = Path::new(&image_path);
+    let thumbnail_path = thumbnail_path(Path::new(&image_path));
+    let path = if thumbnail.unwrap_or(false) && thumbnail_path.is_file() {
+        thumbnail_path.as_path()
+    } else {
+        Path::new(&image_path)
+    };
 
     if !path.exists() {
         return Err(format!("Image file not found: {}", image_path));
@@ -173,6 +265,11 @@ mod tests {
     use super::*;
     use image::{Rgb, RgbImage};
 
+    /// Dominant color as stored in ImageMetadata
+    fn extract_dominant_color(img: &DynamicImage) -> Option<String> {
+        extract_palette(img).map(|palette| palette.dominant().to_string())
+    }
+
     #[test]
     fn test_generate_thumbnail_large_image() {
         // Create a 1000x800 test image
@@ -231,11 +328,72 @@ mod tests {
         let img = DynamicImage::ImageRgb8(img_data);
 
         let color = extract_dominant_color(&img);
-        // Should be somewhere between red and blue (purple-ish)
+        // One of the colors in the image, not the purple in between
         assert!(color.is_some());
 
         let hex = color.unwrap();
         assert!(hex.starts_with("#"));
         assert_eq!(hex.len(), 7);
+        assert!(hex == "#FF0000" || hex == "#0000FF", "{}", hex);
+    }
+
+    #[test]
+    fn test_decode_image_applies_orientation() {
+        use exif::{experimental::Writer, Field, In, Tag, Value};
+        use img_parts::{jpeg::Jpeg, Bytes, ImageEXIF};
+
+        // A 64x48 photo taken with the phone turned (orientation 6: rotate 90° clockwise)
+        let mut jpeg_bytes = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(64, 48))
+            .write_to(&mut Cursor::new(&mut jpeg_bytes), ImageFormat::Jpeg)
+            .unwrap();
+        let orientation = Field { tag: Tag::Orientation, ifd_num: In::PRIMARY, value: Value::Short(vec![6]) };
+        let mut writer = Writer::new();
+        writer.push_field(&orientation);
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let mut jpeg = Jpeg::from_bytes(Bytes::from(jpeg_bytes)).unwrap();
+        jpeg.set_exif(Some(Bytes::from(tiff.into_inner())));
+        let bytes = jpeg.encoder().bytes();
+
+        assert_eq!(decode_image(&bytes).unwrap().dimensions(), (48, 64));
+        assert_eq!(decode_image(&image_metadata::strip_metadata(&bytes).unwrap()).unwrap().dimensions(), (48, 64));
+        assert!(decode_image(b"not an image").is_err());
+    }
+
+    /// A mock dialog: light window, dark title bar, a text block and a button
+    fn dialog(offset_x: u32, offset_y: u32) -> DynamicImage {
+        let mut img = RgbImage::from_pixel(400, 300, Rgb([236, 236, 236]));
+        for (x, y, pixel) in img.enumerate_pixels_mut() {
+            let (x, y) = (x.wrapping_sub(offset_x), y.wrapping_sub(offset_y));
+            if y < 40 {
+                *pixel = Rgb([40, 40, 48]);
+            } else if (30..260).contains(&x) && (80..160).contains(&y) && (y / 12).is_multiple_of(2) {
+                *pixel = Rgb([90, 90, 90]);
+            } else if (270..370).contains(&x) && (230..270).contains(&y) {
+                *pixel = Rgb([30, 110, 230]);
+            }
+        }
+        DynamicImage::ImageRgb8(img)
+    }
+
+    #[test]
+    fn test_perceptual_hash_near_duplicates() {
+        let original = perceptual_hash(&dialog(0, 0));
+
+        // Same dialog a few pixels over, and scaled down
+        let shifted = perceptual_hash(&dialog(3, 2));
+        let scaled = perceptual_hash(&dialog(0, 0).resize_exact(300, 225, FilterType::Triangle));
+        assert!(hash_distance(original, shifted) <= 6, "shifted: {}", hash_distance(original, shifted));
+        assert!(hash_distance(original, scaled) <= 6, "scaled: {}", hash_distance(original, scaled));
+
+        // A different picture
+        let mut other = RgbImage::new(400, 300);
+        for (x, y, pixel) in other.enumerate_pixels_mut() {
+            let v = ((x * 7 + y * 3) % 256) as u8;
+            *pixel = Rgb([v, 255 - v, (y % 256) as u8]);
+        }
+        let other = perceptual_hash(&DynamicImage::ImageRgb8(other));
+        assert!(hash_distance(original, other) > 12, "other: {}", hash_distance(original, other));
     }
 }
@@ -0,0 +1,535 @@
+// Image Metadata Module - Reads and strips metadata embedded in image files
+// Images copied from files (and some browsers) arrive as the original JPEG,
+// PNG or WebP bytes, metadata included. From those bytes:
+// - inspect reports the format, color space (from the ICC profile), DPI,
+//   whether any pixel is actually transparent and whether it is animated
+// - strip_metadata removes EXIF (GPS position, camera, capture time), XMP,
+//   IPTC, comments and PNG text chunks, so a photo pasted into a chat doesn't
+//   reveal where it was taken. Only the EXIF fields needed to display the
+//   image (orientation and resolution) are kept.
+// Stripping rewrites the container, never the pixels. It runs before images
+// are stored and before they are pasted back, when `strip_image_metadata` is on.
+
+use std::io::Cursor;
+
+use exif::experimental::Writer;
+use exif::{Field, In, Reader, Tag, Value};
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, GenericImageView, ImageFormat};
+use img_parts::jpeg::{markers, Jpeg, JpegSegment};
+use img_parts::png::{Png, PngChunk};
+use img_parts::riff::{RiffChunk, RiffContent};
+use img_parts::webp::WebP;
+use img_parts::{Bytes, ImageICC};
+
+/// EXIF fields kept when stripping: how to show the image, not where or how
+/// it was taken
+const KEPT_EXIF_TAGS: [Tag; 4] = [Tag::Orientation, Tag::XResolution, Tag::YResolution, Tag::ResolutionUnit];
+/// JPEG segments (APP1 holds EXIF and XMP, APP13 holds IPTC) and PNG chunks
+/// that carry metadata
+const JPEG_METADATA_MARKERS: [u8; 3] = [markers::APP1, markers::APP13, markers::COM];
+const PNG_METADATA_CHUNKS: [[u8; 4]; 5] = [*b"eXIf", *b"tEXt", *b"zTXt", *b"iTXt", *b"tIME"];
+/// JPEG EXIF segments start with this; PNG and WebP store the TIFF data bare
+const EXIF_PREFIX: &[u8] = b"Exif\0\0";
+const WEBP_EXIF: [u8; 4] = *b"EXIF";
+const WEBP_XMP: [u8; 4] = *b"XMP ";
+const WEBP_VP8X: [u8; 4] = *b"VP8X";
+/// VP8X flag bits announcing EXIF and XMP chunks
+const VP8X_EXIF_FLAG: u8 = 0x08;
+const VP8X_XMP_FLAG: u8 = 0x04;
+const CM_PER_INCH: f64 = 2.54;
+
+/// What the image file says about itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageDetails {
+    /// "PNG", "JPEG", "WebP", ...
+    pub format: String,
+    /// ICC profile description ("Display P3"), else "sRGB" or "Gray"
+    pub color_space: String,
+    pub dpi: Option<u32>,
+    /// Some pixel is not fully opaque (an unused alpha channel doesn't count)
+    pub has_alpha: bool,
+    /// More than one frame (GIF, animated WebP or APNG)
+    pub animated: bool,
+}
+
+/// Image files whose metadata can be read and removed
+enum Container {
+    Jpeg(Jpeg),
+    Png(Png),
+    WebP(WebP),
+}
+
+impl Container {
+    /// None for formats without EXIF support (GIF, BMP, ...)
+    fn parse(bytes: &[u8]) -> Result<Option<Self>, String> {
+        let data = Bytes::copy_from_slice(bytes);
+        let container = match image::guess_format(bytes) {
+            Ok(ImageFormat::Jpeg) => Jpeg::from_bytes(data).map(Container::Jpeg),
+            Ok(ImageFormat::Png) => Png::from_bytes(data).map(Container::Png),
+            Ok(ImageFormat::WebP) => WebP::from_bytes(data).map(Container::WebP),
+            _ => return Ok(None),
+        };
+        container
+            .map(Some)
+            .map_err(|e| format!("Failed to read image metadata: {}", e))
+    }
+
+    /// Raw EXIF (TIFF) data
+    fn exif(&self) -> Option<Bytes> {
+        match self {
+            Container::Jpeg(jpeg) => jpeg
+                .segments_by_marker(markers::APP1)
+                .find(|segment| segment.contents().starts_with(EXIF_PREFIX))
+                .map(|segment| segment.contents().slice(EXIF_PREFIX.len()..)),
+            Container::Png(png) => png.chunk_by_type(*b"eXIf").map(|chunk| chunk.contents().clone()),
+            // Some writers add the JPEG prefix here too
+            Container::WebP(webp) => webp.chunk_by_id(WEBP_EXIF).and_then(|chunk| chunk.content().data()).map(|data| {
+                if data.starts_with(EXIF_PREFIX) {
+                    data.slice(EXIF_PREFIX.len()..)
+                } else {
+                    data.clone()
+                }
+            }),
+        }
+    }
+
+    fn icc_profile(&self) -> Option<Bytes> {
+        match self {
+            Container::Jpeg(jpeg) => jpeg.icc_profile(),
+            Container::Png(png) => png.icc_profile(),
+            Container::WebP(webp) => webp.icc_profile(),
+        }
+    }
+
+    /// Resolution from JFIF or pHYs, else from EXIF
+    fn dpi(&self) -> Option<u32> {
+        let native = match self {
+            Container::Jpeg(jpeg) => jpeg.segment_by_marker(markers::APP0).and_then(|segment| jfif_dpi(segment.contents())),
+            Container::Png(png) => png.chunk_by_type(*b"pHYs").and_then(|chunk| png_dpi(chunk.contents())),
+            Container::WebP(_) => None,
+        };
+        native.or_else(|| self.exif().and_then(|exif| exif_dpi(&exif)))
+    }
+
+    /// Drop every metadata block, putting `exif` (TIFF data) in place of the
+    /// original EXIF if given
+    fn strip(self, exif: Option<Vec<u8>>) -> Vec<u8> {
+        match self {
+            Container::Jpeg(mut jpeg) => {
+                let segments = jpeg.segments_mut();
+                let position = segments
+                    .iter()
+                    .position(|segment| segment.marker() == markers::APP1 && segment.contents().starts_with(EXIF_PREFIX));
+                segments.retain(|segment| !JPEG_METADATA_MARKERS.contains(&segment.marker()));
+                if let (Some(exif), Some(position)) = (exif, position) {
+                    let contents = Bytes::from([EXIF_PREFIX, &exif].concat());
+                    segments.insert(position.min(segments.len()), JpegSegment::new_with_contents(markers::APP1, contents));
+                }
+                jpeg.encoder().bytes().to_vec()
+            }
+            Container::Png(mut png) => {
+                for kind in PNG_METADATA_CHUNKS {
+                    png.remove_chunks_by_type(kind);
+                }
+                if let Some(exif) = exif {
+                    let chunks = png.chunks_mut();
+                    let position = chunks.iter().position(|chunk| chunk.kind() == *b"IDAT").unwrap_or(chunks.len() - 1);
+                    chunks.insert(position, PngChunk::new(*b"eXIf", Bytes::from(exif)));
+                }
+                png.encoder().bytes().to_vec()
+            }
+            Container::WebP(mut webp) => {
+                webp.remove_chunks_by_id(WEBP_EXIF);
+                webp.remove_chunks_by_id(WEBP_XMP);
+                // Simple (VP8/VP8L only) files can't hold an EXIF chunk
+                let exif = exif.filter(|_| webp.has_chunk(WEBP_VP8X));
+                let has_exif = exif.is_some();
+                if let Some(exif) = exif {
+                    webp.chunks_mut().push(RiffChunk::new(WEBP_EXIF, RiffContent::Data(Bytes::from(exif))));
+                }
+                // Extended WebP files announce their chunks in the VP8X flags
+                if let Some(vp8x) = webp.chunks_mut().iter_mut().find(|chunk| chunk.id() == WEBP_VP8X) {
+                    if let RiffContent::Data(data) = vp8x.content_mut() {
+                        let mut flags = data.to_vec();
+                        if let Some(first) = flags.first_mut() {
+                            *first &= !(VP8X_EXIF_FLAG | VP8X_XMP_FLAG);
+                            if has_exif {
+                                *first |= VP8X_EXIF_FLAG;
+                            }
+                        }
+                        *data = Bytes::from(flags);
+                    }
+                }
+                webp.encoder().bytes().to_vec()
+            }
+        }
+    }
+}
+
+// ============================================
+// INSPECTING
+// ============================================
+
+/// Format, color space, DPI and transparency of an encoded image
+/// (`img` is the decoded image)
+pub fn inspect(bytes: &[u8], img: &DynamicImage) -> ImageDetails {
+    let container = Container::parse(bytes).ok().flatten();
+    let color_space = container
+        .as_ref()
+        .and_then(Container::icc_profile)
+        .and_then(|icc| icc_color_space(&icc))
+        .unwrap_or_else(|| if img.color().has_color() { "sRGB" } else { "Gray" }.to_string());
+
+    ImageDetails {
+        format: image::guess_format(bytes).map(format_name).unwrap_or_else(|_| "Unknown".to_string()),
+        color_space,
+        dpi: container.as_ref().and_then(Container::dpi),
+        has_alpha: img.color().has_alpha() && img.pixels().any(|(_, _, pixel)| pixel[3] < u8::MAX),
+        animated: is_animated(bytes),
+    }
+}
+
+/// Whether the file holds more than one frame
+fn is_animated(bytes: &[u8]) -> bool {
+    match image::guess_format(bytes) {
+        // GIFs don't announce animation, so look for a second frame
+        Ok(ImageFormat::Gif) => GifDecoder::new(Cursor::new(bytes))
+            .map(|decoder| decoder.into_frames().take(2).count() > 1)
+            .unwrap_or(false),
+        Ok(ImageFormat::WebP) => WebPDecoder::new(Cursor::new(bytes))
+            .map(|decoder| decoder.has_animation())
+            .unwrap_or(false),
+        Ok(ImageFormat::Png) => PngDecoder::new(Cursor::new(bytes))
+            .and_then(|decoder| decoder.is_apng())
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+pub fn format_name(format: ImageFormat) -> String {
+    match format {
+        ImageFormat::Png => "PNG".to_string(),
+        ImageFormat::Jpeg => "JPEG".to_string(),
+        ImageFormat::WebP => "WebP".to_string(),
+        ImageFormat::Gif => "GIF".to_string(),
+        other => other
+            .extensions_str()
+            .first()
+            .map(|extension| extension.to_uppercase())
+            .unwrap_or_else(|| "Unknown".to_string()),
+    }
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// The profile's description, else the color space in its header
+fn icc_color_space(icc: &[u8]) -> Option<String> {
+    icc_description(icc).or_else(|| {
+        match icc.get(16..20)? {
+            b"RGB " => Some("RGB".to_string()),
+            b"GRAY" => Some("Gray".to_string()),
+            b"CMYK" => Some("CMYK".to_string()),
+            _ => None,
+        }
+    })
+}
+
+/// Text of the 'desc' tag: ASCII in version 2 profiles, UTF-16 in version 4
+fn icc_description(icc: &[u8]) -> Option<String> {
+    let tag_count = be_u32(icc, 128)? as usize;
+    let entry = (0..tag_count.min(256))
+        .map(|i| 132 + i * 12)
+        .find(|&entry| icc.get(entry..entry + 4) == Some(b"desc"))?;
+    let offset = be_u32(icc, entry + 4)? as usize;
+    let size = be_u32(icc, entry + 8)? as usize;
+    let tag = icc.get(offset..offset.checked_add(size)?)?;
+
+    let text = match tag.get(0..4)? {
+        b"desc" => {
+            let length = be_u32(tag, 8)? as usize;
+            String::from_utf8_lossy(tag.get(12..12usize.checked_add(length)?)?).to_string()
+        }
+        b"mluc" => {
+            // First record: language, country, length, offset
+            let length = be_u32(tag, 20)? as usize;
+            let start = be_u32(tag, 24)? as usize;
+            let units: Vec<u16> = tag
+                .get(start..start.checked_add(length)?)?
+                .chunks_exact(2)
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => return None,
+    };
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Dots per inch from a density and a unit (1 = inch, 2 = centimeter)
+fn density_dpi(density: f64, unit: u8) -> Option<u32> {
+    let dpi = match unit {
+        1 => density,
+        2 => density * CM_PER_INCH,
+        _ => return None,
+    };
+    let dpi = dpi.round();
+    (dpi >= 1.0 && dpi <= u32::MAX as f64).then_some(dpi as u32)
+}
+
+/// JFIF APP0: "JFIF\0", version, units, X density, Y density
+fn jfif_dpi(contents: &[u8]) -> Option<u32> {
+    if !contents.starts_with(b"JFIF\0") {
+        return None;
+    }
+    density_dpi(be_u16(contents, 8)? as f64, *contents.get(7)?)
+}
+
+/// pHYs: pixels per unit on X and Y, then the unit (1 = meter, so /100 per cm)
+fn png_dpi(contents: &[u8]) -> Option<u32> {
+    if *contents.get(8)? != 1 {
+        return None;
+    }
+    density_dpi(be_u32(contents, 0)? as f64 / 100.0, 2)
+}
+
+/// EXIF XResolution in ResolutionUnit (2 = inch, 3 = centimeter)
+fn exif_dpi(exif: &[u8]) -> Option<u32> {
+    let exif = Reader::new().read_raw(exif.to_vec()).ok()?;
+    let resolution = match &exif.get_field(Tag::XResolution, In::PRIMARY)?.value {
+        Value::Rational(values) => values.first()?.to_f64(),
+        _ => return None,
+    };
+    let unit = exif
+        .get_field(Tag::ResolutionUnit, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0));
+    match unit {
+        None | Some(2) => density_dpi(resolution, 1),
+        Some(3) => density_dpi(resolution, 2),
+        _ => None,
+    }
+}
+
+// ============================================
+// STRIPPING
+// ============================================
+
+/// EXIF (TIFF) data with only KEPT_EXIF_TAGS; None if none are present
+fn display_exif(exif: &[u8]) -> Option<Vec<u8>> {
+    let exif = Reader::new().read_raw(exif.to_vec()).ok()?;
+    let kept: Vec<&Field> = exif
+        .fields()
+        .filter(|field| field.ifd_num == In::PRIMARY && KEPT_EXIF_TAGS.contains(&field.tag))
+        .collect();
+    if kept.is_empty() {
+        return None;
+    }
+    let mut writer = Writer::new();
+    for field in kept {
+        writer.push_field(field);
+    }
+    let mut tiff = Cursor::new(Vec::new());
+    writer.write(&mut tiff, exif.little_endian()).ok()?;
+    Some(tiff.into_inner())
+}
+
+/// The image without location, camera and other metadata
+///
+/// Formats without EXIF support are returned as they are. Errors if a JPEG,
+/// PNG or WebP file can't be read, so callers can re-encode it instead.
+pub fn strip_metadata(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let Some(container) = Container::parse(bytes)? else {
+        return Ok(bytes.to_vec());
+    };
+    let exif = container.exif().and_then(|exif| display_exif(&exif));
+    Ok(container.strip(exif))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+    use img_parts::ImageEXIF;
+    use exif::Rational;
+
+    fn encode(img: &DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        img.write_to(&mut Cursor::new(&mut bytes), format).unwrap();
+        bytes
+    }
+
+    /// EXIF from a phone: orientation, 300 dpi, camera model and a GPS position
+    fn phone_exif() -> Vec<u8> {
+        let fields = [
+            Field { tag: Tag::Orientation, ifd_num: In::PRIMARY, value: Value::Short(vec![6]) },
+            Field { tag: Tag::XResolution, ifd_num: In::PRIMARY, value: Value::Rational(vec![Rational { num: 300, denom: 1 }]) },
+            Field { tag: Tag::ResolutionUnit, ifd_num: In::PRIMARY, value: Value::Short(vec![2]) },
+            Field { tag: Tag::Model, ifd_num: In::PRIMARY, value: Value::Ascii(vec![b"Pixel 8".to_vec()]) },
+            Field { tag: Tag::GPSLatitudeRef, ifd_num: In::PRIMARY, value: Value::Ascii(vec![b"N".to_vec()]) },
+            Field {
+                tag: Tag::GPSLatitude,
+                ifd_num: In::PRIMARY,
+                value: Value::Rational(vec![
+                    Rational { num: 48, denom: 1 },
+                    Rational { num: 51, denom: 1 },
+                    Rational { num: 2964, denom: 100 },
+                ]),
+            },
+        ];
+        let mut writer = Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        tiff.into_inner()
+    }
+
+    fn read_exif(bytes: &[u8]) -> Option<exif::Exif> {
+        let exif = Container::parse(bytes).unwrap()?.exif()?;
+        Reader::new().read_raw(exif.to_vec()).ok()
+    }
+
+    fn photo() -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(64, 48, |x, y| Rgb([(x * 4) as u8, (y * 5) as u8, 90])))
+    }
+
+    #[test]
+    fn test_strip_jpeg_keeps_orientation_only() {
+        let mut jpeg = Jpeg::from_bytes(Bytes::from(encode(&photo(), ImageFormat::Jpeg))).unwrap();
+        jpeg.set_exif(Some(Bytes::from(phone_exif())));
+        let xmp = [b"http://ns.adobe.com/xap/1.0/\0".as_slice(), b"<x:xmpmeta/>"].concat();
+        jpeg.segments_mut().insert(1, JpegSegment::new_with_contents(markers::APP1, Bytes::from(xmp)));
+        let original = jpeg.encoder().bytes().to_vec();
+
+        let exif = read_exif(&original).unwrap();
+        assert!(exif.get_field(Tag::GPSLatitude, In::PRIMARY).is_some());
+        assert_eq!(inspect(&original, &photo()).dpi, Some(300));
+
+        let stripped = strip_metadata(&original).unwrap();
+        let exif = read_exif(&stripped).unwrap();
+        assert!(exif.get_field(Tag::GPSLatitude, In::PRIMARY).is_none());
+        assert!(exif.get_field(Tag::Model, In::PRIMARY).is_none());
+        assert_eq!(exif.get_field(Tag::Orientation, In::PRIMARY).unwrap().value.get_uint(0), Some(6));
+        assert!(!stripped.windows(9).any(|window| window == b"xmpmeta/>"));
+
+        // Same pixels, still readable
+        let decoded = image::load_from_memory(&stripped).unwrap();
+        assert_eq!(decoded.dimensions(), (64, 48));
+        assert_eq!(inspect(&stripped, &decoded).dpi, Some(300));
+    }
+
+    #[test]
+    fn test_strip_png_text_and_exif() {
+        let mut png = Png::from_bytes(Bytes::from(encode(&photo(), ImageFormat::Png))).unwrap();
+        png.set_exif(Some(Bytes::from(phone_exif())));
+        let chunks = png.chunks_mut();
+        chunks.insert(1, PngChunk::new(*b"tEXt", Bytes::from_static(b"Comment\0Taken at home")));
+        // 3780 pixels per meter = 96 dpi
+        chunks.insert(1, PngChunk::new(*b"pHYs", Bytes::from_static(&[0, 0, 0x0E, 0xC4, 0, 0, 0x0E, 0xC4, 1])));
+        let original = png.encoder().bytes().to_vec();
+
+        let stripped = strip_metadata(&original).unwrap();
+        let png = Png::from_bytes(Bytes::from(stripped.clone())).unwrap();
+        assert!(png.chunk_by_type(*b"tEXt").is_none());
+        let exif = read_exif(&stripped).unwrap();
+        assert!(exif.get_field(Tag::GPSLatitude, In::PRIMARY).is_none());
+
+        let decoded = image::load_from_memory(&stripped).unwrap();
+        assert_eq!(decoded.to_rgb8(), photo().to_rgb8());
+        // pHYs wins over EXIF
+        assert_eq!(inspect(&stripped, &decoded).dpi, Some(96));
+    }
+
+    #[test]
+    fn test_strip_without_metadata_or_support() {
+        let png = encode(&photo(), ImageFormat::Png);
+        let stripped = strip_metadata(&png).unwrap();
+        assert!(read_exif(&stripped).is_none());
+        assert_eq!(image::load_from_memory(&stripped).unwrap().to_rgb8(), photo().to_rgb8());
+
+        let bmp = encode(&photo(), ImageFormat::Bmp);
+        assert_eq!(strip_metadata(&bmp).unwrap(), bmp);
+
+        // Truncated files are reported rather than passed through
+        assert!(strip_metadata(&png[..20]).is_err());
+    }
+
+    #[test]
+    fn test_inspect_format_color_space_and_alpha() {
+        let opaque = DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 8, Rgba([10, 20, 30, 255])));
+        let bytes = encode(&opaque, ImageFormat::Png);
+        let details = inspect(&bytes, &opaque);
+        assert_eq!(details.format, "PNG");
+        assert_eq!(details.color_space, "sRGB");
+        assert_eq!(details.dpi, None);
+        assert!(!details.has_alpha);
+
+        let mut pixels = RgbaImage::from_pixel(8, 8, Rgba([10, 20, 30, 255]));
+        pixels.put_pixel(3, 3, Rgba([0, 0, 0, 0]));
+        let transparent = DynamicImage::ImageRgba8(pixels);
+        assert!(inspect(&encode(&transparent, ImageFormat::Png), &transparent).has_alpha);
+
+        let gray = DynamicImage::ImageLuma8(photo().to_luma8());
+        let bytes = encode(&gray, ImageFormat::Jpeg);
+        let details = inspect(&bytes, &gray);
+        assert_eq!(details.format, "JPEG");
+        assert_eq!(details.color_space, "Gray");
+        assert!(!details.animated);
+    }
+
+    #[test]
+    fn test_inspect_animated_gif() {
+        use image::codecs::gif::GifEncoder;
+        use image::{Delay, Frame};
+
+        let frames = [Rgba([255, 0, 0, 255]), Rgba([0, 0, 255, 255])]
+            .map(|color| Frame::from_parts(RgbaImage::from_pixel(8, 8, color), 0, 0, Delay::from_numer_denom_ms(100, 1)));
+        let mut gif = Vec::new();
+        GifEncoder::new(&mut gif).encode_frames(frames).unwrap();
+        let first = DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 8, Rgba([255, 0, 0, 255])));
+        let details = inspect(&gif, &first);
+        assert_eq!(details.format, "GIF");
+        assert!(details.animated);
+
+        // A single-frame GIF is a still image
+        let still = encode(&first, ImageFormat::Gif);
+        assert!(!inspect(&still, &first).animated);
+        assert!(!inspect(&encode(&first, ImageFormat::Png), &first).animated);
+    }
+
+    #[test]
+    fn test_icc_description() {
+        // Version 4 profile with a 'mluc' description
+        let name: Vec<u8> = "Display P3".encode_utf16().flat_map(|unit| unit.to_be_bytes()).collect();
+        let mut tag = b"mluc\0\0\0\0".to_vec();
+        tag.extend(1u32.to_be_bytes());
+        tag.extend(12u32.to_be_bytes());
+        tag.extend(b"enUS");
+        tag.extend((name.len() as u32).to_be_bytes());
+        tag.extend(28u32.to_be_bytes());
+        tag.extend(&name);
+
+        let mut icc = vec![0u8; 144];
+        icc[16..20].copy_from_slice(b"RGB ");
+        icc[128..132].copy_from_slice(&1u32.to_be_bytes());
+        icc[132..136].copy_from_slice(b"desc");
+        icc[136..140].copy_from_slice(&144u32.to_be_bytes());
+        icc[140..144].copy_from_slice(&(tag.len() as u32).to_be_bytes());
+        icc.extend(&tag);
+        assert_eq!(icc_color_space(&icc).as_deref(), Some("Display P3"));
+
+        // No description: the header's color space
+        icc[132..136].copy_from_slice(b"wtpt");
+        assert_eq!(icc_color_space(&icc).as_deref(), Some("RGB"));
+    }
+}
@@ -0,0 +1,744 @@
+// Importers Module - Bring history over from other clipboard managers
+// Supports CopyQ exports (.cpq), GPaste history.xml, Clipman (xfce4-clipman
+// textsrc and the wlroots clipman.json), Ditto's and Maccy's SQLite databases
+// and plain newline/NUL-separated text dumps.
+// Every importer produces ImportedItems; import_items classifies them with
+// detect_content_type and inserts them in one transaction. Original timestamps
+// are kept; tools that don't record them get timestamps just before the import,
+// newest first, so the original order survives.
+
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::clipboard_monitor::category_for_content_type;
+use crate::content_detector::detect_content_type;
+use crate::db::items;
+use crate::export::read_import_text;
+use crate::paths::database_path;
+
+/// Core Data stores dates as seconds since 2001-01-01
+const CORE_DATA_EPOCH_SECS: f64 = 978_307_200.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportSource {
+    CopyQ,
+    GPaste,
+    Clipman,
+    Ditto,
+    Maccy,
+    Text,
+}
+
+impl ImportSource {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "copyq" => Some(Self::CopyQ),
+            "gpaste" => Some(Self::GPaste),
+            "clipman" | "xfce4-clipman" => Some(Self::Clipman),
+            "ditto" => Some(Self::Ditto),
+            "maccy" => Some(Self::Maccy),
+            "text" | "txt" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CopyQ => "copyq",
+            Self::GPaste => "gpaste",
+            Self::Clipman => "clipman",
+            Self::Ditto => "ditto",
+            Self::Maccy => "maccy",
+            Self::Text => "text",
+        }
+    }
+
+    /// Where the tool keeps its history by default, for the file dialog
+    pub fn default_path(&self) -> Option<PathBuf> {
+        match self {
+            Self::GPaste => dirs::data_dir().map(|dir| dir.join("gpaste").join("history.xml")),
+            Self::Clipman => {
+                let xfce = dirs::cache_dir().map(|dir| dir.join("xfce4").join("clipman").join("textsrc"));
+                let wayland = dirs::data_dir().map(|dir| dir.join("clipman.json"));
+                xfce.filter(|p| p.exists()).or(wayland)
+            }
+            Self::Ditto => dirs::config_dir().map(|dir| dir.join("Ditto").join("Ditto.db")),
+            Self::Maccy => dirs::home_dir().map(|home| {
+                home.join("Library/Containers/org.p0deje.Maccy/Data/Library/Application Support/Maccy/Storage.sqlite")
+            }),
+            Self::CopyQ | Self::Text => None,
+        }
+    }
+}
+
+/// One history entry read from another tool
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedItem {
+    pub content: String,
+    /// Milliseconds; None when the tool doesn't record it
+    pub timestamp: Option<i64>,
+    pub app_name: Option<String>,
+    pub app_bundle_id: Option<String>,
+    pub is_pinned: bool,
+}
+
+impl ImportedItem {
+    fn text(content: impl Into<String>) -> Self {
+        Self { content: content.into(), ..Default::default() }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+    pub source: String,
+    /// Entries found in the file
+    pub found: usize,
+    pub imported: usize,
+    /// Empty entries and text already in the history
+    pub skipped: usize,
+    /// Entries that aren't text (images, files) and were left out
+    pub unsupported: usize,
+}
+
+/// Items read from a source, plus how many non-text entries were left out
+pub struct SourceItems {
+    pub items: Vec<ImportedItem>,
+    pub unsupported: usize,
+}
+
+impl From<Vec<ImportedItem>> for SourceItems {
+    fn from(items: Vec<ImportedItem>) -> Self {
+        Self { items, unsupported: 0 }
+    }
+}
+
+/// Read another tool's history file
+pub fn read_source(source: ImportSource, path: &Path) -> Result<SourceItems, String> {
+    match source {
+        ImportSource::CopyQ => {
+            let bytes = std::fs::read(path).map_err(|e| format!("Failed to read import file: {}", e))?;
+            parse_copyq(&bytes)
+        }
+        ImportSource::GPaste => Ok(parse_gpaste(&read_import_text(path, None)?)),
+        ImportSource::Clipman => parse_clipman(&read_import_text(path, None)?).map(Into::into),
+        ImportSource::Ditto => read_ditto(path).map(Into::into),
+        ImportSource::Maccy => read_maccy(path).map(Into::into),
+        ImportSource::Text => Ok(parse_text_dump(&read_import_text(path, None)?).into()),
+    }
+}
+
+/// Insert imported items in one transaction, skipping text already in the history
+/// Returns (imported, skipped)
+pub fn import_items(conn: &mut Connection, items: Vec<ImportedItem>) -> Result<(usize, usize), String> {
+    let tx = conn.transaction().map_err(|e| format!("Failed to start import: {}", e))?;
+    let now = items::now_millis();
+    let mut seen = HashSet::new();
+    let (mut imported, mut skipped) = (0, 0);
+
+    for (index, item) in items.into_iter().enumerate() {
+        if item.content.trim().is_empty() || !seen.insert(item.content.clone()) {
+            skipped += 1;
+            continue;
+        }
+
+        let exists: Option<i64> = tx
+            .query_row(
+                "SELECT id FROM clipboard_items WHERE content = ?1 AND is_deleted = 0 LIMIT 1",
+                params![item.content],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to check for duplicates: {}", e))?;
+        if exists.is_some() {
+            skipped += 1;
+            continue;
+        }
+
+        let content_type = detect_content_type(&item.content);
+        let timestamp = item.timestamp.unwrap_or(now - 1 - index as i64);
+        tx.execute(
+            "INSERT INTO clipboard_items
+             (content, content_type, category, app_name, app_bundle_id, is_image, timestamp, is_pinned, is_deleted)
+             VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7, 0)",
+            params![
+                item.content,
+                content_type.as_str(),
+                category_for_content_type(&content_type),
+                item.app_name,
+                item.app_bundle_id,
+                timestamp,
+                item.is_pinned,
+            ],
+        )
+        .map_err(|e| format!("Failed to import item: {}", e))?;
+        imported += 1;
+    }
+
+    tx.commit().map_err(|e| format!("Failed to commit import: {}", e))?;
+    Ok((imported, skipped))
+}
+
+/// Accept seconds, milliseconds or microseconds since the epoch
+fn normalize_timestamp(value: i64) -> i64 {
+    if value > 100_000_000_000_000 {
+        value / 1000
+    } else if value > 100_000_000_000 {
+        value
+    } else {
+        value * 1000
+    }
+}
+
+// ============================================
+// TEXT DUMPS
+// ============================================
+
+/// One entry per line, or per NUL when the dump contains any (multi-line entries)
+/// The first entry is treated as the newest
+pub fn parse_text_dump(contents: &str) -> Vec<ImportedItem> {
+    let entries: Vec<&str> = if contents.contains('\0') {
+        contents.split('\0').collect()
+    } else {
+        contents.lines().collect()
+    };
+    entries
+        .into_iter()
+        .map(|entry| entry.strip_suffix('\r').unwrap_or(entry))
+        .filter(|entry| !entry.trim().is_empty())
+        .map(ImportedItem::text)
+        .collect()
+}
+
+// ============================================
+// GPASTE
+// ============================================
+
+/// GPaste's history.xml: <item kind="Text" date="..."><value><![CDATA[...]]></value></item>
+pub fn parse_gpaste(xml: &str) -> SourceItems {
+    let item_re = Regex::new(r"(?s)<item\b([^>]*?)(?:/>|>(.*?)</item>)").unwrap();
+    let attr_re = Regex::new(r#"(\w+)="([^"]*)""#).unwrap();
+    let cdata_re = Regex::new(r"(?s)<!\[CDATA\[(.*?)\]\]>").unwrap();
+    let value_re = Regex::new(r"(?s)<value>(.*?)</value>").unwrap();
+
+    let mut result = SourceItems { items: Vec::new(), unsupported: 0 };
+    for captures in item_re.captures_iter(xml) {
+        let attrs: Vec<(&str, &str)> = attr_re
+            .captures_iter(&captures[1])
+            .map(|attr| (attr.get(1).unwrap().as_str(), attr.get(2).unwrap().as_str()))
+            .collect();
+        let attr = |name: &str| attrs.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+
+        // Images are stored by GPaste as files we can't carry over reliably
+        if !matches!(attr("kind"), Some("Text") | Some("Uris") | None) {
+            result.unsupported += 1;
+            continue;
+        }
+
+        let body = captures.get(2).map(|m| m.as_str()).unwrap_or_default();
+        let inner = value_re.captures(body).map(|v| v.get(1).unwrap().as_str()).unwrap_or(body);
+        // GPaste splits "]]>" across CDATA sections, so join them all
+        let content = if cdata_re.is_match(inner) {
+            cdata_re.captures_iter(inner).map(|c| c.get(1).unwrap().as_str()).collect::<String>()
+        } else {
+            unescape_xml(inner.trim())
+        };
+
+        result.items.push(ImportedItem {
+            content,
+            timestamp: attr("date").and_then(|date| date.parse().ok()).map(normalize_timestamp),
+            ..Default::default()
+        });
+    }
+    result
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// ============================================
+// CLIPMAN
+// ============================================
+
+/// xfce4-clipman's textsrc key file or the wlroots clipman's JSON array
+/// Both store the oldest entry first.
+pub fn parse_clipman(contents: &str) -> Result<Vec<ImportedItem>, String> {
+    let mut entries: Vec<String> = match contents.lines().find_map(|line| line.strip_prefix("texts=")) {
+        Some(line) => split_key_file_list(line),
+        None => serde_json::from_str(contents).map_err(|e| format!("Not a Clipman history file: {}", e))?,
+    };
+    entries.reverse();
+    Ok(entries.into_iter().map(ImportedItem::text).collect())
+}
+
+/// GKeyFile string list: ';'-separated with \; \n \t \r \s and \\ escapes
+fn split_key_file_list(value: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => current.push('\n'),
+                Some('t') => current.push('\t'),
+                Some('r') => current.push('\r'),
+                Some('s') => current.push(' '),
+                Some(other) => current.push(other),
+                None => {}
+            },
+            ';' => entries.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        entries.push(current);
+    }
+    entries
+}
+
+// ============================================
+// DITTO / MACCY (SQLITE)
+// ============================================
+
+fn open_foreign_db(path: &Path) -> Result<Connection, String> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+/// Ditto keeps text as CF_UNICODETEXT (UTF-16LE) or CF_TEXT blobs in Data
+pub fn read_ditto(path: &Path) -> Result<Vec<ImportedItem>, String> {
+    let conn = open_foreign_db(path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.lDate, m.lDontAutoDelete, u.ooData, t.ooData
+             FROM Main m
+             LEFT JOIN Data u ON u.lParentID = m.lID AND u.strClipBoardFormat = 'CF_UNICODETEXT'
+             LEFT JOIN Data t ON t.lParentID = m.lID AND t.strClipBoardFormat = 'CF_TEXT'
+             WHERE m.bIsGroup = 0 AND (u.ooData IS NOT NULL OR t.ooData IS NOT NULL)
+             GROUP BY m.lID
+             ORDER BY m.lDate DESC",
+        )
+        .map_err(|e| format!("Not a Ditto database: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            let date: i64 = row.get(0)?;
+            let keep: Option<i64> = row.get(1)?;
+            let unicode: Option<Vec<u8>> = row.get(2)?;
+            let ansi: Option<Vec<u8>> = row.get(3)?;
+            let content = match (unicode, ansi) {
+                (Some(bytes), _) => decode_utf16le(&bytes),
+                (None, Some(bytes)) => String::from_utf8_lossy(&bytes).trim_end_matches('\0').to_string(),
+                (None, None) => String::new(),
+            };
+            Ok(ImportedItem {
+                content,
+                timestamp: Some(normalize_timestamp(date)),
+                // "Never auto delete" is the closest thing Ditto has to pinning
+                is_pinned: keep.unwrap_or(0) > 0,
+                ..Default::default()
+            })
+        })
+        .map_err(|e| format!("Failed to read Ditto history: {}", e))?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read Ditto history: {}", e))
+}
+
+fn decode_utf16le(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Maccy's Core Data store: ZHISTORYITEM with contents per pasteboard type
+pub fn read_maccy(path: &Path) -> Result<Vec<ImportedItem>, String> {
+    let conn = open_foreign_db(path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT i.ZLASTCOPIEDAT, i.ZPIN, i.ZAPPLICATION, c.ZVALUE
+             FROM ZHISTORYITEM i
+             JOIN ZHISTORYITEMCONTENT c ON c.ZITEM = i.Z_PK
+             WHERE c.ZTYPE = 'public.utf8-plain-text'
+             GROUP BY i.Z_PK
+             ORDER BY i.ZLASTCOPIEDAT DESC",
+        )
+        .map_err(|e| format!("Not a Maccy database: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            let copied_at: Option<f64> = row.get(0)?;
+            let pin: Option<String> = row.get(1)?;
+            let value: Option<Vec<u8>> = row.get(3)?;
+            Ok(ImportedItem {
+                content: value.map(|v| String::from_utf8_lossy(&v).to_string()).unwrap_or_default(),
+                timestamp: copied_at.map(|secs| ((secs + CORE_DATA_EPOCH_SECS) * 1000.0) as i64),
+                app_bundle_id: row.get(2)?,
+                is_pinned: pin.is_some(),
+                ..Default::default()
+            })
+        })
+        .map_err(|e| format!("Failed to read Maccy history: {}", e))?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read Maccy history: {}", e))
+}
+
+// ============================================
+// COPYQ
+// ============================================
+
+/// CopyQ's "Export" file: QByteArray "CopyQ v4" followed by a QVariantMap whose
+/// "tabs" entry lists { name, data } maps; data is the tab's serialized items.
+/// CopyQ doesn't store copy times, so items keep their tab order.
+pub fn parse_copyq(bytes: &[u8]) -> Result<SourceItems, String> {
+    let mut stream = QtStream::new(bytes);
+    let header = stream.byte_array().ok_or("Not a CopyQ export")?;
+    if header != b"CopyQ v4" {
+        return Err("Unsupported CopyQ export (expected \"CopyQ v4\" from File > Export)".to_string());
+    }
+
+    let data = stream.variant_map().ok_or("Failed to read CopyQ export")?;
+    let tabs = data
+        .into_iter()
+        .find(|(key, _)| key == "tabs")
+        .map(|(_, tabs)| tabs)
+        .ok_or("CopyQ export contains no tabs")?;
+
+    let mut result = SourceItems { items: Vec::new(), unsupported: 0 };
+    let QVariant::List(tabs) = tabs else {
+        return Err("Failed to read CopyQ tabs".to_string());
+    };
+    for tab in tabs {
+        let QVariant::Map(fields) = tab else { continue };
+        let Some(QVariant::Bytes(data)) = fields.into_iter().find(|(key, _)| key == "data").map(|(_, v)| v) else {
+            continue;
+        };
+        read_copyq_tab(&data, &mut result).ok_or("Failed to read CopyQ tab")?;
+    }
+    Ok(result)
+}
+
+/// Items of one tab: count, then per item a version marker and MIME -> data pairs
+fn read_copyq_tab(data: &[u8], result: &mut SourceItems) -> Option<()> {
+    let mut stream = QtStream::new(data);
+    let count = stream.i32()?;
+    for _ in 0..count.max(0) {
+        let mut text = None;
+        let marker = stream.i32()?;
+        // -2: compressed MIME names, -1: plain names with compression flag, else legacy map
+        let formats = if marker < 0 { stream.i32()? } else { marker };
+        for _ in 0..formats.max(0) {
+            let mime = stream.string()?;
+            let compressed = if marker < 0 { stream.u8()? != 0 } else { false };
+            let bytes = stream.byte_array()?;
+            if text.is_none() && is_plain_text_mime(&mime) {
+                let bytes = if compressed { q_uncompress(&bytes)? } else { bytes };
+                text = Some(String::from_utf8_lossy(&bytes).to_string());
+            }
+        }
+        match text {
+            Some(text) => result.items.push(ImportedItem::text(text)),
+            None => result.unsupported += 1,
+        }
+    }
+    Some(())
+}
+
+/// CopyQ shortens MIME names to a one-digit prefix id plus the rest
+fn is_plain_text_mime(mime: &str) -> bool {
+    let rest = mime.strip_prefix(|c: char| c.is_ascii_digit()).unwrap_or(mime);
+    [mime, rest].iter().any(|name| {
+        *name == "text/plain" || *name == "plain" || name.starts_with("text/plain;") || name.starts_with("plain;")
+    })
+}
+
+/// qCompress: 4-byte big-endian length, then a zlib stream
+fn q_uncompress(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    flate2::read::ZlibDecoder::new(bytes.get(4..)?).read_to_end(&mut out).ok()?;
+    Some(out)
+}
+
+#[derive(Debug)]
+enum QVariant {
+    Map(Vec<(String, QVariant)>),
+    List(Vec<QVariant>),
+    Bytes(Vec<u8>),
+    Other,
+}
+
+/// Reader for the subset of QDataStream (Qt 4.7 format) CopyQ exports use
+struct QtStream<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> QtStream<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        self.u32().map(|v| v as i32)
+    }
+
+    /// Length-prefixed bytes; 0xFFFFFFFF marks a null array
+    fn byte_array(&mut self) -> Option<Vec<u8>> {
+        match self.u32()? {
+            u32::MAX => Some(Vec::new()),
+            len => self.take(len as usize).map(<[u8]>::to_vec),
+        }
+    }
+
+    /// QString: byte length, then UTF-16BE
+    fn string(&mut self) -> Option<String> {
+        let bytes = self.byte_array()?;
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|p| u16::from_be_bytes([p[0], p[1]])).collect();
+        Some(String::from_utf16_lossy(&units))
+    }
+
+    fn variant_map(&mut self) -> Option<Vec<(String, QVariant)>> {
+        let count = self.u32()?;
+        (0..count).map(|_| Some((self.string()?, self.variant()?))).collect()
+    }
+
+    fn variant(&mut self) -> Option<QVariant> {
+        let kind = self.u32()?;
+        let _is_null = self.u8()?;
+        Some(match kind {
+            1 => self.take(1).map(|_| QVariant::Other)?,      // Bool
+            2 | 3 => self.take(4).map(|_| QVariant::Other)?,  // Int, UInt
+            4..=6 => self.take(8).map(|_| QVariant::Other)?,  // LongLong, ULongLong, Double
+            7 => self.take(2).map(|_| QVariant::Other)?,      // Char
+            8 => QVariant::Map(self.variant_map()?),
+            9 => {
+                let count = self.u32()?;
+                QVariant::List((0..count).map(|_| self.variant()).collect::<Option<_>>()?)
+            }
+            10 => {
+                self.byte_array()?;
+                QVariant::Other
+            }
+            11 => {
+                let count = self.u32()?;
+                for _ in 0..count {
+                    self.byte_array()?;
+                }
+                QVariant::Other
+            }
+            12 => QVariant::Bytes(self.byte_array()?),
+            _ => return None,
+        })
+    }
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
+/// Default history location of another clipboard manager, if it has one
+#[tauri::command]
+pub fn get_import_default_path(source: String) -> Option<String> {
+    ImportSource::parse(&source)?
+        .default_path()
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// Import another clipboard manager's history
+#[tauri::command]
+pub async fn import_from_app(app: AppHandle, source: String, file_path: String) -> Result<ImportSummary, String> {
+    let source = ImportSource::parse(&source).ok_or_else(|| format!("Unknown import source: {}", source))?;
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        let SourceItems { items, unsupported } = read_source(source, Path::new(&file_path))?;
+        let found = items.len() + unsupported;
+        let mut conn = items::open_read_write(&database_path()?)?;
+        let (imported, skipped) = import_items(&mut conn, items)?;
+        Ok::<_, String>(ImportSummary { source: source.as_str().to_string(), found, imported, skipped, unsupported })
+    })
+    .await
+    .map_err(|e| format!("Import failed: {}", e))??;
+
+    println!(
+        "[CopyGum] Imported {} of {} items from {}",
+        summary.imported, summary.found, summary.source
+    );
+    let _ = app.emit("items-changed", ());
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(items: &[ImportedItem]) -> Vec<&str> {
+        items.iter().map(|item| item.content.as_str()).collect()
+    }
+
+    #[test]
+    fn test_text_dump_splits_on_nul_when_present() {
+        assert_eq!(contents(&parse_text_dump("one\r\n\ntwo\n")), vec!["one", "two"]);
+        assert_eq!(contents(&parse_text_dump("multi\nline\0second\0")), vec!["multi\nline", "second"]);
+    }
+
+    #[test]
+    fn test_gpaste_history() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<history version="2.0">
+  <item kind="Text" uuid="a" date="1700000000"><value><![CDATA[echo "]]]]><![CDATA[>"]]></value></item>
+  <item kind="Image" uuid="b" date="1700000001"><value><![CDATA[/tmp/x.png]]></value></item>
+  <item kind="Uris" uuid="c" date="1700000002"><value>file:///a&amp;b</value></item>
+</history>"#;
+        let result = parse_gpaste(xml);
+        assert_eq!(contents(&result.items), vec!["echo \"]]>\"", "file:///a&b"]);
+        assert_eq!(result.items[0].timestamp, Some(1_700_000_000_000));
+        assert_eq!(result.unsupported, 1);
+    }
+
+    #[test]
+    fn test_clipman_formats_newest_first() {
+        let keyfile = "[texts]\ntexts=old\\;er;multi\\nline;newest;\n";
+        assert_eq!(contents(&parse_clipman(keyfile).unwrap()), vec!["newest", "multi\nline", "old;er"]);
+        assert_eq!(contents(&parse_clipman(r#"["a","b"]"#).unwrap()), vec!["b", "a"]);
+    }
+
+    fn qt_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+        out.extend((bytes.len() as u32).to_be_bytes());
+        out.extend(bytes);
+    }
+
+    fn qt_string(out: &mut Vec<u8>, s: &str) {
+        let utf16: Vec<u8> = s.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        qt_bytes(out, &utf16);
+    }
+
+    #[test]
+    fn test_copyq_export() {
+        let mut tab = Vec::new();
+        tab.extend(2i32.to_be_bytes());
+        // Item 1: plain text, compressed MIME name with id prefix
+        tab.extend((-2i32).to_be_bytes());
+        tab.extend(1i32.to_be_bytes());
+        qt_string(&mut tab, "0text/plain");
+        tab.push(0);
+        qt_bytes(&mut tab, "héllo".as_bytes());
+        // Item 2: image only
+        tab.extend((-2i32).to_be_bytes());
+        tab.extend(1i32.to_be_bytes());
+        qt_string(&mut tab, "0image/png");
+        tab.push(0);
+        qt_bytes(&mut tab, b"\x89PNG");
+
+        let mut file = Vec::new();
+        qt_bytes(&mut file, b"CopyQ v4");
+        file.extend(1u32.to_be_bytes());
+        qt_string(&mut file, "tabs");
+        file.extend(9u32.to_be_bytes()); // QVariantList
+        file.push(0);
+        file.extend(1u32.to_be_bytes());
+        file.extend(8u32.to_be_bytes()); // QVariantMap
+        file.push(0);
+        file.extend(2u32.to_be_bytes());
+        qt_string(&mut file, "name");
+        file.extend(10u32.to_be_bytes());
+        file.push(0);
+        qt_string(&mut file, "&clipboard");
+        qt_string(&mut file, "data");
+        file.extend(12u32.to_be_bytes());
+        file.push(0);
+        qt_bytes(&mut file, &tab);
+
+        let result = parse_copyq(&file).unwrap();
+        assert_eq!(contents(&result.items), vec!["héllo"]);
+        assert_eq!(result.unsupported, 1);
+        assert!(parse_copyq(b"garbage").is_err());
+    }
+
+    #[test]
+    fn test_import_items_classifies_and_keeps_timestamps() {
+        let mut conn = items::test_connection();
+        items::add_text_item(&conn, "already here", "text", "text", None).unwrap();
+
+        let imported = vec![
+            ImportedItem { timestamp: Some(1_000), ..ImportedItem::text("https://example.com") },
+            ImportedItem::text("already here"),
+            ImportedItem::text("https://example.com"),
+            ImportedItem::text("   "),
+            ImportedItem { is_pinned: true, ..ImportedItem::text("plain words") },
+        ];
+        assert_eq!(import_items(&mut conn, imported).unwrap(), (2, 3));
+
+        let (category, timestamp): (String, i64) = conn
+            .query_row(
+                "SELECT category, timestamp FROM clipboard_items WHERE content = 'https://example.com'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((category.as_str(), timestamp), ("links", 1_000));
+        let pinned: bool = conn
+            .query_row("SELECT is_pinned FROM clipboard_items WHERE content = 'plain words'", [], |row| row.get(0))
+            .unwrap();
+        assert!(pinned);
+    }
+
+    #[test]
+    fn test_ditto_and_maccy_databases() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let ditto = dir.path().join("Ditto.db");
+        let conn = Connection::open(&ditto).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE Main (lID INTEGER PRIMARY KEY, lDate INTEGER, mText TEXT, lDontAutoDelete INTEGER, bIsGroup INTEGER);
+             CREATE TABLE Data (lID INTEGER PRIMARY KEY, lParentID INTEGER, strClipBoardFormat TEXT, ooData BLOB);
+             INSERT INTO Main VALUES (1, 1700000000, 'hi', 0, 0), (2, 1700000100, 'pinned', 1700000100, 0), (3, 1, 'group', 0, 1);
+             INSERT INTO Data VALUES (1, 1, 'CF_UNICODETEXT', X'680069000000'), (2, 2, 'CF_TEXT', X'70696E6E656400');",
+        )
+        .unwrap();
+        drop(conn);
+        let items = read_ditto(&ditto).unwrap();
+        assert_eq!(contents(&items), vec!["pinned", "hi"]);
+        assert!(items[0].is_pinned);
+        assert_eq!(items[1].timestamp, Some(1_700_000_000_000));
+
+        let maccy = dir.path().join("Storage.sqlite");
+        let conn = Connection::open(&maccy).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE ZHISTORYITEM (Z_PK INTEGER PRIMARY KEY, ZLASTCOPIEDAT REAL, ZPIN TEXT, ZAPPLICATION TEXT);
+             CREATE TABLE ZHISTORYITEMCONTENT (Z_PK INTEGER PRIMARY KEY, ZITEM INTEGER, ZTYPE TEXT, ZVALUE BLOB);
+             INSERT INTO ZHISTORYITEM VALUES (1, 700000000.5, 'b', 'com.apple.Safari');
+             INSERT INTO ZHISTORYITEMCONTENT VALUES (1, 1, 'public.utf8-plain-text', CAST('copied' AS BLOB)),
+                                                    (2, 1, 'public.html', CAST('<b>copied</b>' AS BLOB));",
+        )
+        .unwrap();
+        drop(conn);
+        let items = read_maccy(&maccy).unwrap();
+        assert_eq!(contents(&items), vec!["copied"]);
+        assert_eq!(items[0].timestamp, Some(1_678_307_200_500));
+        assert_eq!(items[0].app_bundle_id.as_deref(), Some("com.apple.Safari"));
+        assert!(items[0].is_pinned);
+    }
+}
@@ -0,0 +1,394 @@
+// JSON Import Module - Validated import of CopyGum JSON exports
+// Parses the file against the schema of its ExportData.version and checks every
+// record (size limits, known categories, sane timestamps) before anything is
+// written. The whole import runs in one transaction; a dry run performs the same
+// work and rolls it back, so its summary is exactly what a real import would do.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::content_detector::detect_content_type;
+use crate::db::items;
+use crate::paths::database_path;
+
+/// ExportData versions this build can import
+pub const SUPPORTED_VERSIONS: &[&str] = &["1.0"];
+
+/// Refuse files larger than this before parsing
+pub(crate) const MAX_FILE_BYTES: u64 = 256 * 1024 * 1024;
+/// Largest text item accepted (1 MB)
+const MAX_CONTENT_BYTES: usize = 1024 * 1024;
+const MAX_APP_NAME_CHARS: usize = 256;
+const MAX_TAGS_PER_ITEM: usize = 32;
+const MAX_TAG_CHARS: usize = 64;
+/// Earliest accepted timestamp (2000-01-01); anything older is likely seconds, not ms
+const MIN_TIMESTAMP_MS: i64 = 946_684_800_000;
+/// Allow some clock skew between machines
+const MAX_FUTURE_MS: i64 = 24 * 60 * 60 * 1000;
+/// Only the first errors are reported back
+const MAX_REPORTED_ERRORS: usize = 100;
+
+const CONTENT_TYPES: &[&str] = &[
+    "password", "apikey", "color", "links", "email", "phone", "number", "code", "text",
+];
+
+/// Top level of a JSON export
+#[derive(Debug, Deserialize)]
+struct ImportFile {
+    version: String,
+    #[serde(default)]
+    items: Vec<serde_json::Value>,
+}
+
+/// One item of a version 1.0 export
+/// Older frontend exports stored tags as a comma-separated string.
+#[derive(Debug, Deserialize)]
+struct RecordV1 {
+    content: String,
+    #[serde(default)]
+    content_type: Option<String>,
+    category: String,
+    timestamp: i64,
+    #[serde(default)]
+    is_pinned: bool,
+    #[serde(default)]
+    is_image: bool,
+    #[serde(default)]
+    app_name: Option<String>,
+    #[serde(default)]
+    tags: Option<TagList>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TagList {
+    List(Vec<String>),
+    Joined(String),
+}
+
+impl TagList {
+    fn into_vec(self) -> Vec<String> {
+        let tags = match self {
+            TagList::List(tags) => tags,
+            TagList::Joined(joined) => joined.split(',').map(str::to_string).collect(),
+        };
+        tags.into_iter()
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+}
+
+/// A record that passed validation
+#[derive(Debug)]
+struct ValidRecord {
+    content: String,
+    content_type: String,
+    category: String,
+    timestamp: i64,
+    is_pinned: bool,
+    app_name: Option<String>,
+    tags: Vec<String>,
+}
+
+/// Why a record was rejected (index is 0-based within `items`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecordError {
+    pub index: usize,
+    pub reason: String,
+}
+
+/// What an import does (or, for a dry run, would do)
+#[derive(Debug, Default, Serialize)]
+pub struct ImportDiff {
+    pub version: String,
+    pub dry_run: bool,
+    /// Records in the file
+    pub total: usize,
+    pub added: usize,
+    /// Already in the history, or repeated in the file
+    pub duplicates: usize,
+    pub invalid: usize,
+    /// Items added per category
+    pub by_category: BTreeMap<String, usize>,
+    pub new_tags: Vec<String>,
+    pub errors: Vec<RecordError>,
+}
+
+impl ImportDiff {
+    fn reject(&mut self, index: usize, reason: impl Into<String>) {
+        self.invalid += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(RecordError { index, reason: reason.into() });
+        }
+    }
+}
+
+/// Parse and apply (or dry-run) a JSON export
+pub fn import_json(conn: &mut Connection, json: &str, dry_run: bool) -> Result<ImportDiff, String> {
+    let file: ImportFile = serde_json::from_str(json).map_err(|e| format!("Invalid export file: {}", e))?;
+    if !SUPPORTED_VERSIONS.contains(&file.version.as_str()) {
+        return Err(format!(
+            "Unsupported export version {} (supported: {})",
+            file.version,
+            SUPPORTED_VERSIONS.join(", ")
+        ));
+    }
+
+    let tx = conn.transaction().map_err(|e| format!("Failed to start import: {}", e))?;
+    let categories = known_categories(&tx)?;
+    let mut diff = ImportDiff {
+        version: file.version,
+        dry_run,
+        total: file.items.len(),
+        ..Default::default()
+    };
+    let now = items::now_millis();
+    let mut seen = HashSet::new();
+
+    for (index, value) in file.items.into_iter().enumerate() {
+        let record = match validate_record(value, &categories, now) {
+            Ok(record) => record,
+            Err(reason) => {
+                diff.reject(index, reason);
+                continue;
+            }
+        };
+
+        if !seen.insert(record.content.clone()) || content_exists(&tx, &record.content)? {
+            diff.duplicates += 1;
+            continue;
+        }
+        insert_record(&tx, &record, &mut diff)?;
+    }
+
+    // Dropping the transaction rolls a dry run back
+    if !dry_run {
+        tx.commit().map_err(|e| format!("Failed to commit import: {}", e))?;
+    }
+    Ok(diff)
+}
+
+fn validate_record(value: serde_json::Value, categories: &HashSet<String>, now: i64) -> Result<ValidRecord, String> {
+    let record: RecordV1 = serde_json::from_value(value).map_err(|e| format!("Malformed record: {}", e))?;
+
+    if record.is_image {
+        return Err("Image items can only be imported from .copygum archives".to_string());
+    }
+    if record.content.trim().is_empty() {
+        return Err("Empty content".to_string());
+    }
+    if record.content.len() > MAX_CONTENT_BYTES {
+        return Err(format!("Content is larger than {} bytes", MAX_CONTENT_BYTES));
+    }
+    if !categories.contains(&record.category) {
+        return Err(format!("Unknown category: {}", record.category));
+    }
+    if record.timestamp < MIN_TIMESTAMP_MS || record.timestamp > now + MAX_FUTURE_MS {
+        return Err(format!("Timestamp out of range: {}", record.timestamp));
+    }
+    if record.app_name.as_ref().is_some_and(|name| name.chars().count() > MAX_APP_NAME_CHARS) {
+        return Err("App name is too long".to_string());
+    }
+
+    let tags = record.tags.map(TagList::into_vec).unwrap_or_default();
+    if tags.len() > MAX_TAGS_PER_ITEM {
+        return Err(format!("More than {} tags", MAX_TAGS_PER_ITEM));
+    }
+    if let Some(tag) = tags.iter().find(|tag| tag.chars().count() > MAX_TAG_CHARS) {
+        return Err(format!("Tag name is too long: {}…", tag.chars().take(20).collect::<String>()));
+    }
+
+    // An unknown content type is re-detected rather than trusted
+    let content_type = match record.content_type {
+        Some(content_type) if CONTENT_TYPES.contains(&content_type.as_str()) => content_type,
+        _ => detect_content_type(&record.content).as_str().to_string(),
+    };
+
+    Ok(ValidRecord {
+        content: record.content,
+        content_type,
+        category: record.category,
+        timestamp: record.timestamp,
+        is_pinned: record.is_pinned,
+        app_name: record.app_name,
+        tags,
+    })
+}
+
+fn known_categories(tx: &Transaction) -> Result<HashSet<String>, String> {
+    let mut stmt = tx
+        .prepare("SELECT name FROM categories")
+        .map_err(|e| format!("Failed to read categories: {}", e))?;
+    let names = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to read categories: {}", e))?;
+    names
+        .collect::<rusqlite::Result<HashSet<String>>>()
+        .map_err(|e| format!("Failed to read categories: {}", e))
+}
+
+fn content_exists(tx: &Transaction, content: &str) -> Result<bool, String> {
+    tx.query_row(
+        "SELECT id FROM clipboard_items WHERE content = ?1 AND is_deleted = 0 LIMIT 1",
+        params![content],
+        |row| row.get::<_, i64>(0),
+    )
+    .optional()
+    .map(|id| id.is_some())
+    .map_err(|e| format!("Failed to check for duplicates: {}", e))
+}
+
+fn insert_record(tx: &Transaction, record: &ValidRecord, diff: &mut ImportDiff) -> Result<(), String> {
+    tx.execute(
+        "INSERT INTO clipboard_items (content, content_type, category, app_name, is_image, timestamp, is_pinned, is_deleted)
+         VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6, 0)",
+        params![
+            record.content,
+            record.content_type,
+            record.category,
+            record.app_name,
+            record.timestamp,
+            record.is_pinned,
+        ],
+    )
+    .map_err(|e| format!("Failed to import item: {}", e))?;
+    let item_id = tx.last_insert_rowid();
+
+    for tag in &record.tags {
+        let created = tx
+            .execute("INSERT OR IGNORE INTO tags (name, is_custom) VALUES (?1, 1)", params![tag])
+            .map_err(|e| format!("Failed to add tag {}: {}", tag, e))?;
+        if created > 0 {
+            diff.new_tags.push(tag.clone());
+        }
+        tx.execute(
+            "INSERT OR IGNORE INTO item_tags (item_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+            params![item_id, tag],
+        )
+        .map_err(|e| format!("Failed to tag item: {}", e))?;
+    }
+
+    diff.added += 1;
+    *diff.by_category.entry(record.category.clone()).or_default() += 1;
+    Ok(())
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
+/// Import a CopyGum JSON export, decrypting it if needed; with dry_run nothing is written
+#[tauri::command]
+pub async fn import_json_file(
+    app: AppHandle,
+    file_path: String,
+    dry_run: bool,
+    passphrase: Option<String>,
+) -> Result<ImportDiff, String> {
+    let diff = tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&file_path);
+        let size = std::fs::metadata(path)
+            .map_err(|e| format!("Failed to read import file: {}", e))?
+            .len();
+        if size > MAX_FILE_BYTES {
+            return Err(format!("Import file is larger than {} MB", MAX_FILE_BYTES / 1024 / 1024));
+        }
+        let json = crate::export::read_import_text(path, passphrase.as_deref())?;
+        let mut conn = items::open_read_write(&database_path()?)?;
+        import_json(&mut conn, &json, dry_run)
+    })
+    .await
+    .map_err(|e| format!("Import failed: {}", e))??;
+
+    if !diff.dry_run {
+        println!(
+            "[CopyGum] Imported {} items ({} duplicates, {} invalid)",
+            diff.added, diff.duplicates, diff.invalid
+        );
+        let _ = app.emit("items-changed", ());
+    }
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export_json(items: serde_json::Value) -> String {
+        serde_json::json!({
+            "version": "1.0",
+            "export_date": "2024-05-01T00:00:00Z",
+            "item_count": 0,
+            "items": items
+        })
+        .to_string()
+    }
+
+    fn count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM clipboard_items", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_dry_run_reports_without_writing() {
+        let mut conn = items::test_connection();
+        items::add_text_item(&conn, "existing", "text", "text", None).unwrap();
+        let json = export_json(serde_json::json!([
+            { "content": "fn main() {}", "content_type": "code", "category": "code", "timestamp": 1_714_521_600_000i64, "tags": ["Rust"] },
+            { "content": "existing", "category": "text", "timestamp": 1_714_521_600_000i64 },
+            { "content": "fn main() {}", "category": "code", "timestamp": 1_714_521_600_000i64 },
+            { "content": "x", "category": "nonsense", "timestamp": 1_714_521_600_000i64 },
+            { "content": "seconds", "category": "text", "timestamp": 1_714_521_600 },
+            { "content": "shot.png", "category": "image", "is_image": true, "timestamp": 1_714_521_600_000i64 },
+            { "category": "text" }
+        ]));
+
+        let diff = import_json(&mut conn, &json, true).unwrap();
+        assert_eq!((diff.total, diff.added, diff.duplicates, diff.invalid), (7, 1, 2, 4));
+        assert_eq!(diff.by_category.get("code"), Some(&1));
+        assert_eq!(diff.new_tags, vec!["Rust"]);
+        assert_eq!(diff.errors.iter().map(|e| e.index).collect::<Vec<_>>(), vec![3, 4, 5, 6]);
+        assert_eq!(count(&conn), 1);
+
+        let applied = import_json(&mut conn, &json, false).unwrap();
+        assert_eq!(applied.added, 1);
+        assert_eq!(count(&conn), 2);
+        let tags: String = conn
+            .query_row("SELECT tag_names FROM clipboard_items_with_tags WHERE category = 'code'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tags, "Rust");
+    }
+
+    #[test]
+    fn test_rejects_unknown_versions_and_oversized_content() {
+        let mut conn = items::test_connection();
+        let err = import_json(&mut conn, r#"{"version":"9.0","items":[]}"#, true).unwrap_err();
+        assert!(err.contains("Unsupported export version 9.0"));
+        assert!(import_json(&mut conn, "not json", true).is_err());
+
+        let json = export_json(serde_json::json!([
+            { "content": "a".repeat(MAX_CONTENT_BYTES + 1), "category": "text", "timestamp": 1_714_521_600_000i64 }
+        ]));
+        assert_eq!(import_json(&mut conn, &json, false).unwrap().invalid, 1);
+    }
+
+    #[test]
+    fn test_legacy_comma_separated_tags_and_unknown_content_type() {
+        let mut conn = items::test_connection();
+        let json = export_json(serde_json::json!([
+            { "content": "https://example.com", "content_type": "<script>", "category": "links",
+              "timestamp": 1_714_521_600_000i64, "tags": "Work, Later" }
+        ]));
+        let diff = import_json(&mut conn, &json, false).unwrap();
+        assert_eq!(diff.added, 1);
+        assert_eq!(diff.new_tags, vec!["Later"]);
+        let content_type: String = conn
+            .query_row("SELECT content_type FROM clipboard_items", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(content_type, "links");
+    }
+}
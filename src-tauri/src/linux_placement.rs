@@ -0,0 +1,264 @@
+// Linux Placement Module - Work area and layer-shell support for the overlay
+// X11: reads panel struts, _NET_WORKAREA and _GTK_FRAME_EXTENTS so the overlay
+// sits above GNOME/KDE panels and docks.
+// Wayland: uses gtk-layer-shell (loaded at runtime, if installed) so the
+// compositor anchors the overlay to a screen edge clear of exclusive zones.
+
+use std::sync::OnceLock;
+
+use libloading::Library;
+use tauri::WebviewWindow;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, Window};
+use x11rb::rust_connection::RustConnection;
+
+use crate::geometry::{work_area_from_struts, Edge, FrameExtents, Rect, Strut};
+
+/// Whether we are running in a Wayland session (X11 calls would only reach XWayland)
+pub fn is_wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_SESSION_TYPE").map(|t| t == "wayland").unwrap_or(false)
+}
+
+// ============================================
+// X11
+// ============================================
+
+pub(crate) fn intern_atom(conn: &RustConnection, name: &[u8]) -> Option<u32> {
+    let atom = conn.intern_atom(true, name).ok()?.reply().ok()?.atom;
+    (atom != 0).then_some(atom)
+}
+
+pub(crate) fn read_cardinals(conn: &RustConnection, window: Window, property: u32, kind: AtomEnum) -> Option<Vec<u32>> {
+    let reply = conn
+        .get_property(false, window, property, kind, 0, 4096)
+        .ok()?
+        .reply()
+        .ok()?;
+    let values = reply.value32()?.collect();
+    Some(values)
+}
+
+/// Work area of the monitor with the given bounds on X11
+///
+/// Prefers per-panel struts so multi-monitor setups only lose space on the
+/// monitor a panel is actually on; falls back to the screen-wide
+/// `_NET_WORKAREA` for the current desktop.
+pub fn work_area(monitor: &Rect) -> Option<Rect> {
+    if is_wayland() {
+        return None;
+    }
+
+    let (conn, screen_num) = x11rb::connect(None).ok()?;
+    let screen = conn.setup().roots.get(screen_num)?;
+    let root = screen.root;
+    let screen_rect = Rect::new(0, 0, screen.width_in_pixels as u32, screen.height_in_pixels as u32);
+
+    // Struts advertised by panels and docks
+    let partial_atom = intern_atom(&conn, b"_NET_WM_STRUT_PARTIAL");
+    let legacy_atom = intern_atom(&conn, b"_NET_WM_STRUT");
+    let struts: Vec<Strut> = intern_atom(&conn, b"_NET_CLIENT_LIST")
+        .and_then(|atom| read_cardinals(&conn, root, atom, AtomEnum::WINDOW))
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|client| {
+            let partial = partial_atom
+                .and_then(|atom| read_cardinals(&conn, client, atom, AtomEnum::CARDINAL))
+                .filter(|values| values.len() == 12);
+            let values = partial.or_else(|| {
+                legacy_atom.and_then(|atom| read_cardinals(&conn, client, atom, AtomEnum::CARDINAL))
+            })?;
+            Strut::from_cardinals(&values, &screen_rect)
+        })
+        .collect();
+
+    if !struts.is_empty() {
+        return Some(work_area_from_struts(monitor, &screen_rect, &struts));
+    }
+
+    // No struts: use the EWMH work area of the current desktop
+    let workarea = intern_atom(&conn, b"_NET_WORKAREA")
+        .and_then(|atom| read_cardinals(&conn, root, atom, AtomEnum::CARDINAL))?;
+    let desktop = intern_atom(&conn, b"_NET_CURRENT_DESKTOP")
+        .and_then(|atom| read_cardinals(&conn, root, atom, AtomEnum::CARDINAL))
+        .and_then(|v| v.first().copied())
+        .unwrap_or(0) as usize;
+
+    let values = workarea.chunks_exact(4).nth(desktop).or_else(|| workarea.chunks_exact(4).next())?;
+    let area = Rect::new(values[0] as i32, values[1] as i32, values[2], values[3]).intersect(monitor);
+
+    (!area.is_empty()).then_some(area)
+}
+
+/// `_GTK_FRAME_EXTENTS` of our own window, if GTK draws a shadow around it
+pub fn frame_extents(window: &WebviewWindow) -> Option<FrameExtents> {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+    if is_wayland() {
+        return None;
+    }
+
+    // Only available once the window has been realized (after the first show)
+    let xid = match window.window_handle().ok()?.as_raw() {
+        RawWindowHandle::Xlib(handle) => handle.window as Window,
+        RawWindowHandle::Xcb(handle) => handle.window.get(),
+        _ => return None,
+    };
+
+    let (conn, _) = x11rb::connect(None).ok()?;
+    let atom = intern_atom(&conn, b"_GTK_FRAME_EXTENTS")?;
+    let values = read_cardinals(&conn, xid, atom, AtomEnum::CARDINAL)?;
+
+    match values.as_slice() {
+        [left, right, top, bottom] => Some(FrameExtents {
+            left: *left,
+            right: *right,
+            top: *top,
+            bottom: *bottom,
+        }),
+        _ => None,
+    }
+}
+
+// ============================================
+// WAYLAND LAYER SHELL
+// ============================================
+
+type GtkWindowPtr = *mut gtk::ffi::GtkWindow;
+
+// Enum values from gtk-layer-shell.h
+const LAYER_OVERLAY: i32 = 3;
+const EDGE_LEFT: i32 = 0;
+const EDGE_RIGHT: i32 = 1;
+const EDGE_TOP: i32 = 2;
+const EDGE_BOTTOM: i32 = 3;
+const KEYBOARD_MODE_ON_DEMAND: i32 = 2;
+
+/// Entry points of libgtk-layer-shell, resolved once at runtime
+struct LayerShell {
+    // Keeps the library mapped for as long as the function pointers live
+    _library: Library,
+    is_supported: unsafe extern "C" fn() -> i32,
+    init_for_window: unsafe extern "C" fn(GtkWindowPtr),
+    is_layer_window: unsafe extern "C" fn(GtkWindowPtr) -> i32,
+    set_namespace: unsafe extern "C" fn(GtkWindowPtr, *const std::ffi::c_char),
+    set_layer: unsafe extern "C" fn(GtkWindowPtr, i32),
+    set_anchor: unsafe extern "C" fn(GtkWindowPtr, i32, i32),
+    set_exclusive_zone: unsafe extern "C" fn(GtkWindowPtr, i32),
+    set_keyboard_mode: unsafe extern "C" fn(GtkWindowPtr, i32),
+}
+
+// The function pointers are plain C symbols; GTK calls themselves only
+// happen on the main thread
+unsafe impl Send for LayerShell {}
+unsafe impl Sync for LayerShell {}
+
+static LAYER_SHELL: OnceLock<Option<LayerShell>> = OnceLock::new();
+
+impl LayerShell {
+    fn load() -> Option<LayerShell> {
+        unsafe {
+            let library = Library::new("libgtk-layer-shell.so.0").ok()?;
+            let shell = LayerShell {
+                is_supported: *library.get(b"gtk_layer_is_supported\0").ok()?,
+                init_for_window: *library.get(b"gtk_layer_init_for_window\0").ok()?,
+                is_layer_window: *library.get(b"gtk_layer_is_layer_window\0").ok()?,
+                set_namespace: *library.get(b"gtk_layer_set_namespace\0").ok()?,
+                set_layer: *library.get(b"gtk_layer_set_layer\0").ok()?,
+                set_anchor: *library.get(b"gtk_layer_set_anchor\0").ok()?,
+                set_exclusive_zone: *library.get(b"gtk_layer_set_exclusive_zone\0").ok()?,
+                set_keyboard_mode: *library.get(b"gtk_layer_set_keyboard_mode\0").ok()?,
+                _library: library,
+            };
+            ((shell.is_supported)() != 0).then_some(shell)
+        }
+    }
+
+    fn get() -> Option<&'static LayerShell> {
+        LAYER_SHELL.get_or_init(LayerShell::load).as_ref()
+    }
+}
+
+fn gtk_window_ptr(window: &WebviewWindow) -> Option<GtkWindowPtr> {
+    use gtk::glib::translate::ToGlibPtr;
+    use gtk::prelude::*;
+
+    let app_window = window.gtk_window().ok()?;
+    let gtk_window: &gtk::Window = app_window.upcast_ref();
+    let ptr: GtkWindowPtr = gtk_window.to_glib_none().0;
+    Some(ptr)
+}
+
+/// Turn the overlay into a layer-shell surface on Wayland
+///
+/// Must run before the window is shown for the first time. Returns false
+/// on X11, when gtk-layer-shell isn't installed, or when the compositor
+/// doesn't support the protocol; the overlay then stays a normal window.
+pub fn init_layer_shell(window: &WebviewWindow) -> bool {
+    use gtk::prelude::*;
+
+    if !is_wayland() {
+        return false;
+    }
+
+    let Some(shell) = LayerShell::get() else {
+        println!("[CopyGum] gtk-layer-shell not available, overlay stays a regular window");
+        return false;
+    };
+
+    let Ok(app_window) = window.gtk_window() else {
+        return false;
+    };
+    if app_window.is_realized() {
+        println!("[CopyGum] Window already realized, can't enable layer shell");
+        return false;
+    }
+
+    let Some(ptr) = gtk_window_ptr(window) else {
+        return false;
+    };
+
+    unsafe {
+        (shell.init_for_window)(ptr);
+        (shell.set_namespace)(ptr, c"copygum".as_ptr());
+        (shell.set_layer)(ptr, LAYER_OVERLAY);
+        // Zero exclusive zone: don't push other windows away, but stay
+        // clear of panels that reserve space
+        (shell.set_exclusive_zone)(ptr, 0);
+        (shell.set_keyboard_mode)(ptr, KEYBOARD_MODE_ON_DEMAND);
+    }
+
+    println!("[CopyGum] Overlay running as a layer-shell surface");
+    true
+}
+
+/// Anchor a layer-shell overlay to the given edge, spanning its full length
+///
+/// Returns false if the window isn't a layer-shell surface, in which case
+/// the caller positions it like any other window.
+pub fn anchor_layer_surface(window: &WebviewWindow, edge: Edge) -> bool {
+    let Some(shell) = LayerShell::get() else {
+        return false;
+    };
+    let Some(ptr) = gtk_window_ptr(window) else {
+        return false;
+    };
+
+    unsafe {
+        if (shell.is_layer_window)(ptr) == 0 {
+            return false;
+        }
+
+        let anchors = match edge {
+            Edge::Bottom => [(EDGE_BOTTOM, 1), (EDGE_TOP, 0), (EDGE_LEFT, 1), (EDGE_RIGHT, 1)],
+            Edge::Top => [(EDGE_TOP, 1), (EDGE_BOTTOM, 0), (EDGE_LEFT, 1), (EDGE_RIGHT, 1)],
+            Edge::Left => [(EDGE_LEFT, 1), (EDGE_RIGHT, 0), (EDGE_TOP, 1), (EDGE_BOTTOM, 1)],
+            Edge::Right => [(EDGE_RIGHT, 1), (EDGE_LEFT, 0), (EDGE_TOP, 1), (EDGE_BOTTOM, 1)],
+        };
+        for (side, anchored) in anchors {
+            (shell.set_anchor)(ptr, side, anchored);
+        }
+    }
+
+    true
+}
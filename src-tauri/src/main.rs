@@ -3,16 +3,57 @@
 
 mod db;
 mod window_manager;
+mod geometry;
+#[cfg(target_os = "linux")]
+mod linux_placement;
 mod clipboard_monitor;
+mod clipboard_formats;
 mod content_detector;
 mod image_handler;
+mod image_metadata;
+mod palette;
+mod barcode;
+mod similar_images;
 mod settings;
 mod export;
+mod archive;
+mod importers;
+mod json_import;
+mod encryption;
+mod backup;
+mod search;
+mod fuzzy;
+mod query;
+mod ocr;
 mod app_detector;
 mod app_icons;
 mod updater;
+mod tray;
+mod autostart;
+mod single_instance;
+mod paths;
+mod cli;
+#[cfg(unix)]
+mod rpc_server;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // `copygum list`, `copygum copy 42`, ... run without starting the app
+    if cli::is_cli_invocation(&args) {
+        std::process::exit(cli::run(&args));
+    }
+
+    // A second launch hands its arguments to the running instance and exits
+    let instance_lock = match single_instance::acquire(&args) {
+        Ok(Some(lock)) => Some(lock),
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("Failed to set up single-instance lock: {}", e);
+            None
+        }
+    };
+
     tauri::Builder::default()
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(
@@ -40,22 +81,56 @@ fn main() {
             settings::get_settings,
             settings::save_settings,
             settings::reset_settings,
+            autostart::get_launch_at_startup,
             export::save_export_file,
             export::read_import_file,
             export::get_export_stats,
+            export::export_history,
+            archive::export_archive,
+            archive::import_archive_file,
+            importers::get_import_default_path,
+            importers::import_from_app,
+            json_import::import_json_file,
+            encryption::is_encrypted_export,
+            backup::create_backup_now,
+            backup::list_backup_snapshots,
+            backup::restore_backup,
+            search::search_items,
+            search::rebuild_search_index,
+            ocr::ocr_available,
+            ocr::copy_text_from_image,
             app_icons::get_app_icon_data,
             image_handler::get_image_base64,
+            similar_images::find_similar_images,
             updater::check_for_update,
             updater::install_update,
             updater::get_current_version,
         ])
         .manage(clipboard_monitor::ClipboardMonitor::new())
-        .setup(|app| {
+        .on_window_event(|window, event| {
+            // Closing the window keeps CopyGum running in the tray
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                use tauri::Manager;
+                let minimize_to_tray = settings::AppSettings::load(window.app_handle())
+                    .map(|s| s.minimize_to_tray)
+                    .unwrap_or(true);
+                if minimize_to_tray {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
+        .setup(move |app| {
             // Setup global shortcut for Cmd+Shift+V
             if let Err(e) = window_manager::setup_global_shortcut(&app.handle()) {
                 eprintln!("Failed to setup global shortcut: {}", e);
             }
 
+            // System tray with monitoring state and recent items
+            if let Err(e) = tray::setup_tray(app.handle()) {
+                eprintln!("Failed to setup tray: {}", e);
+            }
+
             // Configure window for overlay behavior on macOS
             #[cfg(target_os = "macos")]
             {
@@ -94,6 +169,43 @@ fn main() {
                 }
             }
 
+            // On Wayland, turn the overlay into a layer-shell surface so the
+            // compositor docks it above panels (must happen before the first show)
+            #[cfg(target_os = "linux")]
+            {
+                use tauri::Manager;
+                if let Some(window) = app.get_webview_window("main") {
+                    linux_placement::init_layer_shell(&window);
+                }
+            }
+
+            // Listen for arguments from later launches (--toggle, --pause, ...)
+            if let Some(lock) = instance_lock {
+                single_instance::listen(app.handle().clone(), lock);
+            }
+            single_instance::handle_startup_args(app.handle(), &args);
+
+            // JSON-RPC socket for editors, launchers and scripts
+            #[cfg(unix)]
+            if let Err(e) = rpc_server::start(app.handle().clone()) {
+                eprintln!("Failed to start RPC server: {}", e);
+            }
+
+            // Daily or weekly snapshots of the history, if enabled
+            backup::start_scheduler();
+
+            // Text recognition for captured images, off the capture path
+            ocr::start_worker(app.handle().clone());
+
+            // Launched at login: stay hidden in the tray regardless of show_on_startup
+            if autostart::launched_at_login() {
+                println!("[CopyGum] Launched at login, starting hidden in tray");
+            } else if settings::AppSettings::load(app.handle()).map(|s| s.show_on_startup).unwrap_or(false) {
+                if let Err(e) = window_manager::show_window_activated(app.handle().clone()) {
+                    eprintln!("Failed to show window on startup: {}", e);
+                }
+            }
+
             Ok(())
         })
         .run(tauri::generate_context!())
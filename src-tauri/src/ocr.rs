@@ -0,0 +1,347 @@
+// OCR Module - Recognizes text in captured images
+// Screenshots are otherwise stored as opaque pictures. With OCR enabled:
+// - the clipboard monitor queues each saved image; queueing never blocks,
+//   so capture latency is unchanged
+// - one background worker runs Tesseract (offline) on the queue
+// - the text goes into search_text, which the search index covers
+// - copy_text_from_image puts an image's text on the clipboard, recognizing
+//   it on demand for images captured before OCR was enabled
+// Tesseract is linked only when built with the `ocr` cargo feature (it needs
+// libtesseract and language data). Without it, or without language data, the
+// worker doesn't start, nothing is queued and ocr_available tells the UI to
+// hide the OCR controls.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+use std::time::Duration;
+
+use once_cell::sync::OnceCell;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::db::items;
+use crate::paths::{database_path, settings_path};
+use crate::settings::AppSettings;
+
+/// Images waiting for OCR; captures beyond this are skipped rather than waited on
+const QUEUE_CAPACITY: usize = 64;
+/// The frontend inserts the row after the capture event, so the worker can
+/// finish first; it waits up to STORE_ATTEMPTS * STORE_RETRY_DELAY for it
+const STORE_ATTEMPTS: u32 = 20;
+const STORE_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Output with fewer letters or digits than this is noise (borders, icons)
+const MIN_TEXT_CHARS: usize = 3;
+/// Clipboard images carry no DPI; screenshots are close to this
+#[cfg(feature = "ocr")]
+const SCREEN_PPI: i32 = 96;
+
+static QUEUE: OnceCell<SyncSender<PathBuf>> = OnceCell::new();
+/// Checked once at startup; installing language data takes a restart
+static AVAILABLE: OnceCell<bool> = OnceCell::new();
+
+// ============================================
+// RECOGNITION
+// ============================================
+
+/// Language data: `tessdata` in the app data folder if present, else
+/// Tesseract's own (TESSDATA_PREFIX or the system install)
+#[cfg(feature = "ocr")]
+fn tessdata_dir() -> Option<PathBuf> {
+    crate::paths::app_data_dir()
+        .ok()
+        .map(|dir| dir.join("tessdata"))
+        .filter(|dir| dir.is_dir())
+}
+
+/// Raw Tesseract output for an image file
+#[cfg(feature = "ocr")]
+fn recognize(image_path: &Path, language: &str) -> Result<String, String> {
+    let path = image_path
+        .to_str()
+        .ok_or_else(|| format!("Unsupported image path: {}", image_path.display()))?;
+    let datapath = tessdata_dir();
+    let datapath = datapath.as_deref().and_then(Path::to_str);
+    tesseract::Tesseract::new(datapath, Some(language))
+        .map_err(|e| format!("Failed to start OCR engine: {}", e))?
+        .set_image(path)
+        .map_err(|e| format!("Failed to load image for OCR: {}", e))?
+        .set_source_resolution(SCREEN_PPI)
+        .recognize()
+        .map_err(|e| format!("Failed to recognize text: {}", e))?
+        .get_text()
+        .map_err(|e| format!("Failed to read recognized text: {}", e))
+}
+
+#[cfg(not(feature = "ocr"))]
+fn recognize(_image_path: &Path, _language: &str) -> Result<String, String> {
+    Err("Text recognition is not available in this build".to_string())
+}
+
+/// Whether Tesseract starts with its default language data
+#[cfg(feature = "ocr")]
+fn engine_starts() -> bool {
+    let datapath = tessdata_dir();
+    let datapath = datapath.as_deref().and_then(Path::to_str);
+    tesseract::Tesseract::new(datapath, None).is_ok()
+}
+
+#[cfg(not(feature = "ocr"))]
+fn engine_starts() -> bool {
+    false
+}
+
+/// Whether text recognition works in this build and install
+pub fn is_available() -> bool {
+    *AVAILABLE.get_or_init(engine_starts)
+}
+
+/// Tidy OCR output: trim lines, collapse runs of spaces, drop blank lines
+pub fn normalize_text(raw: &str) -> String {
+    raw.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether normalized output is worth keeping
+fn has_text(text: &str) -> bool {
+    text.chars().filter(|c| c.is_alphanumeric()).count() >= MIN_TEXT_CHARS
+}
+
+/// Recognized, normalized text of an image; None if it has none
+fn image_text(image_path: &Path) -> Result<Option<String>, String> {
+    let settings = AppSettings::load_from(&settings_path()?)?;
+    let text = normalize_text(&recognize(image_path, &settings.ocr_language)?);
+    Ok(has_text(&text).then_some(text))
+}
+
+// ============================================
+// STORAGE
+// ============================================
+
+/// Save text for the item(s) holding this image; returns the rows updated
+/// Text decoded from a QR code or barcode (barcode.rs) stays searchable
+/// after the recognized text, unless it is sensitive.
+pub fn store_text(conn: &Connection, image_path: &str, text: &str) -> Result<usize, String> {
+    let sensitive: Vec<String> = (0..items::SENSITIVE_CATEGORIES.len())
+        .map(|i| format!("?{}", i + 3))
+        .collect();
+    let sql = format!(
+        "UPDATE clipboard_items
+         SET search_text = CASE
+             WHEN decoded_text IS NULL OR decoded_type IN ({}) THEN ?1
+             ELSE ?1 || char(10) || decoded_text
+         END
+         WHERE image_path = ?2 AND is_image = 1",
+        sensitive.join(", ")
+    );
+    let mut values: Vec<&str> = vec![text, image_path];
+    values.extend(items::SENSITIVE_CATEGORIES);
+    conn.execute(&sql, params_from_iter(values))
+        .map_err(|e| format!("Failed to save recognized text: {}", e))
+}
+
+/// store_text, retrying while the row hasn't been inserted yet
+fn store_text_when_saved(
+    conn: &Connection,
+    image_path: &str,
+    text: &str,
+    attempts: u32,
+    delay: Duration,
+) -> Result<bool, String> {
+    for attempt in 0..attempts {
+        if attempt > 0 {
+            thread::sleep(delay);
+        }
+        if store_text(conn, image_path, text)? > 0 {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// ============================================
+// WORKER
+// ============================================
+
+/// Start the background worker (once, at app startup), if OCR is available
+pub fn start_worker(app: AppHandle) {
+    if !is_available() {
+        println!("[CopyGum] Text recognition is not available, images won't be queued for OCR");
+        return;
+    }
+    let (sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+    if QUEUE.set(sender).is_err() {
+        return;
+    }
+    let spawned = thread::Builder::new()
+        .name("ocr-worker".to_string())
+        .spawn(move || run_worker(app, receiver));
+    if let Err(e) = spawned {
+        println!("[CopyGum] Failed to start OCR worker: {}", e);
+    }
+}
+
+/// Queue a saved image for OCR; returns immediately (no-op without a worker)
+pub fn enqueue(image_path: &str) {
+    let Some(sender) = QUEUE.get() else {
+        return;
+    };
+    match sender.try_send(PathBuf::from(image_path)) {
+        Ok(()) => {}
+        Err(TrySendError::Full(path)) => {
+            println!("[CopyGum] OCR queue full, skipping {}", path.display());
+        }
+        Err(TrySendError::Disconnected(_)) => {}
+    }
+}
+
+fn run_worker(app: AppHandle, receiver: Receiver<PathBuf>) {
+    for image_path in receiver {
+        match process_image(&image_path) {
+            Ok(true) => {
+                let _ = app.emit("items-changed", ());
+            }
+            Ok(false) => {}
+            Err(e) => println!("[CopyGum] OCR failed for {}: {}", image_path.display(), e),
+        }
+    }
+}
+
+/// Recognize one queued image and store its text; true if anything was stored
+fn process_image(image_path: &Path) -> Result<bool, String> {
+    let Some(text) = image_text(image_path)? else {
+        return Ok(false);
+    };
+    let conn = items::open_read_write(&database_path()?)?;
+    let path = image_path.to_string_lossy();
+    let stored = store_text_when_saved(&conn, &path, &text, STORE_ATTEMPTS, STORE_RETRY_DELAY)?;
+    if !stored {
+        println!("[CopyGum] OCR text for {} not saved: image is no longer in the history", path);
+    }
+    Ok(stored)
+}
+
+/// Text of an image item: the stored OCR result, or a fresh recognition
+fn item_text(id: i64) -> Result<String, String> {
+    let conn = items::open_read_write(&database_path()?)?;
+    let (image_path, search_text, decoded_text): (Option<String>, Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT image_path, search_text, decoded_text FROM clipboard_items
+             WHERE id = ?1 AND is_image = 1 AND is_deleted = 0",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get item {}: {}", id, e))?
+        .ok_or_else(|| format!("Image {} not found", id))?;
+
+    // Recognized before, unless search_text only holds the decoded code
+    if let Some(text) = search_text.filter(|text| !text.is_empty() && Some(text) != decoded_text.as_ref()) {
+        return Ok(text);
+    }
+    let image_path = image_path.ok_or_else(|| format!("Image {} has no file", id))?;
+    let text = image_text(Path::new(&image_path))?.ok_or_else(|| "No text found in this image".to_string())?;
+    store_text(&conn, &image_path, &text)?;
+    Ok(text)
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
+/// Whether to show the OCR setting and the copy-text button
+#[tauri::command]
+pub fn ocr_available() -> bool {
+    is_available()
+}
+
+/// Copy the text recognized in an image item to the clipboard and return it
+#[tauri::command]
+pub async fn copy_text_from_image(app: AppHandle, id: i64) -> Result<String, String> {
+    let text = tauri::async_runtime::spawn_blocking(move || item_text(id))
+        .await
+        .map_err(|e| format!("Text recognition failed: {}", e))??;
+    app.clipboard()
+        .write_text(text.clone())
+        .map_err(|e| format!("Failed to write clipboard: {}", e))?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_image(conn: &Connection, image_path: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO clipboard_items (content, content_type, category, is_image, image_path, timestamp)
+             VALUES ('', 'image', 'image', 1, ?1, 1000)",
+            params![image_path],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    #[test]
+    fn test_normalize_text() {
+        let raw = "  Quarterly   report \n\n\n  Total:\t$1,200  \n \x0c";
+        assert_eq!(normalize_text(raw), "Quarterly report\nTotal: $1,200");
+        assert!(has_text(&normalize_text("Ok 1")));
+        // Tesseract reads borders and icons as stray punctuation
+        assert!(!has_text(&normalize_text(" | — \n ~ ")));
+    }
+
+    #[test]
+    fn test_stored_text_is_searchable() {
+        let conn = items::test_connection();
+        let id = insert_image(&conn, "/images/1_1000.png");
+        insert_image(&conn, "/images/2_1000.png");
+
+        assert_eq!(store_text(&conn, "/images/1_1000.png", "Invoice 2041\nTotal due").unwrap(), 1);
+        let query = crate::search::SearchQuery { query: "invoice".to_string(), ..Default::default() };
+        let page = crate::search::search(&conn, &query).unwrap();
+        assert_eq!(page.hits.len(), 1);
+        assert_eq!(page.hits[0].item.id, id);
+    }
+
+    #[test]
+    fn test_decoded_text_is_kept_unless_sensitive() {
+        let conn = items::test_connection();
+        insert_image(&conn, "/images/4_1000.png");
+        insert_image(&conn, "/images/5_1000.png");
+        conn.execute_batch(
+            "UPDATE clipboard_items SET decoded_text = 'https://example.com', decoded_type = 'links'
+             WHERE image_path = '/images/4_1000.png';
+             UPDATE clipboard_items SET decoded_text = 'otpauth://totp/me?secret=ABC', decoded_type = 'apikey'
+             WHERE image_path = '/images/5_1000.png';",
+        )
+        .unwrap();
+
+        // Storing twice doesn't repeat the decoded text
+        for _ in 0..2 {
+            store_text(&conn, "/images/4_1000.png", "Scan to join").unwrap();
+            store_text(&conn, "/images/5_1000.png", "Scan to enroll").unwrap();
+        }
+        let texts: Vec<String> = conn
+            .prepare("SELECT search_text FROM clipboard_items ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(texts, vec!["Scan to join\nhttps://example.com", "Scan to enroll"]);
+    }
+
+    #[test]
+    fn test_store_waits_for_the_row() {
+        let conn = items::test_connection();
+        let missing = store_text_when_saved(&conn, "/images/3_1000.png", "hello", 3, Duration::ZERO).unwrap();
+        assert!(!missing);
+
+        insert_image(&conn, "/images/3_1000.png");
+        assert!(store_text_when_saved(&conn, "/images/3_1000.png", "hello", 3, Duration::ZERO).unwrap());
+    }
+}
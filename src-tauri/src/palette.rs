@@ -0,0 +1,389 @@
+// Palette Module - Color palettes of captured images
+// Averaging every pixel of a red and blue image gives purple, a color that
+// isn't in the image. Instead:
+// - opaque pixels are sampled (transparent ones are ignored)
+// - median cut splits them into up to MAX_COLORS groups of similar colors
+// - a few k-means rounds in Lab space settle the groups and their shares
+// - the overall average is taken in Lab, where equal steps look equal
+// The palette is stored as JSON in clipboard_items.image_palette; its most
+// common color is the image's dominant color.
+
+use std::cmp::Reverse;
+
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+/// Colors in a palette, at most
+const MAX_COLORS: usize = 5;
+/// Pixels sampled per image, at most
+const MAX_SAMPLES: u64 = 16_384;
+/// Pixels less opaque than this are ignored
+const MIN_ALPHA: u8 = 128;
+const KMEANS_ROUNDS: usize = 4;
+/// Colors closer than this (CIE76 delta E) count as one color
+const MERGE_DISTANCE: f64 = 8.0;
+/// Colors covering less of the image than this are left out
+const MIN_PROPORTION: f64 = 0.01;
+
+/// A palette color and the share of the (opaque) image it covers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Swatch {
+    pub color: String,
+    pub proportion: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorPalette {
+    /// Most common first; never empty
+    pub colors: Vec<Swatch>,
+    /// Average of all opaque pixels, computed in Lab
+    pub average: String,
+}
+
+impl ColorPalette {
+    /// The most common color
+    pub fn dominant(&self) -> &str {
+        &self.colors[0].color
+    }
+}
+
+type Rgb = [u8; 3];
+type Lab = [f64; 3];
+
+/// Palette of an image; None if it has no opaque pixels
+pub fn extract_palette(img: &DynamicImage) -> Option<ColorPalette> {
+    let samples = opaque_samples(img);
+    if samples.is_empty() {
+        return None;
+    }
+    let labs: Vec<Lab> = samples.iter().map(|&rgb| to_lab(rgb)).collect();
+
+    let centers = median_cut(samples.clone(), MAX_COLORS)
+        .iter()
+        .map(|group| mean_lab(group.iter().map(|&rgb| to_lab(rgb))))
+        .collect();
+    let clusters = merge_close(kmeans(&samples, &labs, centers));
+
+    let total = samples.len() as f64;
+    let colors = clusters
+        .iter()
+        .filter(|cluster| cluster.count as f64 / total >= MIN_PROPORTION)
+        .map(|cluster| Swatch {
+            color: to_hex(cluster.mean_rgb()),
+            proportion: (cluster.count as f64 / total) as f32,
+        })
+        .collect();
+
+    Some(ColorPalette {
+        colors,
+        average: to_hex(from_lab(mean_lab(labs.iter().copied()))),
+    })
+}
+
+/// Colors of opaque pixels, on a grid of at most MAX_SAMPLES points
+fn opaque_samples(img: &DynamicImage) -> Vec<Rgb> {
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let pixels = width as u64 * height as u64;
+    let step = ((pixels as f64 / MAX_SAMPLES as f64).sqrt().ceil() as usize).max(1);
+
+    let mut samples = Vec::new();
+    for y in (0..height).step_by(step) {
+        for x in (0..width).step_by(step) {
+            let pixel = rgba.get_pixel(x, y);
+            if pixel[3] >= MIN_ALPHA {
+                samples.push([pixel[0], pixel[1], pixel[2]]);
+            }
+        }
+    }
+    samples
+}
+
+// ============================================
+// MEDIAN CUT
+// ============================================
+
+/// Split colors into up to `count` groups, always cutting the group with the
+/// widest channel range at its median
+fn median_cut(colors: Vec<Rgb>, count: usize) -> Vec<Vec<Rgb>> {
+    let mut groups = vec![colors];
+    while groups.len() < count {
+        let widest = groups
+            .iter()
+            .enumerate()
+            .map(|(index, group)| (index, widest_channel(group)))
+            .max_by_key(|(_, (_, range))| *range);
+        let Some((index, (channel, range))) = widest else {
+            break;
+        };
+        if range == 0 {
+            break;
+        }
+        let mut group = groups.swap_remove(index);
+        group.sort_unstable_by_key(|rgb| rgb[channel]);
+        let upper = group.split_off(split_index(&group, channel));
+        groups.push(group);
+        groups.push(upper);
+    }
+    groups
+}
+
+/// Channel with the largest spread, and that spread
+fn widest_channel(colors: &[Rgb]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let min = colors.iter().map(|rgb| rgb[channel]).min().unwrap_or(0);
+            let max = colors.iter().map(|rgb| rgb[channel]).max().unwrap_or(0);
+            (channel, max - min)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+/// The median of colors sorted on `channel`, moved to the nearest change of
+/// value so equal colors stay in one group (needs a non-zero range)
+fn split_index(sorted: &[Rgb], channel: usize) -> usize {
+    let middle = sorted.len() / 2;
+    let value = sorted[middle][channel];
+    let start = sorted.partition_point(|rgb| rgb[channel] < value);
+    let end = sorted.partition_point(|rgb| rgb[channel] <= value);
+    if start == 0 {
+        end
+    } else if end == sorted.len() || middle - start <= end - middle {
+        start
+    } else {
+        end
+    }
+}
+
+// ============================================
+// K-MEANS
+// ============================================
+
+/// Pixels nearest to one palette color
+#[derive(Debug, Clone)]
+struct Cluster {
+    center: Lab,
+    rgb_sum: [u64; 3],
+    count: usize,
+}
+
+impl Cluster {
+    /// The swatch color: members are alike, so a plain RGB mean is fine
+    fn mean_rgb(&self) -> Rgb {
+        let count = self.count.max(1) as u64;
+        self.rgb_sum.map(|sum| ((sum + count / 2) / count) as u8)
+    }
+}
+
+/// Refine centers by k-means; clusters come back most common first
+fn kmeans(samples: &[Rgb], labs: &[Lab], mut centers: Vec<Lab>) -> Vec<Cluster> {
+    for _ in 0..KMEANS_ROUNDS {
+        let mut sums = vec![[0.0; 3]; centers.len()];
+        let mut counts = vec![0usize; centers.len()];
+        for lab in labs {
+            let nearest = nearest(&centers, lab);
+            for (sum, value) in sums[nearest].iter_mut().zip(lab) {
+                *sum += value;
+            }
+            counts[nearest] += 1;
+        }
+        for ((center, sum), count) in centers.iter_mut().zip(sums).zip(counts) {
+            if count > 0 {
+                *center = sum.map(|value| value / count as f64);
+            }
+        }
+    }
+
+    let mut clusters: Vec<Cluster> = centers
+        .into_iter()
+        .map(|center| Cluster { center, rgb_sum: [0; 3], count: 0 })
+        .collect();
+    let final_centers: Vec<Lab> = clusters.iter().map(|cluster| cluster.center).collect();
+    for (rgb, lab) in samples.iter().zip(labs) {
+        let cluster = &mut clusters[nearest(&final_centers, lab)];
+        for (sum, value) in cluster.rgb_sum.iter_mut().zip(rgb) {
+            *sum += *value as u64;
+        }
+        cluster.count += 1;
+    }
+    clusters.retain(|cluster| cluster.count > 0);
+    clusters.sort_by_key(|cluster| Reverse(cluster.count));
+    clusters
+}
+
+/// Fold clusters into a larger one that looks the same
+fn merge_close(clusters: Vec<Cluster>) -> Vec<Cluster> {
+    let mut merged: Vec<Cluster> = Vec::new();
+    for cluster in clusters {
+        match merged.iter_mut().find(|kept| distance(&kept.center, &cluster.center) < MERGE_DISTANCE) {
+            Some(kept) => {
+                let total = (kept.count + cluster.count) as f64;
+                for channel in 0..3 {
+                    kept.center[channel] = (kept.center[channel] * kept.count as f64
+                        + cluster.center[channel] * cluster.count as f64)
+                        / total;
+                    kept.rgb_sum[channel] += cluster.rgb_sum[channel];
+                }
+                kept.count += cluster.count;
+            }
+            None => merged.push(cluster),
+        }
+    }
+    merged.sort_by_key(|cluster| Reverse(cluster.count));
+    merged
+}
+
+fn nearest(centers: &[Lab], lab: &Lab) -> usize {
+    centers
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(a, lab).total_cmp(&distance(b, lab)))
+        .map(|(index, _)| index)
+        .unwrap_or(0)
+}
+
+/// CIE76 color difference
+fn distance(a: &Lab, b: &Lab) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt()
+}
+
+fn mean_lab(labs: impl Iterator<Item = Lab>) -> Lab {
+    let mut sum = [0.0; 3];
+    let mut count = 0.0;
+    for lab in labs {
+        for (total, value) in sum.iter_mut().zip(lab) {
+            *total += value;
+        }
+        count += 1.0;
+    }
+    sum.map(|total| if count > 0.0 { total / count } else { 0.0 })
+}
+
+// ============================================
+// COLOR SPACES (sRGB, D65 white)
+// ============================================
+
+const WHITE: [f64; 3] = [0.950_47, 1.0, 1.088_83];
+const EPSILON: f64 = 216.0 / 24_389.0;
+const KAPPA: f64 = 24_389.0 / 27.0;
+
+fn to_linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(c: f64) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let srgb = if c <= 0.003_130_8 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (srgb * 255.0).round() as u8
+}
+
+fn to_lab(rgb: Rgb) -> Lab {
+    let [r, g, b] = rgb.map(to_linear);
+    let xyz = [
+        0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+        0.212_672_9 * r + 0.715_152_2 * g + 0.072_175_0 * b,
+        0.019_333_9 * r + 0.119_192_0 * g + 0.950_304_1 * b,
+    ];
+    let [fx, fy, fz] = [0, 1, 2].map(|i| {
+        let t = xyz[i] / WHITE[i];
+        if t > EPSILON { t.cbrt() } else { (KAPPA * t + 16.0) / 116.0 }
+    });
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn from_lab(lab: Lab) -> Rgb {
+    let fy = (lab[0] + 16.0) / 116.0;
+    let f = [fy + lab[1] / 500.0, fy, fy - lab[2] / 200.0];
+    let [x, y, z] = [0, 1, 2].map(|i| {
+        let cube = f[i].powi(3);
+        let t = if cube > EPSILON { cube } else { (116.0 * f[i] - 16.0) / KAPPA };
+        t * WHITE[i]
+    });
+    [
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266_0 * x + 1.876_010_8 * y + 0.041_556_0 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    ]
+    .map(from_linear)
+}
+
+fn to_hex(rgb: Rgb) -> String {
+    format!("#{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn palette_of(img: RgbaImage) -> ColorPalette {
+        extract_palette(&DynamicImage::ImageRgba8(img)).expect("image has opaque pixels")
+    }
+
+    fn colors(palette: &ColorPalette) -> Vec<(&str, f32)> {
+        palette.colors.iter().map(|swatch| (swatch.color.as_str(), swatch.proportion)).collect()
+    }
+
+    #[test]
+    fn test_lab_round_trip() {
+        for rgb in [[255, 0, 0], [0, 0, 255], [255, 255, 255], [0, 0, 0], [18, 200, 77], [128, 128, 128]] {
+            assert_eq!(from_lab(to_lab(rgb)), rgb);
+        }
+        // Reference values for pure red
+        let [l, a, b] = to_lab([255, 0, 0]);
+        assert!((l - 53.24).abs() < 0.01 && (a - 80.09).abs() < 0.01 && (b - 67.20).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_palette_keeps_real_colors() {
+        // Three quarters red, one quarter blue
+        let img = RgbaImage::from_fn(200, 100, |x, _| {
+            if x < 150 { Rgba([255, 0, 0, 255]) } else { Rgba([0, 0, 255, 255]) }
+        });
+        let palette = palette_of(img);
+        assert_eq!(colors(&palette), vec![("#FF0000", 0.75), ("#0000FF", 0.25)]);
+        assert_eq!(palette.dominant(), "#FF0000");
+        // Averaged in Lab, not the darker RGB mean (#BF0040)
+        assert_eq!(palette.average, "#E80050");
+    }
+
+    #[test]
+    fn test_palette_ignores_transparent_pixels() {
+        // A green logo on a transparent (black, alpha 0) background
+        let img = RgbaImage::from_fn(100, 100, |x, y| {
+            if (25..75).contains(&x) && (25..75).contains(&y) {
+                Rgba([20, 180, 60, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        });
+        let palette = palette_of(img);
+        assert_eq!(colors(&palette), vec![("#14B43C", 1.0)]);
+        assert_eq!(palette.average, "#14B43C");
+
+        assert!(extract_palette(&DynamicImage::ImageRgba8(RgbaImage::new(10, 10))).is_none());
+    }
+
+    #[test]
+    fn test_palette_of_a_gradient_and_shades() {
+        // A gradient is summarized by at most MAX_COLORS colors
+        let gradient = palette_of(RgbaImage::from_fn(256, 64, |x, _| Rgba([x as u8, 64, 255 - x as u8, 255])));
+        assert!(gradient.colors.len() > 1 && gradient.colors.len() <= MAX_COLORS);
+        let total: f32 = gradient.colors.iter().map(|swatch| swatch.proportion).sum();
+        assert!((total - 1.0).abs() < 0.01);
+
+        // Barely different shades (antialiasing, noise) are one color
+        let shades = palette_of(RgbaImage::from_fn(64, 64, |x, y| {
+            let jitter = ((x + y) % 3) as u8;
+            Rgba([200 + jitter, 200 + jitter, 200, 255])
+        }));
+        assert_eq!(shades.colors.len(), 1);
+        assert_eq!(shades.colors[0].proportion, 1.0);
+    }
+}
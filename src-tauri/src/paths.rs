@@ -0,0 +1,46 @@
+// Paths Module - App directories without an AppHandle
+// Mirrors Tauri's app_data_dir/app_config_dir so code that runs before the
+// app starts (single-instance lock, CLI) finds the same files.
+
+use std::path::PathBuf;
+
+/// Bundle identifier from tauri.conf.json
+pub const APP_ID: &str = "com.copygum.clipboard";
+
+/// Database file name used with tauri-plugin-sql ("sqlite:copygum.db")
+const DATABASE_FILE: &str = "copygum.db";
+
+/// Settings file written by settings.rs
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Same location Tauri resolves as app_data_dir (settings.json, images)
+pub fn app_data_dir() -> Result<PathBuf, String> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| "Failed to get data dir".to_string())?
+        .join(APP_ID);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(dir)
+}
+
+/// Same location Tauri resolves as app_config_dir (where plugin-sql keeps the DB)
+pub fn app_config_dir() -> Result<PathBuf, String> {
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_ID))
+        .ok_or_else(|| "Failed to get config dir".to_string())
+}
+
+/// Path of the clipboard history database
+pub fn database_path() -> Result<PathBuf, String> {
+    Ok(app_config_dir()?.join(DATABASE_FILE))
+}
+
+/// Where clipboard images are stored (see image_handler::save_clipboard_image)
+pub fn images_dir() -> Result<PathBuf, String> {
+    Ok(app_data_dir()?.join("CopyGum").join("images"))
+}
+
+/// Path of the settings file
+pub fn settings_path() -> Result<PathBuf, String> {
+    Ok(app_data_dir()?.join(SETTINGS_FILE))
+}
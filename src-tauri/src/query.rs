@@ -0,0 +1,492 @@
+// Query Module - Search box syntax
+// Parses queries like
+//   app:firefox type:url tag:work after:2026-09-01 is:pinned "deploy key"
+// into a typed AST, then compiles it for search.rs: free text becomes an
+// fts5 MATCH expression (ranked with bm25), everything else SQL conditions
+// over clipboard_items_with_tags, item_tags/tags and clipboard_items_fts.
+//
+// Grammar (clauses are ANDed; a leading - negates one):
+//   query  := clause*
+//   clause := "-"? (field ":" value | phrase | word)
+//   field  := app | type | tag | category | cat | after | before | is
+//   value  := phrase | word
+//   phrase := '"' any* '"'
+// Words before a colon that aren't fields ("https://...") stay plain text.
+// Errors carry character spans so the UI can underline them; the clause in
+// error is dropped and the rest of the query still runs.
+
+use chrono::{DateTime, Days, Local, NaiveDate, TimeZone};
+use rusqlite::types::Value;
+use serde::Serialize;
+
+use crate::db::items::escape_like;
+
+/// Furthest back a relative date (7d, 2w) may reach, about a century
+const MAX_DAYS_AGO: u32 = 36_500;
+
+/// Content types accepted by type:, with aliases, as stored in content_type
+const CONTENT_TYPES: &[(&str, &str)] = &[
+    ("text", "text"),
+    ("url", "links"),
+    ("link", "links"),
+    ("links", "links"),
+    ("email", "email"),
+    ("phone", "phone"),
+    ("number", "number"),
+    ("code", "code"),
+    ("color", "color"),
+    ("password", "password"),
+    ("apikey", "apikey"),
+    ("key", "apikey"),
+    ("image", "image"),
+];
+
+/// Character offsets into the query, end exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateSpec {
+    Day(NaiveDate),
+    /// Start of the day this many days before today (today = 0)
+    DaysAgo(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flag {
+    Pinned,
+    Image,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    /// Matches as a prefix ("dock" finds "docker")
+    Word(String),
+    Phrase(String),
+    /// Case-insensitive substring of the source app
+    App(String),
+    /// content_type as stored (aliases resolved)
+    Type(String),
+    Tag(String),
+    Category(String),
+    /// On or after the start of the day
+    After(DateSpec),
+    /// Before the start of the day
+    Before(DateSpec),
+    Is(Flag),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause {
+    pub term: Term,
+    pub negated: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    pub clauses: Vec<Clause>,
+    pub errors: Vec<ParseError>,
+}
+
+// ============================================
+// PARSING
+// ============================================
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    clauses: Vec<Clause>,
+    errors: Vec<ParseError>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn error(&mut self, message: String, start: usize, end: usize) {
+        self.errors.push(ParseError { message, span: Span { start, end } });
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Characters up to the next whitespace
+    fn read_word(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !c.is_whitespace()) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// A quoted string starting at the opening quote
+    fn read_phrase(&mut self) -> String {
+        let start = self.pos;
+        self.pos += 1;
+        let text_start = self.pos;
+        while self.peek().is_some_and(|c| c != '"') {
+            self.pos += 1;
+        }
+        let text: String = self.chars[text_start..self.pos].iter().collect();
+        if self.peek() == Some('"') {
+            self.pos += 1;
+        } else {
+            self.error("Missing closing quote".to_string(), start, self.pos);
+        }
+        text
+    }
+
+    /// A field name followed by a colon, if the word starts with one
+    fn read_field(&mut self) -> Option<String> {
+        let mut end = self.pos;
+        while self.chars.get(end).is_some_and(|c| c.is_ascii_alphabetic()) {
+            end += 1;
+        }
+        if end == self.pos || self.chars.get(end) != Some(&':') {
+            return None;
+        }
+        let name: String = self.chars[self.pos..end].iter().collect::<String>().to_lowercase();
+        let known = matches!(
+            name.as_str(),
+            "app" | "type" | "tag" | "category" | "cat" | "after" | "before" | "is"
+        );
+        if known {
+            self.pos = end + 1;
+            Some(name)
+        } else {
+            None
+        }
+    }
+
+    fn parse_clause(&mut self) {
+        let start = self.pos;
+        let negated = self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|c| !c.is_whitespace());
+        if negated {
+            self.pos += 1;
+        }
+
+        let term = if self.peek() == Some('"') {
+            let text = self.read_phrase();
+            let text = text.trim();
+            (!text.is_empty()).then(|| Term::Phrase(text.to_string()))
+        } else if let Some(field) = self.read_field() {
+            let value_start = self.pos;
+            let value = if self.peek() == Some('"') { self.read_phrase() } else { self.read_word() };
+            self.field_term(&field, value.trim(), start, value_start)
+        } else {
+            let word = self.read_word();
+            let word = word.trim_end_matches('*');
+            // Lone punctuation ("-", "*", "|") has nothing to search for
+            word.chars().any(char::is_alphanumeric).then(|| Term::Word(word.to_string()))
+        };
+
+        if let Some(term) = term {
+            self.clauses.push(Clause { term, negated, span: Span { start, end: self.pos } });
+        }
+    }
+
+    fn field_term(&mut self, field: &str, value: &str, start: usize, value_start: usize) -> Option<Term> {
+        let end = self.pos;
+        if value.is_empty() {
+            self.error(format!("{}: needs a value", field), start, end);
+            return None;
+        }
+        let term = match field {
+            "app" => Term::App(value.to_string()),
+            "tag" => Term::Tag(value.to_string()),
+            "category" | "cat" => Term::Category(value.to_string()),
+            "type" => {
+                let lower = value.to_lowercase();
+                match CONTENT_TYPES.iter().find(|(name, _)| *name == lower) {
+                    Some((_, stored)) => Term::Type(stored.to_string()),
+                    None => {
+                        let names: Vec<_> = CONTENT_TYPES.iter().map(|(name, _)| *name).collect();
+                        self.error(format!("Unknown type '{}' (try {})", value, names.join(", ")), value_start, end);
+                        return None;
+                    }
+                }
+            }
+            "is" => match value.to_lowercase().as_str() {
+                "pinned" => Term::Is(Flag::Pinned),
+                "image" => Term::Is(Flag::Image),
+                "text" => Term::Is(Flag::Text),
+                _ => {
+                    self.error(format!("Unknown flag '{}' (try pinned, image, text)", value), value_start, end);
+                    return None;
+                }
+            },
+            _ => match parse_date(value) {
+                Some(date) if field == "after" => Term::After(date),
+                Some(date) => Term::Before(date),
+                None => {
+                    self.error(
+                        format!("Invalid date '{}' (use YYYY-MM-DD, today, yesterday, 7d or 2w)", value),
+                        value_start,
+                        end,
+                    );
+                    return None;
+                }
+            },
+        };
+        Some(term)
+    }
+}
+
+/// YYYY-MM-DD, today, yesterday, or a number of days (7d) or weeks (2w) ago
+fn parse_date(value: &str) -> Option<DateSpec> {
+    let value = value.to_lowercase();
+    match value.as_str() {
+        "today" => return Some(DateSpec::DaysAgo(0)),
+        "yesterday" => return Some(DateSpec::DaysAgo(1)),
+        _ => {}
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+        return Some(DateSpec::Day(day));
+    }
+    let days = match value.strip_suffix('d') {
+        Some(days) => days.parse().ok()?,
+        None => value.strip_suffix('w')?.parse::<u32>().ok()?.checked_mul(7)?,
+    };
+    (days <= MAX_DAYS_AGO).then_some(DateSpec::DaysAgo(days))
+}
+
+/// Parse a search box query; never fails, problems are listed in `errors`
+pub fn parse(input: &str) -> ParsedQuery {
+    let mut parser = Parser { chars: input.chars().collect(), pos: 0, clauses: Vec::new(), errors: Vec::new() };
+    loop {
+        parser.skip_whitespace();
+        if parser.peek().is_none() {
+            break;
+        }
+        parser.parse_clause();
+    }
+    ParsedQuery { clauses: parser.clauses, errors: parser.errors }
+}
+
+// ============================================
+// COMPILING
+// ============================================
+
+/// Quote a term for fts5 so punctuation and keywords (AND, NEAR) are literal
+fn quote_term(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+fn fts_term(term: &Term) -> Option<String> {
+    match term {
+        Term::Word(word) => Some(format!("{}*", quote_term(word))),
+        Term::Phrase(phrase) => Some(quote_term(phrase)),
+        _ => None,
+    }
+}
+
+/// Milliseconds at the start of a day in local time
+fn day_start_millis(date: DateSpec, now: DateTime<Local>) -> i64 {
+    let day = match date {
+        DateSpec::Day(day) => day,
+        DateSpec::DaysAgo(days) => now
+            .date_naive()
+            .checked_sub_days(Days::new(u64::from(days)))
+            .unwrap_or(NaiveDate::MIN),
+    };
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|start| start.timestamp_millis())
+        // Midnight skipped by a DST change: fall back to UTC
+        .unwrap_or_else(|| midnight.and_utc().timestamp_millis())
+}
+
+impl ParsedQuery {
+    /// fts5 MATCH expression for the free text (all of it must match)
+    pub fn match_expression(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .clauses
+            .iter()
+            .filter(|clause| !clause.negated)
+            .filter_map(|clause| fts_term(&clause.term))
+            .collect();
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" AND "))
+        }
+    }
+
+    /// The free text words and phrases, for fuzzy matching
+    pub fn text_terms(&self) -> Vec<String> {
+        self.clauses
+            .iter()
+            .filter(|clause| !clause.negated)
+            .filter_map(|clause| match &clause.term {
+                Term::Word(text) | Term::Phrase(text) => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// SQL conditions for everything but the free text, over
+    /// clipboard_items_with_tags aliased `v`; empty when there are none
+    pub fn filter_sql(&self, now: DateTime<Local>) -> (String, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        for clause in &self.clauses {
+            let condition = match &clause.term {
+                Term::Word(_) | Term::Phrase(_) => {
+                    if !clause.negated {
+                        continue;
+                    }
+                    values.push(Value::Text(fts_term(&clause.term).unwrap_or_default()));
+                    "v.id IN (SELECT rowid FROM clipboard_items_fts WHERE clipboard_items_fts MATCH ?)"
+                }
+                Term::App(app) => {
+                    values.push(Value::Text(format!("%{}%", escape_like(app))));
+                    "COALESCE(v.app_name, '') LIKE ? ESCAPE '\\'"
+                }
+                Term::Type(content_type) => {
+                    values.push(Value::Text(content_type.clone()));
+                    "v.content_type = ?"
+                }
+                Term::Tag(tag) => {
+                    values.push(Value::Text(tag.clone()));
+                    "EXISTS (SELECT 1 FROM item_tags it JOIN tags t ON t.id = it.tag_id
+                             WHERE it.item_id = v.id AND t.name = ? COLLATE NOCASE)"
+                }
+                Term::Category(category) => {
+                    values.push(Value::Text(category.clone()));
+                    "v.category = ? COLLATE NOCASE"
+                }
+                Term::After(date) => {
+                    values.push(Value::Integer(day_start_millis(*date, now)));
+                    "v.timestamp >= ?"
+                }
+                Term::Before(date) => {
+                    values.push(Value::Integer(day_start_millis(*date, now)));
+                    "v.timestamp < ?"
+                }
+                Term::Is(Flag::Pinned) => "v.is_pinned = 1",
+                Term::Is(Flag::Image) => "v.is_image = 1",
+                Term::Is(Flag::Text) => "v.is_image = 0",
+            };
+            conditions.push(if clause.negated {
+                format!("NOT ({})", condition)
+            } else {
+                condition.to_string()
+            });
+        }
+
+        (conditions.join(" AND "), values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(input: &str) -> Vec<Term> {
+        let parsed = parse(input);
+        assert!(parsed.errors.is_empty(), "unexpected errors: {:?}", parsed.errors);
+        parsed.clauses.into_iter().map(|clause| clause.term).collect()
+    }
+
+    fn error_spans(input: &str) -> Vec<(usize, usize)> {
+        parse(input).errors.iter().map(|e| (e.span.start, e.span.end)).collect()
+    }
+
+    #[test]
+    fn test_fields_phrases_and_words() {
+        assert_eq!(
+            terms(r#"app:firefox type:url tag:work after:2026-09-01 is:pinned "deploy key""#),
+            vec![
+                Term::App("firefox".into()),
+                Term::Type("links".into()),
+                Term::Tag("work".into()),
+                Term::After(DateSpec::Day(NaiveDate::from_ymd_opt(2026, 9, 1).unwrap())),
+                Term::Is(Flag::Pinned),
+                Term::Phrase("deploy key".into()),
+            ]
+        );
+        assert_eq!(
+            terms(r#"APP:"Google Chrome" cat:Code before:2w ssh*"#),
+            vec![
+                Term::App("Google Chrome".into()),
+                Term::Category("Code".into()),
+                Term::Before(DateSpec::DaysAgo(14)),
+                Term::Word("ssh".into()),
+            ]
+        );
+        // Unknown fields and lone punctuation are just text
+        assert_eq!(
+            terms("https://example.com note:x - *"),
+            vec![Term::Word("https://example.com".into()), Term::Word("note:x".into())]
+        );
+    }
+
+    #[test]
+    fn test_negation_and_spans() {
+        let parsed = parse("docker -tag:work -\"compose up\"");
+        let negated: Vec<_> = parsed.clauses.iter().map(|c| (c.negated, c.span.start, c.span.end)).collect();
+        assert_eq!(negated, vec![(false, 0, 6), (true, 7, 16), (true, 17, 30)]);
+        assert_eq!(parsed.match_expression().unwrap(), "\"docker\"*");
+        assert_eq!(parsed.text_terms(), vec!["docker"]);
+    }
+
+    #[test]
+    fn test_errors_point_at_the_problem() {
+        assert_eq!(error_spans("type:movie"), vec![(5, 10)]);
+        assert_eq!(error_spans("x after:2026-13-01"), vec![(8, 18)]);
+        assert_eq!(error_spans("is:starred app:"), vec![(3, 10), (11, 15)]);
+        // Character offsets, not bytes
+        assert_eq!(error_spans("café \"open"), vec![(5, 10)]);
+        // A non-ASCII unit is an error, not a panic
+        assert_eq!(error_spans("after:7é before:3д"), vec![(6, 8), (16, 18)]);
+        // Out of range, not an overflow
+        assert_eq!(error_spans("after:100000000d before:20000000w"), vec![(6, 16), (24, 33)]);
+
+        // The rest of the query still applies
+        let parsed = parse("type:movie is:image");
+        assert_eq!(parsed.clauses.len(), 1);
+        assert_eq!(parsed.errors[0].message.split(' ').next(), Some("Unknown"));
+    }
+
+    #[test]
+    fn test_match_expression_quotes_fts_syntax() {
+        assert_eq!(parse("dock comp").match_expression().unwrap(), r#""dock"* AND "comp"*"#);
+        assert_eq!(
+            parse(r#"run "docker compose" up"#).match_expression().unwrap(),
+            r#""run"* AND "docker compose" AND "up"*"#
+        );
+        assert_eq!(parse("NEAR(a b) OR").match_expression().unwrap(), r#""NEAR(a"* AND "b)"* AND "OR"*"#);
+        assert!(parse("  \"\" - * is:pinned").match_expression().is_none());
+    }
+
+    #[test]
+    fn test_filter_sql() {
+        let now = Local.with_ymd_and_hms(2026, 10, 18, 15, 30, 0).unwrap();
+        let (sql, values) = parse("app:fire_fox -is:pinned after:today word").filter_sql(now);
+        assert_eq!(
+            sql,
+            "COALESCE(v.app_name, '') LIKE ? ESCAPE '\\' AND NOT (v.is_pinned = 1) AND v.timestamp >= ?"
+        );
+        let midnight = Local.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap().timestamp_millis();
+        assert_eq!(values, vec![Value::Text("%fire\\_fox%".into()), Value::Integer(midnight)]);
+        assert_eq!(parse("plain text").filter_sql(now).0, "");
+    }
+}
@@ -0,0 +1,503 @@
+// RPC Server Module - JSON-RPC over a Unix socket for integrations
+// Editors, launchers and scripts connect to copygum-rpc.sock in the app data
+// dir and exchange newline-delimited JSON-RPC 2.0 messages. The socket is
+// only accessible to the current user, and every connection must first call
+// `auth` with the token stored in rpc-token next to it.
+//
+// Methods:
+// - auth { token }
+// - items.list { limit?, category? } / items.search { query, limit? }
+// - items.add { content, category? } -> { id, is_new }
+// - items.copy { id }
+// - monitor.pause { minutes? } / monitor.resume
+// - subscribe { events } -> app events are sent back as notifications
+//   ({"jsonrpc":"2.0","method":"clipboard-changed","params":{...}}), dropped
+//   while a client falls too far behind reading them
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, EventId, Listener};
+
+use crate::clipboard_monitor;
+use crate::content_detector::detect_content_type;
+use crate::db::items::{self, ItemFilter, ItemQuery};
+use crate::paths::{app_data_dir, database_path};
+use crate::single_instance::token_matches;
+
+const SOCKET_FILE: &str = "copygum-rpc.sock";
+const TOKEN_FILE: &str = "rpc-token";
+const TOKEN_BYTES: usize = 32;
+const DEFAULT_LIMIT: usize = 100;
+/// Lines queued per connection; notifications beyond this are dropped
+const OUTBOX_SIZE: usize = 256;
+/// A client that doesn't read for this long is disconnected
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Events a connection can subscribe to
+const SUBSCRIBABLE_EVENTS: &[&str] = &["clipboard-changed", "items-changed", "monitoring-state-changed"];
+
+// JSON-RPC 2.0 error codes (-32001 is in the server-defined range)
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const UNAUTHORIZED: i64 = -32001;
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    jsonrpc: Option<String>,
+    // Absent for notifications, which get no reply
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    fn internal(message: String) -> Self {
+        Self::new(INTERNAL_ERROR, message)
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct AuthParams {
+    token: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct ListParams {
+    limit: Option<usize>,
+    category: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct SearchParams {
+    query: String,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct AddParams {
+    content: String,
+    category: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct CopyParams {
+    id: i64,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct PauseParams {
+    minutes: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct SubscribeParams {
+    events: Vec<String>,
+}
+
+/// A method call with validated parameters
+#[derive(Debug, PartialEq)]
+enum Call {
+    Auth(AuthParams),
+    ItemsList(ListParams),
+    ItemsSearch(SearchParams),
+    ItemsAdd(AddParams),
+    ItemsCopy(CopyParams),
+    MonitorPause(PauseParams),
+    MonitorResume,
+    Subscribe(SubscribeParams),
+}
+
+/// Per-connection state
+#[derive(Default)]
+struct Session {
+    authenticated: bool,
+    subscriptions: Vec<EventId>,
+}
+
+// ============================================
+// SERVER
+// ============================================
+
+/// Create the token file if needed and start accepting connections
+pub fn start(app: AppHandle) -> Result<(), String> {
+    let dir = app_data_dir()?;
+    let token = load_or_create_token(&dir.join(TOKEN_FILE))?;
+
+    let path = dir.join(SOCKET_FILE);
+    // Only one instance runs (single_instance.rs), so an existing socket is stale
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .map_err(|e| format!("Failed to bind RPC socket: {}", e))?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict RPC socket: {}", e))?;
+
+    println!("[CopyGum] RPC server listening on {}", path.display());
+
+    let token = Arc::new(token);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let app = app.clone();
+                    let token = Arc::clone(&token);
+                    std::thread::spawn(move || handle_connection(&app, stream, &token));
+                }
+                Err(e) => println!("[CopyGum] RPC connection failed: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Reuse the existing token so configured integrations keep working
+fn load_or_create_token(path: &Path) -> Result<String, String> {
+    if let Ok(existing) = fs::read_to_string(path) {
+        let existing = existing.trim();
+        if existing.len() == TOKEN_BYTES * 2 && existing.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(existing.to_string());
+        }
+    }
+
+    let mut bytes = [0u8; TOKEN_BYTES];
+    fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to generate RPC token: {}", e))?;
+    let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+    let _ = fs::remove_file(path);
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| format!("Failed to create RPC token file: {}", e))?;
+    file.write_all(token.as_bytes())
+        .map_err(|e| format!("Failed to write RPC token file: {}", e))?;
+
+    Ok(token)
+}
+
+fn handle_connection(app: &AppHandle, stream: UnixStream, token: &str) {
+    let outbox = match spawn_writer(&stream) {
+        Ok(outbox) => outbox,
+        Err(e) => {
+            println!("[CopyGum] RPC connection failed: {}", e);
+            return;
+        }
+    };
+    let mut session = Session::default();
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = handle_line(&line, session.authenticated, |call| {
+            execute(app, &outbox, &mut session, token, call)
+        });
+        if let Some(reply) = reply {
+            // Fails once the writer thread has given up on the client
+            if outbox.send(reply).is_err() {
+                break;
+            }
+        }
+    }
+
+    for id in session.subscriptions {
+        app.unlisten(id);
+    }
+}
+
+/// Write queued lines to the client on a separate thread, so a slow reader
+/// never blocks event listeners. Closes the socket when a write fails.
+fn spawn_writer(stream: &UnixStream) -> std::io::Result<SyncSender<String>> {
+    let mut stream = stream.try_clone()?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let (outbox, lines) = mpsc::sync_channel::<String>(OUTBOX_SIZE);
+
+    std::thread::spawn(move || {
+        for line in lines {
+            if stream.write_all(line.as_bytes()).and_then(|_| stream.write_all(b"\n")).is_err() {
+                break;
+            }
+        }
+        // Ends the reader loop, which unsubscribes the connection
+        let _ = stream.shutdown(Shutdown::Both);
+    });
+
+    Ok(outbox)
+}
+
+// ============================================
+// PROTOCOL
+// ============================================
+
+/// Parse one request line, run it and return the reply line (if any)
+fn handle_line(
+    line: &str,
+    authenticated: bool,
+    run: impl FnOnce(Call) -> Result<Value, RpcError>,
+) -> Option<String> {
+    let request = match serde_json::from_str::<RpcRequest>(line) {
+        Ok(request) => request,
+        Err(e) => {
+            let code = if serde_json::from_str::<Value>(line).is_ok() { INVALID_REQUEST } else { PARSE_ERROR };
+            return Some(response_line(Value::Null, Err(RpcError::new(code, e.to_string()))));
+        }
+    };
+
+    let result = if request.jsonrpc.as_deref() != Some("2.0") {
+        Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""))
+    } else {
+        parse_call(&request.method, request.params).and_then(|call| match call {
+            Call::Auth(_) => run(call),
+            _ if !authenticated => Err(RpcError::new(UNAUTHORIZED, "Call auth with the token from rpc-token first")),
+            _ => run(call),
+        })
+    };
+
+    request.id.map(|id| response_line(id, result))
+}
+
+fn response_line(id: Value, result: Result<Value, RpcError>) -> String {
+    let (result, error) = match result {
+        Ok(value) => (Some(value), None),
+        Err(error) => (None, Some(error)),
+    };
+    serde_json::to_string(&RpcResponse { jsonrpc: "2.0", id, result, error })
+        .unwrap_or_else(|_| r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32603,"message":"Failed to serialize response"}}"#.to_string())
+}
+
+fn parse_call(method: &str, params: Value) -> Result<Call, RpcError> {
+    // Methods without required params accept a missing params member
+    let params = if params.is_null() { json!({}) } else { params };
+
+    Ok(match method {
+        "auth" => Call::Auth(parse_params(params)?),
+        "items.list" => Call::ItemsList(parse_params(params)?),
+        "items.search" => Call::ItemsSearch(parse_params(params)?),
+        "items.add" => Call::ItemsAdd(parse_params(params)?),
+        "items.copy" => Call::ItemsCopy(parse_params(params)?),
+        "monitor.pause" => Call::MonitorPause(parse_params(params)?),
+        "monitor.resume" => Call::MonitorResume,
+        "subscribe" => {
+            let params: SubscribeParams = parse_params(params)?;
+            if let Some(unknown) = params.events.iter().find(|e| !SUBSCRIBABLE_EVENTS.contains(&e.as_str())) {
+                return Err(RpcError::new(INVALID_PARAMS, format!("Unknown event: {}", unknown)));
+            }
+            Call::Subscribe(params)
+        }
+        _ => return Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+    })
+}
+
+/// Authenticated integrations see the whole history, sensitive items included
+fn rpc_filter() -> ItemFilter {
+    ItemFilter { include_sensitive: true, ..ItemFilter::default() }
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+// ============================================
+// METHODS
+// ============================================
+
+fn execute(
+    app: &AppHandle,
+    outbox: &SyncSender<String>,
+    session: &mut Session,
+    token: &str,
+    call: Call,
+) -> Result<Value, RpcError> {
+    match call {
+        Call::Auth(AuthParams { token: given }) => {
+            if !token_matches(&given, token) {
+                return Err(RpcError::new(UNAUTHORIZED, "Invalid token"));
+            }
+            session.authenticated = true;
+            Ok(json!(true))
+        }
+        Call::ItemsList(ListParams { limit, category }) => {
+            let conn = items::open_read_only(&database_path().map_err(RpcError::internal)?)
+                .map_err(RpcError::internal)?;
+            let filter = ItemFilter { categories: category.into_iter().collect(), ..rpc_filter() };
+            let query = ItemQuery { limit: Some(limit.unwrap_or(DEFAULT_LIMIT)), filter };
+            to_value(items::list_items(&conn, &query).map_err(RpcError::internal)?)
+        }
+        Call::ItemsSearch(SearchParams { query, limit }) => {
+            let conn = items::open_read_only(&database_path().map_err(RpcError::internal)?)
+                .map_err(RpcError::internal)?;
+            let options = ItemQuery { limit: Some(limit.unwrap_or(DEFAULT_LIMIT)), filter: rpc_filter() };
+            to_value(items::search_items(&conn, &query, &options).map_err(RpcError::internal)?)
+        }
+        Call::ItemsAdd(AddParams { content, category }) => {
+            if content.trim().is_empty() {
+                return Err(RpcError::new(INVALID_PARAMS, "content must not be empty"));
+            }
+            let content_type = detect_content_type(&content);
+            let category = category
+                .unwrap_or_else(|| clipboard_monitor::category_for_content_type(&content_type).to_string());
+
+            let conn = items::open_read_write(&database_path().map_err(RpcError::internal)?)
+                .map_err(RpcError::internal)?;
+            let (id, is_new) = items::add_text_item(&conn, &content, content_type.as_str(), &category, None)
+                .map_err(RpcError::internal)?;
+            let _ = app.emit("items-changed", id);
+            Ok(json!({ "id": id, "is_new": is_new }))
+        }
+        Call::ItemsCopy(CopyParams { id }) => {
+            tauri::async_runtime::block_on(clipboard_monitor::copy_history_item(app, id))
+                .map_err(RpcError::internal)?;
+            Ok(json!(true))
+        }
+        Call::MonitorPause(PauseParams { minutes }) => {
+            let result = match minutes {
+                Some(minutes) => tauri::async_runtime::block_on(
+                    clipboard_monitor::pause_clipboard_monitoring_for(app.clone(), minutes),
+                ),
+                None => tauri::async_runtime::block_on(
+                    clipboard_monitor::pause_clipboard_monitoring(app.clone()),
+                ),
+            };
+            result.map_err(RpcError::internal)?;
+            Ok(json!(true))
+        }
+        Call::MonitorResume => {
+            tauri::async_runtime::block_on(clipboard_monitor::resume_clipboard_monitoring(app.clone()))
+                .map_err(RpcError::internal)?;
+            Ok(json!(true))
+        }
+        Call::Subscribe(SubscribeParams { events }) => {
+            for event in events {
+                let outbox = outbox.clone();
+                let method = event.clone();
+                let id = app.listen(event, move |e| {
+                    let params = serde_json::from_str::<Value>(e.payload()).unwrap_or(Value::Null);
+                    let notification = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+                    // Never block the emitting thread: drop events for a client that isn't keeping up
+                    match outbox.try_send(notification.to_string()) {
+                        Err(TrySendError::Full(_)) => println!("[CopyGum] RPC client is behind, dropped {}", method),
+                        // A closed connection is cleaned up when its reader loop ends
+                        Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+                    }
+                });
+                session.subscriptions.push(id);
+            }
+            Ok(json!(true))
+        }
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::internal(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(line: &str, authenticated: bool) -> Value {
+        let reply = handle_line(line, authenticated, |call| Ok(json!(format!("{:?}", call))))
+            .expect("expected a reply");
+        serde_json::from_str(&reply).unwrap()
+    }
+
+    #[test]
+    fn test_parse_call_params() {
+        assert_eq!(
+            parse_call("items.search", json!({ "query": "docker", "limit": 5 })).unwrap(),
+            Call::ItemsSearch(SearchParams { query: "docker".into(), limit: Some(5) })
+        );
+        assert_eq!(
+            parse_call("items.list", Value::Null).unwrap(),
+            Call::ItemsList(ListParams { limit: None, category: None })
+        );
+        assert_eq!(parse_call("items.copy", json!({})).unwrap_err().code, INVALID_PARAMS);
+        assert_eq!(parse_call("items.nuke", json!({})).unwrap_err().code, METHOD_NOT_FOUND);
+        assert_eq!(
+            parse_call("subscribe", json!({ "events": ["window-moved"] })).unwrap_err().code,
+            INVALID_PARAMS
+        );
+    }
+
+    #[test]
+    fn test_methods_require_auth() {
+        let denied = reply(r#"{"jsonrpc":"2.0","id":1,"method":"items.list"}"#, false);
+        assert_eq!(denied["error"]["code"], UNAUTHORIZED);
+        assert_eq!(denied["id"], 1);
+
+        let auth = reply(r#"{"jsonrpc":"2.0","id":2,"method":"auth","params":{"token":"t"}}"#, false);
+        assert!(auth["result"].is_string());
+
+        let allowed = reply(r#"{"jsonrpc":"2.0","id":"a","method":"items.list"}"#, true);
+        assert!(allowed.get("error").is_none());
+        assert_eq!(allowed["id"], "a");
+    }
+
+    #[test]
+    fn test_malformed_requests() {
+        assert_eq!(reply("{not json", true)["error"]["code"], PARSE_ERROR);
+        assert_eq!(reply(r#"{"id":1}"#, true)["error"]["code"], INVALID_REQUEST);
+        assert_eq!(
+            reply(r#"{"jsonrpc":"1.0","id":1,"method":"items.list"}"#, true)["error"]["code"],
+            INVALID_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_notifications_get_no_reply() {
+        assert!(handle_line(r#"{"jsonrpc":"2.0","method":"monitor.resume"}"#, true, |_| Ok(json!(true))).is_none());
+    }
+
+    #[test]
+    fn test_token_file_is_private_and_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TOKEN_FILE);
+
+        let token = load_or_create_token(&path).unwrap();
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(load_or_create_token(&path).unwrap(), token);
+
+        assert!(token_matches(&token, &token));
+        assert!(!token_matches("abc", &token));
+    }
+}
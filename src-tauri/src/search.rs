@@ -0,0 +1,568 @@
+// Search Module - Full-text search over the clipboard history
+// Queries the clipboard_items_fts index (fts5, see schema.sql) instead of
+// scanning content with LIKE:
+// - bare words match as prefixes ("dock" finds "docker"), "quoted text" as a phrase
+// - fields like app:, tag: and after: in the text are parsed by query.rs
+// - results are ranked with bm25, pinned items count double
+// - filters by category, tag, app and date reuse ItemFilter
+// - pages are fetched with a keyset cursor (score, id), never OFFSET, so the
+//   100th page costs the same as the first
+// - each hit carries a snippet split into matched / unmatched parts
+// - search_items adds fuzzy.rs matches after the exact ones, for typos
+// - trashed and sensitive items are never indexed (db/search_index.sql);
+//   rebuild_search_index reindexes from scratch if the index drifts
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::db::items::{self, ClipboardItem, ItemFilter};
+use crate::db::REBUILD_SEARCH_INDEX;
+use crate::fuzzy;
+use crate::paths::database_path;
+use crate::query::{self, ParseError};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+/// Relative weight of the indexed columns: content, app_name, category,
+/// search_text, tags
+const BM25_WEIGHTS: &str = "10.0, 2.0, 1.0, 5.0, 3.0";
+/// bm25 scores are negative (lower is better), so this pulls pinned items up
+const PINNED_BOOST: f64 = 2.0;
+/// Tokens of context around the first match in a snippet
+const SNIPPET_TOKENS: usize = 16;
+/// Characters shown when there is no text query to highlight
+const PREVIEW_CHARS: usize = 120;
+
+// Unprintable markers fts5 wraps around matches; never present in copied text
+const MATCH_START: char = '\u{2}';
+const MATCH_END: char = '\u{3}';
+
+/// Cursor prefix for pages of the fuzzy tier (followed by an offset)
+const FUZZY_CURSOR: &str = "fuzzy:";
+
+/// A search request from the UI (all fields optional; timestamps in ms)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SearchQuery {
+    pub query: String,
+    pub categories: Vec<String>,
+    pub tags: Vec<String>,
+    pub app: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub pinned_only: bool,
+    pub limit: Option<usize>,
+    /// next_cursor of the previous page, for the same query
+    pub cursor: Option<String>,
+}
+
+/// Part of a snippet; `matched` parts are highlighted
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnippetPart {
+    pub text: String,
+    pub matched: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub item: ClipboardItem,
+    /// Lower is better within a tier
+    pub score: f64,
+    pub snippet: Vec<SnippetPart>,
+    /// Found by the typo-tolerant tier rather than the exact search
+    pub fuzzy: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchPage {
+    pub hits: Vec<SearchHit>,
+    /// Pass back as `cursor` to get the next page; None on the last page
+    pub next_cursor: Option<String>,
+    /// Problems in the query syntax; those parts were left out of the search
+    pub errors: Vec<ParseError>,
+}
+
+/// Position after the last hit of a page
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cursor {
+    score: f64,
+    id: i64,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        format!("{}:{}", self.score, self.id)
+    }
+
+    fn decode(text: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid search cursor: {}", text);
+        let (score, id) = text.rsplit_once(':').ok_or_else(invalid)?;
+        Ok(Cursor {
+            score: score.parse().map_err(|_| invalid())?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+// ============================================
+// SNIPPETS
+// ============================================
+
+/// Split an fts5 snippet into highlighted and plain parts
+fn split_snippet(snippet: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    let mut rest = snippet;
+    while let Some(start) = rest.find(MATCH_START) {
+        if start > 0 {
+            parts.push(SnippetPart { text: rest[..start].to_string(), matched: false });
+        }
+        rest = &rest[start + MATCH_START.len_utf8()..];
+        let end = rest.find(MATCH_END).unwrap_or(rest.len());
+        parts.push(SnippetPart { text: rest[..end].to_string(), matched: true });
+        rest = rest.get(end + MATCH_END.len_utf8()..).unwrap_or("");
+    }
+    if !rest.is_empty() {
+        parts.push(SnippetPart { text: rest.to_string(), matched: false });
+    }
+    parts
+}
+
+/// Start of the content, for browsing without a text query
+fn preview(content: &str) -> Vec<SnippetPart> {
+    let mut text: String = content.chars().take(PREVIEW_CHARS).collect();
+    if text.len() < content.len() {
+        text.push('…');
+    }
+    vec![SnippetPart { text, matched: false }]
+}
+
+// ============================================
+// SEARCH
+// ============================================
+
+impl SearchQuery {
+    /// WHERE clause for the filters, including those typed in the query
+    /// text, over clipboard_items_with_tags (aliased `v`)
+    pub fn where_clause(&self) -> (String, Vec<Value>) {
+        let (mut where_sql, mut values) = self.filter().where_clause();
+        if let Some(app) = self.app.as_deref().filter(|app| !app.is_empty()) {
+            where_sql.push_str(" AND v.app_name = ?");
+            values.push(Value::Text(app.to_string()));
+        }
+        let (typed_sql, typed_values) = query::parse(&self.query).filter_sql(chrono::Local::now());
+        if !typed_sql.is_empty() {
+            where_sql.push_str(" AND ");
+            where_sql.push_str(&typed_sql);
+            values.extend(typed_values);
+        }
+        (where_sql, values)
+    }
+
+    fn filter(&self) -> ItemFilter {
+        ItemFilter {
+            since: self.since,
+            until: self.until,
+            categories: self.categories.clone(),
+            tags: self.tags.clone(),
+            pinned_only: self.pinned_only,
+            // Search shows everything the history view shows
+            include_sensitive: true,
+        }
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+fn hit_from_row(row: &Row, has_text: bool) -> rusqlite::Result<SearchHit> {
+    let item = items::item_from_row(row)?;
+    let score: f64 = row.get(12)?;
+    let snippet = if has_text {
+        split_snippet(&row.get::<_, String>(13)?)
+    } else {
+        preview(&item.content)
+    };
+    Ok(SearchHit { item, score, snippet, fuzzy: false })
+}
+
+/// One page of results, best first
+/// Without a text query items are listed pinned first, then newest, so the
+/// same filters and cursor paging work for browsing.
+pub fn search(conn: &Connection, query: &SearchQuery) -> Result<SearchPage, String> {
+    let (where_sql, mut values) = query.where_clause();
+    let columns = items::qualified_columns("v");
+    let parsed = query::parse(&query.query);
+    let match_expression = parsed.match_expression();
+    let inner = match &match_expression {
+        Some(expression) => {
+            values.insert(0, Value::Text(expression.clone()));
+            format!(
+                "SELECT {columns},
+                        bm25(clipboard_items_fts, {weights}) * (CASE WHEN v.is_pinned THEN {boost} ELSE 1.0 END) AS score,
+                        snippet(clipboard_items_fts, 0, '{start}', '{end}', '…', {tokens}) AS snippet
+                 FROM clipboard_items_fts
+                 JOIN clipboard_items_with_tags v ON v.id = clipboard_items_fts.rowid
+                 WHERE clipboard_items_fts MATCH ? AND {where_sql}",
+                columns = columns,
+                weights = BM25_WEIGHTS,
+                boost = PINNED_BOOST,
+                start = MATCH_START,
+                end = MATCH_END,
+                tokens = SNIPPET_TOKENS,
+                where_sql = where_sql,
+            )
+        }
+        // Pinned first, then newest: the score orders like the history view
+        // (timestamps in ms stay well below the pinned offset)
+        None => format!(
+            "SELECT {}, -(v.is_pinned * 1e14 + v.timestamp) AS score
+             FROM clipboard_items_with_tags v WHERE {}",
+            columns, where_sql
+        ),
+    };
+
+    let mut sql = format!("SELECT * FROM ({})", inner);
+    if let Some(cursor) = query.cursor.as_deref() {
+        let cursor = Cursor::decode(cursor)?;
+        sql.push_str(" WHERE score > ? OR (score = ? AND id < ?)");
+        values.extend([Value::Real(cursor.score), Value::Real(cursor.score), Value::Integer(cursor.id)]);
+    }
+    let limit = query.limit();
+    sql.push_str(" ORDER BY score, id DESC LIMIT ?");
+    // One extra row tells whether there is another page
+    values.push(Value::Integer(limit as i64 + 1));
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to search items: {}", e))?;
+    let has_text = match_expression.is_some();
+    let mut hits = stmt
+        .query_map(params_from_iter(values), |row| hit_from_row(row, has_text))
+        .map_err(|e| format!("Failed to search items: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read search results: {}", e))?;
+
+    let next_cursor = if hits.len() > limit {
+        hits.truncate(limit);
+        hits.last().map(|hit| Cursor { score: hit.score, id: hit.item.id }.encode())
+    } else {
+        None
+    };
+    Ok(SearchPage { hits, next_cursor, errors: parsed.errors })
+}
+
+/// Exact results first, then fuzzy matches the exact search missed
+/// Paging runs through the exact tier with keyset cursors, then through the
+/// fuzzy tier (scored in memory from a bounded candidate set) by offset.
+pub fn search_with_fuzzy(conn: &Connection, query: &SearchQuery) -> Result<SearchPage, String> {
+    let limit = query.limit();
+    let (mut page, offset) = match query.cursor.as_deref().and_then(|c| c.strip_prefix(FUZZY_CURSOR)) {
+        Some(offset) => {
+            let offset = offset.parse().map_err(|_| format!("Invalid search cursor: {}", FUZZY_CURSOR))?;
+            let errors = query::parse(&query.query).errors;
+            (SearchPage { hits: Vec::new(), next_cursor: None, errors }, offset)
+        }
+        None => {
+            let page = search(conn, query)?;
+            if page.next_cursor.is_some() {
+                return Ok(page);
+            }
+            (page, 0)
+        }
+    };
+
+    let Some(expression) = query::parse(&query.query).match_expression() else {
+        return Ok(page);
+    };
+    let fuzzy_hits = fuzzy::fuzzy_search(conn, query, Some(&expression))?;
+    let room = limit - page.hits.len();
+    let end = (offset + room).min(fuzzy_hits.len());
+    if end < fuzzy_hits.len() {
+        page.next_cursor = Some(format!("{}{}", FUZZY_CURSOR, end));
+    }
+    page.hits.extend(fuzzy_hits.into_iter().skip(offset).take(room));
+    Ok(page)
+}
+
+// ============================================
+// REBUILD
+// ============================================
+
+/// What a rebuild changed in the full-text index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct RebuildReport {
+    /// Items in the index afterwards
+    pub indexed: usize,
+    /// Items that were missing from the index
+    pub added: usize,
+    /// Rows that shouldn't have been indexed (trashed, sensitive or gone)
+    pub removed: usize,
+    /// Items indexed with outdated text or tags
+    pub updated: usize,
+}
+
+impl RebuildReport {
+    pub fn changed(&self) -> usize {
+        self.added + self.removed + self.updated
+    }
+}
+
+fn count(conn: &Connection, sql: &str) -> Result<usize, String> {
+    conn.query_row(sql, [], |row| row.get::<_, i64>(0))
+        .map(|n| n as usize)
+        .map_err(|e| format!("Failed to compare search index: {}", e))
+}
+
+/// Reindex every active, non-sensitive item and report the difference
+pub fn rebuild_index(conn: &mut Connection) -> Result<RebuildReport, String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    tx.execute_batch(
+        "CREATE TEMP TABLE search_index_before AS
+         SELECT rowid AS id, content, app_name, category, search_text, tags FROM clipboard_items_fts",
+    )
+    .map_err(|e| format!("Failed to snapshot search index: {}", e))?;
+    tx.execute_batch(REBUILD_SEARCH_INDEX)
+        .map_err(|e| format!("Failed to rebuild search index: {}", e))?;
+
+    let report = RebuildReport {
+        indexed: count(&tx, "SELECT COUNT(*) FROM clipboard_items_fts")?,
+        added: count(
+            &tx,
+            "SELECT COUNT(*) FROM clipboard_items_fts WHERE rowid NOT IN (SELECT id FROM search_index_before)",
+        )?,
+        removed: count(
+            &tx,
+            "SELECT COUNT(*) FROM search_index_before WHERE id NOT IN (SELECT rowid FROM clipboard_items_fts)",
+        )?,
+        updated: count(
+            &tx,
+            "SELECT COUNT(*) FROM clipboard_items_fts f JOIN search_index_before b ON b.id = f.rowid
+             WHERE f.content IS NOT b.content OR f.app_name IS NOT b.app_name OR f.category IS NOT b.category
+                OR f.search_text IS NOT b.search_text OR f.tags IS NOT b.tags",
+        )?,
+    };
+    tx.execute_batch("DROP TABLE search_index_before")
+        .map_err(|e| format!("Failed to rebuild search index: {}", e))?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+    Ok(report)
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
+/// Full-text search with filters and a fuzzy fallback; pass next_cursor
+/// back for more results
+#[tauri::command]
+pub async fn search_items(query: SearchQuery) -> Result<SearchPage, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = items::open_read_only(&database_path()?)?;
+        search_with_fuzzy(&conn, &query)
+    })
+    .await
+    .map_err(|e| format!("Search failed: {}", e))?
+}
+
+/// Reindex the whole history for search; returns what changed
+#[tauri::command]
+pub async fn rebuild_search_index() -> Result<RebuildReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = items::open_read_write(&database_path()?)?;
+        let report = rebuild_index(&mut conn)?;
+        println!(
+            "[CopyGum] Rebuilt search index: {} items, {} rows changed",
+            report.indexed,
+            report.changed()
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Search index rebuild failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::params;
+
+    fn insert(conn: &Connection, content: &str, app: &str, timestamp: i64, pinned: bool) -> i64 {
+        conn.execute(
+            "INSERT INTO clipboard_items (content, content_type, category, app_name, is_image, timestamp, is_pinned, is_deleted)
+             VALUES (?1, 'text', 'text', ?2, 0, ?3, ?4, 0)",
+            params![content, app, timestamp, pinned],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn test_setup() -> Connection {
+        let conn = items::test_connection();
+        insert(&conn, "meeting notes", "Notes", 1_000, false);
+        insert(&conn, "docker ps", "Notes", 2_000, true);
+        insert(&conn, "docker compose up -d", "Terminal", 3_000, false);
+        conn
+    }
+
+    fn contents(page: &SearchPage) -> Vec<&str> {
+        page.hits.iter().map(|hit| hit.item.content.as_str()).collect()
+    }
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery { query: text.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_prefix_phrase_and_snippets() {
+        let conn = test_setup();
+        let page = search(&conn, &query("dock")).unwrap();
+        assert_eq!(page.hits.len(), 2);
+
+        let page = search(&conn, &query("\"compose up\"")).unwrap();
+        assert_eq!(contents(&page), vec!["docker compose up -d"]);
+        let highlighted: Vec<_> = page.hits[0].snippet.iter().filter(|p| p.matched).map(|p| p.text.as_str()).collect();
+        assert_eq!(highlighted, vec!["compose up"]);
+
+        assert!(search(&conn, &query("\"up compose\"")).unwrap().hits.is_empty());
+    }
+
+    #[test]
+    fn test_filters_and_pinned_boost() {
+        let conn = test_setup();
+        // Same text in both; the pinned one wins despite being older
+        let page = search(&conn, &query("docker")).unwrap();
+        assert_eq!(page.hits[0].item.content, "docker ps");
+        assert!(page.hits[0].item.is_pinned);
+
+        let page = search(&conn, &SearchQuery { app: Some("Terminal".into()), ..query("docker") }).unwrap();
+        assert_eq!(contents(&page), vec!["docker compose up -d"]);
+
+        let page = search(&conn, &SearchQuery { since: Some(2_500), ..query("docker") }).unwrap();
+        assert_eq!(contents(&page), vec!["docker compose up -d"]);
+
+        // Browsing without text: pinned first, then newest
+        let page = search(&conn, &query("")).unwrap();
+        assert_eq!(contents(&page), vec!["docker ps", "docker compose up -d", "meeting notes"]);
+    }
+
+    #[test]
+    fn test_typed_filters_and_errors() {
+        let conn = test_setup();
+        let page = search(&conn, &query("docker app:term")).unwrap();
+        assert_eq!(contents(&page), vec!["docker compose up -d"]);
+
+        let page = search(&conn, &query("-is:pinned dock")).unwrap();
+        assert_eq!(contents(&page), vec!["docker compose up -d"]);
+
+        let page = search(&conn, &query("docker -compose")).unwrap();
+        assert_eq!(contents(&page), vec!["docker ps"]);
+
+        // A bad clause is reported and left out; the rest still runs
+        let page = search(&conn, &query("is:starred docker")).unwrap();
+        assert_eq!(page.hits.len(), 2);
+        assert_eq!(page.errors[0].span.start, 3);
+    }
+
+    #[test]
+    fn test_fuzzy_tier_follows_exact_hits() {
+        let conn = test_setup();
+        insert(&conn, "kubectl get pods", "Terminal", 4_000, false);
+        insert(&conn, "kubctl is a typo", "Notes", 5_000, false);
+
+        let page = search_with_fuzzy(&conn, &query("kubctl")).unwrap();
+        assert_eq!(contents(&page), vec!["kubctl is a typo", "kubectl get pods"]);
+        assert!(!page.hits[0].fuzzy);
+        assert!(page.hits[1].fuzzy);
+
+        // The fuzzy tier pages on after the exact one
+        let first = search_with_fuzzy(&conn, &SearchQuery { limit: Some(1), ..query("kubctl") }).unwrap();
+        let cursor = first.next_cursor.clone();
+        let second = search_with_fuzzy(&conn, &SearchQuery { limit: Some(1), cursor, ..query("kubctl") }).unwrap();
+        assert_eq!(contents(&first), vec!["kubctl is a typo"]);
+        assert_eq!(contents(&second), vec!["kubectl get pods"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_keyset_pagination_visits_every_match_once() {
+        let conn = items::test_connection();
+        for i in 0..25 {
+            insert(&conn, &format!("log line {}", i), "Terminal", 1_000 + i, i % 7 == 0);
+        }
+
+        for text in ["log", ""] {
+            let mut seen = Vec::new();
+            let mut cursor = None;
+            loop {
+                let page = search(&conn, &SearchQuery { limit: Some(10), cursor, ..query(text) }).unwrap();
+                seen.extend(page.hits.iter().map(|hit| hit.item.id));
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+            let mut unique = seen.clone();
+            unique.sort();
+            unique.dedup();
+            assert_eq!(seen.len(), 25);
+            assert_eq!(unique.len(), 25);
+        }
+        assert!(search(&conn, &SearchQuery { cursor: Some("nope".into()), ..query("log") }).is_err());
+    }
+
+    #[test]
+    fn test_index_skips_trashed_and_sensitive_items() {
+        let conn = test_setup();
+        let secret = insert(&conn, "hunter2 docker registry", "Vault", 4_000, false);
+        conn.execute("UPDATE clipboard_items SET category = 'password' WHERE id = ?1", [secret]).unwrap();
+        assert_eq!(search_with_fuzzy(&conn, &query("hunter2")).unwrap().hits.len(), 0);
+        assert_eq!(search(&conn, &query("registry")).unwrap().hits.len(), 0);
+
+        conn.execute("UPDATE clipboard_items SET is_deleted = 1 WHERE content = 'docker ps'", []).unwrap();
+        assert_eq!(contents(&search(&conn, &query("docker")).unwrap()), vec!["docker compose up -d"]);
+        // Restoring from the trash makes it searchable again
+        conn.execute("UPDATE clipboard_items SET is_deleted = 0 WHERE content = 'docker ps'", []).unwrap();
+        assert_eq!(search(&conn, &query("docker")).unwrap().hits.len(), 2);
+    }
+
+    #[test]
+    fn test_search_text_and_tags_are_indexed() {
+        let conn = test_setup();
+        let id = insert(&conn, "screenshot", "Preview", 4_000, false);
+        conn.execute("UPDATE clipboard_items SET search_text = 'quarterly invoice' WHERE id = ?1", [id]).unwrap();
+        assert_eq!(contents(&search(&conn, &query("invoice")).unwrap()), vec!["screenshot"]);
+
+        conn.execute("INSERT INTO item_tags (item_id, tag_id) SELECT ?1, id FROM tags WHERE name = 'Work'", [id])
+            .unwrap();
+        assert_eq!(contents(&search(&conn, &query("work")).unwrap()), vec!["screenshot"]);
+        conn.execute("UPDATE tags SET name = 'Office' WHERE name = 'Work'", []).unwrap();
+        assert_eq!(contents(&search(&conn, &query("office")).unwrap()), vec!["screenshot"]);
+        conn.execute("DELETE FROM item_tags WHERE item_id = ?1", [id]).unwrap();
+        assert!(search(&conn, &query("office")).unwrap().hits.is_empty());
+    }
+
+    #[test]
+    fn test_rebuild_reports_changed_rows() {
+        let mut conn = test_setup();
+        assert_eq!(rebuild_index(&mut conn).unwrap(), RebuildReport { indexed: 3, ..Default::default() });
+
+        // Drift the index: one item missing, one stale, one that should be gone
+        conn.execute_batch(
+            "DELETE FROM clipboard_items_fts WHERE rowid = 1;
+             UPDATE clipboard_items_fts SET content = 'stale' WHERE rowid = 2;
+             INSERT INTO clipboard_items_fts (rowid, content) VALUES (99, 'old secret');",
+        )
+        .unwrap();
+        let report = rebuild_index(&mut conn).unwrap();
+        assert_eq!(report, RebuildReport { indexed: 3, added: 1, removed: 1, updated: 1 });
+        assert_eq!(report.changed(), 3);
+        assert!(search(&conn, &query("secret")).unwrap().hits.is_empty());
+        assert_eq!(search(&conn, &query("meeting")).unwrap().hits.len(), 1);
+    }
+}
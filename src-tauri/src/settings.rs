@@ -3,9 +3,11 @@
 
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, Manager};
 
+use crate::autostart;
+
 // Default functions for new settings fields
 fn default_density() -> String {
     "comfortable".to_string()
@@ -23,18 +25,72 @@ fn default_has_shown_overlay_info() -> bool {
     false
 }
 
+fn default_overlay_edge() -> String {
+    "bottom".to_string()
+}
+
+fn default_overlay_monitor() -> String {
+    "cursor".to_string()
+}
+
+fn default_overlay_height_percent() -> u32 {
+    35
+}
+
+fn default_overlay_min_height() -> u32 {
+    350
+}
+
+fn default_overlay_max_height() -> u32 {
+    450
+}
+
+fn default_ocr_language() -> String {
+    "eng".to_string()
+}
+
+fn default_similar_images() -> String {
+    "stack".to_string()
+}
+
+fn default_similar_image_threshold() -> u32 {
+    6
+}
+
+fn default_strip_image_metadata() -> bool {
+    true
+}
+
+fn default_backup_frequency() -> String {
+    "daily".to_string()
+}
+
+fn default_backup_keep() -> u32 {
+    7
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
     // General
     pub auto_start_monitoring: bool,
     pub show_on_startup: bool,
     pub minimize_to_tray: bool,
+    #[serde(default)]
+    pub launch_at_startup: bool,  // Mirrors the installed login item (see autostart.rs)
 
     // Storage
     pub history_limit: i32,  // 100, 500, 1000, -1 (unlimited)
     pub auto_delete_days: i32,  // 0 (never), 7, 30, 90
     pub save_images: bool,
     pub max_image_size_mb: i32,
+    #[serde(default)]
+    pub ocr_enabled: bool,  // Recognize text in captured images (see ocr.rs)
+    #[serde(default = "default_ocr_language")]
+    pub ocr_language: String,  // Tesseract language codes, e.g. "eng" or "eng+deu"
+    #[serde(default = "default_similar_images")]
+    pub similar_images: String,  // "off", "stack", "merge" (see similar_images.rs)
+    #[serde(default = "default_similar_image_threshold")]
+    pub similar_image_threshold: u32,  // Max differing perceptual hash bits (of 64)
 
     // Appearance
     pub theme: String,  // "light", "dark", "auto", "high-contrast", "nord", "dracula", "solarized"
@@ -50,6 +106,28 @@ pub struct AppSettings {
     #[serde(default = "default_blur")]
     pub enable_blur: bool,  // Enable blur effect
 
+    // Overlay placement
+    #[serde(default = "default_overlay_edge")]
+    pub overlay_edge: String,  // "bottom", "top", "left", "right"
+    #[serde(default = "default_overlay_monitor")]
+    pub overlay_monitor: String,  // "cursor", "focused-window" (cursor on Wayland, which hides window positions)
+    #[serde(default = "default_overlay_height_percent")]
+    pub overlay_height_percent: u32,  // Share of the screen the overlay covers (width for left/right)
+    #[serde(default = "default_overlay_min_height")]
+    pub overlay_min_height: u32,  // Logical pixels, scaled per monitor
+    #[serde(default = "default_overlay_max_height")]
+    pub overlay_max_height: u32,  // Logical pixels, scaled per monitor
+
+    // Backups
+    #[serde(default)]
+    pub backup_enabled: bool,
+    #[serde(default = "default_backup_frequency")]
+    pub backup_frequency: String,  // "daily", "weekly"
+    #[serde(default)]
+    pub backup_directory: Option<String>,  // None: <app data>/backups
+    #[serde(default = "default_backup_keep")]
+    pub backup_keep: u32,  // Snapshots kept before the oldest is removed
+
     // Shortcuts
     pub toggle_window_shortcut: String,
     pub search_shortcut: String,
@@ -58,6 +136,8 @@ pub struct AppSettings {
     pub exclude_apps: Vec<String>,
     pub sensitive_keywords: Vec<String>,
     pub enable_analytics: bool,
+    #[serde(default = "default_strip_image_metadata")]
+    pub strip_image_metadata: bool,  // Remove EXIF/GPS from saved and pasted images (see image_metadata.rs)
 
     // First-run
     #[serde(default = "default_has_shown_overlay_info", rename = "hasShownOverlayInfo")]
@@ -71,12 +151,17 @@ impl Default for AppSettings {
             auto_start_monitoring: true,
             show_on_startup: false,
             minimize_to_tray: true,
+            launch_at_startup: false,
 
             // Storage defaults
             history_limit: 500,
             auto_delete_days: 0,  // Never auto-delete by default
             save_images: true,
             max_image_size_mb: 10,
+            ocr_enabled: false,
+            ocr_language: "eng".to_string(),
+            similar_images: "stack".to_string(),
+            similar_image_threshold: 6,
 
             // Appearance defaults
             theme: "auto".to_string(),
@@ -88,6 +173,19 @@ impl Default for AppSettings {
             window_opacity: 100,
             enable_blur: false,
 
+            // Overlay placement defaults
+            overlay_edge: "bottom".to_string(),
+            overlay_monitor: "cursor".to_string(),
+            overlay_height_percent: 35,
+            overlay_min_height: 350,
+            overlay_max_height: 450,
+
+            // Backup defaults
+            backup_enabled: false,
+            backup_frequency: "daily".to_string(),
+            backup_directory: None,
+            backup_keep: 7,
+
             // Shortcuts defaults
             toggle_window_shortcut: "CommandOrControl+Shift+V".to_string(),
             search_shortcut: "CommandOrControl+F".to_string(),
@@ -96,6 +194,7 @@ impl Default for AppSettings {
             exclude_apps: vec![],
             sensitive_keywords: vec!["password".to_string(), "secret".to_string()],
             enable_analytics: false,
+            strip_image_metadata: true,
 
             // First-run defaults
             has_shown_overlay_info: false,
@@ -106,10 +205,13 @@ impl Default for AppSettings {
 impl AppSettings {
     /// Load settings from file
     pub fn load(app: &AppHandle) -> Result<Self, String> {
-        let settings_path = Self::get_settings_path(app)?;
+        Self::load_from(&Self::get_settings_path(app)?)
+    }
 
+    /// Load settings from a specific file (used by the CLI, which has no AppHandle)
+    pub fn load_from(settings_path: &Path) -> Result<Self, String> {
         if settings_path.exists() {
-            let contents = fs::read_to_string(&settings_path)
+            let contents = fs::read_to_string(settings_path)
                 .map_err(|e| format!("Failed to read settings: {}", e))?;
 
             let settings: AppSettings = serde_json::from_str(&contents)
@@ -151,15 +253,29 @@ impl AppSettings {
 // TAURI COMMANDS
 // ============================================
 
+/// Install or remove the login item to match the setting, then record
+/// what is actually installed so the UI never shows a stale toggle
+fn apply_launch_at_startup(app: &AppHandle, settings: &mut AppSettings) -> Result<(), String> {
+    if autostart::is_enabled(app) != settings.launch_at_startup {
+        autostart::set_enabled(app, settings.launch_at_startup)?;
+    }
+    settings.launch_at_startup = autostart::is_enabled(app);
+    Ok(())
+}
+
 /// Get current settings
 #[tauri::command]
 pub async fn get_settings(app: AppHandle) -> Result<AppSettings, String> {
-    AppSettings::load(&app)
+    let mut settings = AppSettings::load(&app)?;
+    // The login item can be removed outside the app (e.g. GNOME Tweaks)
+    settings.launch_at_startup = autostart::is_enabled(&app);
+    Ok(settings)
 }
 
 /// Save settings
 #[tauri::command]
-pub async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
+pub async fn save_settings(app: AppHandle, mut settings: AppSettings) -> Result<(), String> {
+    apply_launch_at_startup(&app, &mut settings)?;
     settings.save(&app)?;
 
     // Emit event so other parts of app can react to settings changes
@@ -171,7 +287,8 @@ pub async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(),
 /// Reset settings to defaults
 #[tauri::command]
 pub async fn reset_settings(app: AppHandle) -> Result<AppSettings, String> {
-    let default = AppSettings::default();
+    let mut default = AppSettings::default();
+    apply_launch_at_startup(&app, &mut default)?;
     default.save(&app)?;
 
     // Emit event
@@ -192,12 +309,17 @@ mod tests {
         assert!(settings.auto_start_monitoring);
         assert!(!settings.show_on_startup);
         assert!(settings.minimize_to_tray);
+        assert!(!settings.launch_at_startup);
 
         // Storage defaults
         assert_eq!(settings.history_limit, 500);
         assert_eq!(settings.auto_delete_days, 0);
         assert!(settings.save_images);
         assert_eq!(settings.max_image_size_mb, 10);
+        assert!(!settings.ocr_enabled);
+        assert_eq!(settings.ocr_language, "eng");
+        assert_eq!(settings.similar_images, "stack");
+        assert_eq!(settings.similar_image_threshold, 6);
 
         // Appearance defaults
         assert_eq!(settings.theme, "auto");
@@ -209,6 +331,19 @@ mod tests {
         assert_eq!(settings.window_opacity, 100);
         assert!(!settings.enable_blur);
 
+        // Overlay placement defaults
+        assert_eq!(settings.overlay_edge, "bottom");
+        assert_eq!(settings.overlay_monitor, "cursor");
+        assert_eq!(settings.overlay_height_percent, 35);
+        assert_eq!(settings.overlay_min_height, 350);
+        assert_eq!(settings.overlay_max_height, 450);
+
+        // Backup defaults
+        assert!(!settings.backup_enabled);
+        assert_eq!(settings.backup_frequency, "daily");
+        assert!(settings.backup_directory.is_none());
+        assert_eq!(settings.backup_keep, 7);
+
         // Shortcuts defaults
         assert_eq!(settings.toggle_window_shortcut, "CommandOrControl+Shift+V");
         assert_eq!(settings.search_shortcut, "CommandOrControl+F");
@@ -217,6 +352,7 @@ mod tests {
         assert!(settings.exclude_apps.is_empty());
         assert_eq!(settings.sensitive_keywords.len(), 2);
         assert!(!settings.enable_analytics);
+        assert!(settings.strip_image_metadata);
 
         // First-run defaults
         assert!(!settings.has_shown_overlay_info);
@@ -275,6 +411,16 @@ mod tests {
         assert_eq!(settings.window_opacity, 100);
         assert!(!settings.enable_blur);
         assert!(!settings.has_shown_overlay_info);
+        assert!(!settings.launch_at_startup);
+        assert_eq!(settings.overlay_edge, "bottom");
+        assert_eq!(settings.overlay_height_percent, 35);
+        assert_eq!(settings.backup_frequency, "daily");
+        assert_eq!(settings.backup_keep, 7);
+        assert!(!settings.ocr_enabled);
+        assert_eq!(settings.ocr_language, "eng");
+        assert_eq!(settings.similar_images, "stack");
+        assert_eq!(settings.similar_image_threshold, 6);
+        assert!(settings.strip_image_metadata);
     }
 
     #[test]
@@ -0,0 +1,307 @@
+// Similar Images Module - Spots near-duplicate images by perceptual hash
+// Taking three screenshots of the same dialog a few pixels apart used to give
+// three unrelated items. Every captured image now stores a perceptual hash
+// (image_handler::perceptual_hash), and a capture within
+// `similar_image_threshold` bits of a recent image is handled per
+// `similar_images`:
+// - "stack": saved, and grouped with that image in the UI (stack_id)
+// - "merge": not saved; the earlier image moves to the top instead
+// - "off": saved as usual
+// find_similar_images lists the images close to a given one, hashing images
+// captured before hashes were stored along the way.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::db::items::{self, ClipboardItem};
+use crate::image_handler::{hash_distance, perceptual_hash};
+use crate::paths::{database_path, settings_path};
+use crate::settings::AppSettings;
+
+/// How many of the newest images a capture is compared with
+const RECENT_IMAGES: usize = 50;
+
+/// What to do with a capture that looks like a recent image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimilarMode {
+    Off,
+    Stack,
+    Merge,
+}
+
+impl SimilarMode {
+    /// Parse the `similar_images` setting; unknown values stack
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "off" => SimilarMode::Off,
+            "merge" => SimilarMode::Merge,
+            _ => SimilarMode::Stack,
+        }
+    }
+}
+
+/// Where a new capture goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placement {
+    /// A new item of its own
+    New,
+    /// A new item in this stack (id of the stack's first image)
+    Stack(i64),
+    /// No new item: the image at this path stands in for it
+    Merge(String),
+}
+
+/// An image close to the one asked about
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarImage {
+    pub item: ClipboardItem,
+    /// Differing hash bits (0 = looks identical)
+    pub distance: u32,
+}
+
+/// Hashes are stored as 16 hex digits
+pub fn format_hash(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+pub fn parse_hash(text: &str) -> Option<u64> {
+    u64::from_str_radix(text, 16).ok()
+}
+
+/// An active image item with a stored hash
+struct HashedImage {
+    id: i64,
+    stack_id: Option<i64>,
+    image_path: String,
+    hash: u64,
+}
+
+// ============================================
+// NEW CAPTURES
+// ============================================
+
+/// Newest active images that have a hash
+fn recent_hashed_images(conn: &Connection, limit: usize) -> Result<Vec<HashedImage>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, stack_id, image_path, image_phash FROM clipboard_items
+             WHERE is_image = 1 AND is_deleted = 0
+               AND image_path IS NOT NULL AND image_phash IS NOT NULL
+             ORDER BY timestamp DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| format!("Failed to query images: {}", e))?;
+    let rows = stmt
+        .query_map(params![limit as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get::<_, String>(3)?))
+        })
+        .map_err(|e| format!("Failed to query images: {}", e))?;
+
+    let mut images = Vec::new();
+    for row in rows {
+        let (id, stack_id, image_path, hash) = row.map_err(|e| format!("Failed to read images: {}", e))?;
+        if let Some(hash) = parse_hash(&hash) {
+            images.push(HashedImage { id, stack_id, image_path, hash });
+        }
+    }
+    Ok(images)
+}
+
+/// Where a capture with this hash goes, given the recent images
+pub fn place_capture(conn: &Connection, mode: SimilarMode, hash: u64, threshold: u32) -> Result<Placement, String> {
+    if mode == SimilarMode::Off {
+        return Ok(Placement::New);
+    }
+    // Closest match; the newest one among equally close matches
+    let closest = recent_hashed_images(conn, RECENT_IMAGES)?
+        .into_iter()
+        .map(|image| (hash_distance(hash, image.hash), image))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance);
+
+    Ok(match (closest, mode) {
+        (None, _) | (_, SimilarMode::Off) => Placement::New,
+        (Some((_, image)), SimilarMode::Stack) => Placement::Stack(image.stack_id.unwrap_or(image.id)),
+        (Some((_, image)), SimilarMode::Merge) => Placement::Merge(image.image_path),
+    })
+}
+
+/// place_capture with the user's settings; a new item if history can't be read
+pub fn placement_for(settings: &AppSettings, hash: u64) -> Placement {
+    let mode = SimilarMode::parse(&settings.similar_images);
+    if mode == SimilarMode::Off {
+        return Placement::New;
+    }
+    let placement = database_path()
+        .and_then(|path| items::open_read_only(&path))
+        .and_then(|conn| place_capture(&conn, mode, hash, settings.similar_image_threshold));
+    placement.unwrap_or_else(|e| {
+        println!("[CopyGum] Failed to compare image with history: {}", e);
+        Placement::New
+    })
+}
+
+// ============================================
+// FINDING SIMILAR IMAGES
+// ============================================
+
+/// Hash active images stored before hashes were recorded; returns how many
+/// were hashed (images whose file is gone are left as they are)
+pub fn backfill_hashes(conn: &Connection) -> Result<usize, String> {
+    let pending: Vec<(i64, String)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, image_path FROM clipboard_items
+                 WHERE is_image = 1 AND is_deleted = 0
+                   AND image_path IS NOT NULL AND image_phash IS NULL",
+            )
+            .map_err(|e| format!("Failed to query images: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| format!("Failed to query images: {}", e))?;
+        rows.collect::<rusqlite::Result<_>>()
+            .map_err(|e| format!("Failed to read images: {}", e))?
+    };
+
+    let mut hashed = 0;
+    for (id, image_path) in pending {
+        let Ok(img) = image::open(Path::new(&image_path)) else {
+            continue;
+        };
+        conn.execute(
+            "UPDATE clipboard_items SET image_phash = ?1 WHERE id = ?2",
+            params![format_hash(perceptual_hash(&img)), id],
+        )
+        .map_err(|e| format!("Failed to save image hash: {}", e))?;
+        hashed += 1;
+    }
+    Ok(hashed)
+}
+
+/// Active images within `threshold` bits of image `id`, closest first
+pub fn find_similar(conn: &Connection, id: i64, threshold: u32) -> Result<Vec<SimilarImage>, String> {
+    let hash: Option<String> = conn
+        .query_row(
+            "SELECT image_phash FROM clipboard_items WHERE id = ?1 AND is_image = 1 AND is_deleted = 0",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get image {}: {}", id, e))?
+        .ok_or_else(|| format!("Image {} not found", id))?;
+    let hash = hash
+        .as_deref()
+        .and_then(parse_hash)
+        .ok_or_else(|| format!("Image {} could not be read", id))?;
+
+    let mut similar = Vec::new();
+    for image in recent_hashed_images(conn, usize::MAX)? {
+        let distance = hash_distance(hash, image.hash);
+        if image.id == id || distance > threshold {
+            continue;
+        }
+        if let Some(item) = items::get_item(conn, image.id)? {
+            similar.push(SimilarImage { item, distance });
+        }
+    }
+    // Newest first among equally close images (the query order)
+    similar.sort_by_key(|image| image.distance);
+    Ok(similar)
+}
+
+// ============================================
+// TAURI COMMANDS
+// ============================================
+
+/// Images that look like image `id`; threshold defaults to the setting
+#[tauri::command]
+pub async fn find_similar_images(id: i64, threshold: Option<u32>) -> Result<Vec<SimilarImage>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let threshold = match threshold {
+            Some(threshold) => threshold,
+            None => AppSettings::load_from(&settings_path()?)?.similar_image_threshold,
+        };
+        let conn = items::open_read_write(&database_path()?)?;
+        let hashed = backfill_hashes(&conn)?;
+        if hashed > 0 {
+            println!("[CopyGum] Hashed {} older images", hashed);
+        }
+        find_similar(&conn, id, threshold)
+    })
+    .await
+    .map_err(|e| format!("Similar image search failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, Rgb, RgbImage};
+
+    fn insert_image(conn: &Connection, image_path: &str, hash: Option<u64>, stack_id: Option<i64>, timestamp: i64) -> i64 {
+        conn.execute(
+            "INSERT INTO clipboard_items
+             (content, content_type, category, is_image, image_path, image_phash, stack_id, timestamp)
+             VALUES ('', 'image', 'image', 1, ?1, ?2, ?3, ?4)",
+            params![image_path, hash.map(format_hash), stack_id, timestamp],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    #[test]
+    fn test_place_capture() {
+        let conn = items::test_connection();
+        let first = insert_image(&conn, "/images/1.png", Some(0xFFFF_0000_FFFF_0000), None, 1000);
+        insert_image(&conn, "/images/2.png", Some(0xFFFF_0000_FFFF_0003), Some(first), 2000);
+        insert_image(&conn, "/images/3.png", Some(0x1234_5678_9ABC_DEF0), None, 3000);
+
+        // Two bits from the second capture, four from the first: joins their stack
+        let hash = 0xFFFF_0000_FFFF_000F;
+        assert_eq!(place_capture(&conn, SimilarMode::Stack, hash, 6).unwrap(), Placement::Stack(first));
+        assert_eq!(
+            place_capture(&conn, SimilarMode::Merge, hash, 6).unwrap(),
+            Placement::Merge("/images/2.png".to_string())
+        );
+        assert_eq!(place_capture(&conn, SimilarMode::Stack, hash, 1).unwrap(), Placement::New);
+        assert_eq!(place_capture(&conn, SimilarMode::Off, hash, 6).unwrap(), Placement::New);
+
+        // Trashed images don't count
+        conn.execute("UPDATE clipboard_items SET is_deleted = 1 WHERE id <> 3", []).unwrap();
+        assert_eq!(place_capture(&conn, SimilarMode::Stack, hash, 6).unwrap(), Placement::New);
+    }
+
+    #[test]
+    fn test_find_similar_hashes_older_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let save = |name: &str, img: RgbImage| {
+            let path = dir.path().join(name);
+            DynamicImage::ImageRgb8(img).save(&path).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let window = |shift: u32| {
+            RgbImage::from_fn(120, 90, |x, y| {
+                if y < 15 + shift || (x > 60 + shift && y > 50) {
+                    Rgb([30, 30, 40])
+                } else {
+                    Rgb([240, 240, 240])
+                }
+            })
+        };
+        let gradient = RgbImage::from_fn(120, 90, |x, _| Rgb([(255 - x * 2) as u8, 0, (x * 2) as u8]));
+
+        let conn = items::test_connection();
+        let original = insert_image(&conn, &save("a.png", window(0)), None, None, 1000);
+        let shifted = insert_image(&conn, &save("b.png", window(1)), None, None, 2000);
+        insert_image(&conn, &save("c.png", gradient), None, None, 3000);
+        insert_image(&conn, "/missing/d.png", None, None, 4000);
+
+        assert_eq!(backfill_hashes(&conn).unwrap(), 3);
+        let similar = find_similar(&conn, original, 10).unwrap();
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].item.id, shifted);
+
+        assert!(find_similar(&conn, 99, 10).is_err());
+    }
+}
@@ -0,0 +1,500 @@
+// Single Instance Module - One running CopyGum per user
+// The first instance holds an exclusive lock on copygum.lock in the app data
+// dir and listens on a local socket (Unix socket, or localhost TCP with the
+// port in copygum.port on Windows). Localhost TCP is open to every local
+// process, so on Windows a connection must first send the token the primary
+// instance wrote to copygum.token. A second launch forwards its arguments
+// there as one JSON line and exits, so `copygum --toggle` can be bound to a
+// desktop hotkey where global shortcuts don't work (Wayland). The CLI (cli.rs)
+// uses the same channel for actions that need the running app.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{BufRead, BufReader, Write};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::autostart::AUTOSTART_ARG;
+use crate::clipboard_monitor;
+use crate::db::items;
+use crate::paths::{app_data_dir, database_path};
+use crate::window_manager;
+
+const LOCK_FILE: &str = "copygum.lock";
+#[cfg(unix)]
+const SOCKET_FILE: &str = "copygum.sock";
+#[cfg(windows)]
+const PORT_FILE: &str = "copygum.port";
+#[cfg(windows)]
+const TOKEN_FILE: &str = "copygum.token";
+#[cfg(windows)]
+const TOKEN_BYTES: usize = 32;
+
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECT_RETRIES: u32 = 10;
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Message a second launch sends to the running instance
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForwardRequest {
+    pub args: Vec<String>,
+}
+
+/// Reply from the running instance
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForwardResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Actions that can be requested on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceCommand {
+    Toggle,
+    Show,
+    Hide,
+    Pause,
+    PauseFor(u32),
+    Resume,
+    Copy(i64),
+    Pin(i64),
+    Unpin(i64),
+    Delete(i64),
+}
+
+/// Held by the primary instance for as long as it runs
+pub struct InstanceLock {
+    // Dropping the file releases the lock
+    _file: File,
+    #[cfg(unix)]
+    listener: std::os::unix::net::UnixListener,
+    #[cfg(windows)]
+    listener: std::net::TcpListener,
+    /// Expected as the first line of every connection
+    #[cfg(windows)]
+    token: String,
+}
+
+/// Map command line arguments to instance commands, ignoring unknown ones
+///
+/// `--copy`, `--pin`, `--unpin` and `--delete` take an item id; `--pause`
+/// takes an optional number of minutes.
+pub fn parse_args(args: &[String]) -> Vec<InstanceCommand> {
+    let mut commands = Vec::new();
+    let mut iter = args.iter().peekable();
+
+    while let Some(arg) = iter.next() {
+        let command = match arg.as_str() {
+            "--toggle" => Some(InstanceCommand::Toggle),
+            "--show" => Some(InstanceCommand::Show),
+            "--hide" => Some(InstanceCommand::Hide),
+            "--pause" => match iter.peek().and_then(|value| value.parse().ok()) {
+                Some(minutes) => {
+                    iter.next();
+                    Some(InstanceCommand::PauseFor(minutes))
+                }
+                None => Some(InstanceCommand::Pause),
+            },
+            "--resume" => Some(InstanceCommand::Resume),
+            "--copy" | "--pin" | "--unpin" | "--delete" => {
+                match iter.peek().and_then(|value| value.parse().ok()) {
+                    Some(id) => {
+                        iter.next();
+                        Some(match arg.as_str() {
+                            "--copy" => InstanceCommand::Copy(id),
+                            "--pin" => InstanceCommand::Pin(id),
+                            "--unpin" => InstanceCommand::Unpin(id),
+                            _ => InstanceCommand::Delete(id),
+                        })
+                    }
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        commands.extend(command);
+    }
+
+    commands
+}
+
+// ============================================
+// ACQUIRE / FORWARD
+// ============================================
+
+/// Become the primary instance, or forward `args` to the one already running
+///
+/// Returns `Ok(None)` when another instance is running and this process
+/// should exit.
+pub fn acquire(args: &[String]) -> Result<Option<InstanceLock>, String> {
+    let dir = app_data_dir()?;
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(LOCK_FILE))
+        .map_err(|e| format!("Failed to open lock file: {}", e))?;
+
+    match file.try_lock() {
+        Ok(()) => {
+            #[cfg(unix)]
+            let listener = bind_listener(&dir)?;
+            #[cfg(windows)]
+            let (listener, token) = bind_listener(&dir)?;
+            Ok(Some(InstanceLock {
+                _file: file,
+                listener,
+                #[cfg(windows)]
+                token,
+            }))
+        }
+        Err(TryLockError::WouldBlock) => {
+            // Never fall through to a second monitor, even if forwarding fails
+            match forward(args) {
+                Ok(()) => println!("[CopyGum] Already running, arguments forwarded"),
+                Err(e) => eprintln!("{}", e),
+            }
+            Ok(None)
+        }
+        Err(TryLockError::Error(e)) => Err(format!("Failed to lock instance file: {}", e)),
+    }
+}
+
+/// Whether another process holds the instance lock
+pub fn is_running() -> bool {
+    let Ok(dir) = app_data_dir() else {
+        return false;
+    };
+    let Ok(file) = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(LOCK_FILE))
+    else {
+        return false;
+    };
+    // Taking the lock here is harmless: it is released when `file` drops
+    matches!(file.try_lock(), Err(TryLockError::WouldBlock))
+}
+
+/// Hand arguments to the running instance and report whether they succeeded
+pub fn forward(args: &[String]) -> Result<(), String> {
+    match send_request::<_, ForwardResponse>(&ForwardRequest { args: args.to_vec() })? {
+        ForwardResponse { ok: true, .. } => Ok(()),
+        ForwardResponse { error, .. } => Err(format!(
+            "Running instance failed: {}",
+            error.unwrap_or_default()
+        )),
+    }
+}
+
+#[cfg(unix)]
+fn bind_listener(dir: &std::path::Path) -> Result<std::os::unix::net::UnixListener, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join(SOCKET_FILE);
+    // We hold the lock, so any socket file left over is from a crashed run
+    let _ = std::fs::remove_file(&path);
+
+    let listener = std::os::unix::net::UnixListener::bind(&path)
+        .map_err(|e| format!("Failed to bind instance socket: {}", e))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict instance socket: {}", e))?;
+
+    Ok(listener)
+}
+
+/// Listen on a localhost port and write a fresh token for this run
+///
+/// The token file sits in the user's app data dir, which other users can't
+/// read, so only this user's processes can talk to the running instance.
+#[cfg(windows)]
+fn bind_listener(dir: &std::path::Path) -> Result<(std::net::TcpListener, String), String> {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate instance token: {}", e))?;
+    let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    // Written before the port, so a client that finds the port finds the token
+    std::fs::write(dir.join(TOKEN_FILE), &token)
+        .map_err(|e| format!("Failed to write token file: {}", e))?;
+
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))
+        .map_err(|e| format!("Failed to bind instance port: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read instance port: {}", e))?
+        .port();
+    std::fs::write(dir.join(PORT_FILE), port.to_string())
+        .map_err(|e| format!("Failed to write port file: {}", e))?;
+
+    Ok((listener, token))
+}
+
+/// Send a request to the running instance and wait for its reply
+pub fn send_request<T: Serialize, R: for<'de> Deserialize<'de>>(request: &T) -> Result<R, String> {
+    // The running instance may still be starting up; give it a moment
+    let mut attempts = 0;
+    let stream = loop {
+        match connect() {
+            Ok(stream) => break stream,
+            Err(_) if attempts < CONNECT_RETRIES => {
+                attempts += 1;
+                std::thread::sleep(CONNECT_RETRY_DELAY);
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    stream
+        .set_read_timeout(Some(FORWARD_TIMEOUT))
+        .map_err(|e| format!("Failed to configure instance connection: {}", e))?;
+
+    let mut line = serde_json::to_string(request)
+        .map_err(|e| format!("Failed to serialize request: {}", e))?;
+    line.push('\n');
+    (&stream)
+        .write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to send request to running instance: {}", e))?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .map_err(|e| format!("Failed to read reply from running instance: {}", e))?;
+
+    serde_json::from_str(&reply).map_err(|e| format!("Invalid reply from running instance: {}", e))
+}
+
+#[cfg(unix)]
+fn connect() -> Result<std::os::unix::net::UnixStream, String> {
+    let path = app_data_dir()?.join(SOCKET_FILE);
+    std::os::unix::net::UnixStream::connect(path)
+        .map_err(|e| format!("Failed to connect to running instance: {}", e))
+}
+
+/// Connect to the running instance's port and send its token
+#[cfg(windows)]
+fn connect() -> Result<std::net::TcpStream, String> {
+    let dir = app_data_dir()?;
+    let port: u16 = std::fs::read_to_string(dir.join(PORT_FILE))
+        .map_err(|e| format!("Failed to read port file: {}", e))?
+        .trim()
+        .parse()
+        .map_err(|e| format!("Invalid port file: {}", e))?;
+    let token = std::fs::read_to_string(dir.join(TOKEN_FILE))
+        .map_err(|e| format!("Failed to read token file: {}", e))?;
+
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    let mut stream = std::net::TcpStream::connect_timeout(&addr, FORWARD_TIMEOUT)
+        .map_err(|e| format!("Failed to connect to running instance: {}", e))?;
+    writeln!(stream, "{}", token.trim())
+        .map_err(|e| format!("Failed to send request to running instance: {}", e))?;
+    Ok(stream)
+}
+
+// ============================================
+// PRIMARY INSTANCE
+// ============================================
+
+/// Accept forwarded arguments for the lifetime of the app
+pub fn listen(app: AppHandle, lock: InstanceLock) {
+    std::thread::spawn(move || {
+        // Move the whole lock in, not just the listener, so the file stays locked
+        let lock = lock;
+        #[cfg(unix)]
+        let token = None;
+        #[cfg(windows)]
+        let token = Some(lock.token.as_str());
+        for stream in lock.listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    // Connections are handled one at a time; don't let one stall the rest
+                    let _ = stream.set_read_timeout(Some(FORWARD_TIMEOUT));
+                    handle_connection(&app, stream, token);
+                }
+                Err(e) => println!("[CopyGum] Instance connection failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Read one request and reply; with a `token`, it must be the first line
+fn handle_connection<S>(app: &AppHandle, stream: S, token: Option<&str>)
+where
+    for<'a> &'a S: std::io::Read + Write,
+{
+    let mut reader = BufReader::new(&stream);
+    if let Some(token) = token {
+        let mut given = String::new();
+        if reader.read_line(&mut given).is_err() || !token_matches(given.trim(), token) {
+            println!("[CopyGum] Rejected instance connection without a valid token");
+            return;
+        }
+    }
+
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+
+    let response = match serde_json::from_str::<ForwardRequest>(&line) {
+        Ok(request) => {
+            println!("[CopyGum] Arguments from second launch: {:?}", request.args);
+            match handle_forwarded_args(app, &request.args) {
+                Ok(()) => ForwardResponse { ok: true, error: None },
+                Err(e) => ForwardResponse { ok: false, error: Some(e) },
+            }
+        }
+        Err(e) => ForwardResponse {
+            ok: false,
+            error: Some(format!("Invalid request: {}", e)),
+        },
+    };
+
+    if let Ok(mut reply) = serde_json::to_string(&response) {
+        reply.push('\n');
+        let _ = (&stream).write_all(reply.as_bytes());
+    }
+}
+
+/// Compare tokens without returning early on the first differing byte
+pub fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// A plain second launch brings the window up
+fn handle_forwarded_args(app: &AppHandle, args: &[String]) -> Result<(), String> {
+    let commands = parse_args(args);
+    if commands.is_empty() && !args.iter().any(|arg| arg == AUTOSTART_ARG) {
+        return run_command(app, InstanceCommand::Show);
+    }
+    commands.into_iter().try_for_each(|command| run_command(app, command))
+}
+
+/// Apply the commands from this process's own command line
+pub fn handle_startup_args(app: &AppHandle, args: &[String]) {
+    for command in parse_args(args) {
+        if let Err(e) = run_command(app, command) {
+            println!("[CopyGum] Failed to run {:?}: {}", command, e);
+        }
+    }
+}
+
+fn run_command(app: &AppHandle, command: InstanceCommand) -> Result<(), String> {
+    match command {
+        InstanceCommand::Pause => tauri::async_runtime::block_on(
+            clipboard_monitor::pause_clipboard_monitoring(app.clone()),
+        ),
+        InstanceCommand::PauseFor(minutes) => tauri::async_runtime::block_on(
+            clipboard_monitor::pause_clipboard_monitoring_for(app.clone(), minutes),
+        ),
+        InstanceCommand::Resume => tauri::async_runtime::block_on(
+            clipboard_monitor::resume_clipboard_monitoring(app.clone()),
+        ),
+        InstanceCommand::Copy(id) => {
+            tauri::async_runtime::block_on(clipboard_monitor::copy_history_item(app, id))
+        }
+        InstanceCommand::Pin(id) => update_item(app, id, |conn| items::set_pinned(conn, id, true)),
+        InstanceCommand::Unpin(id) => update_item(app, id, |conn| items::set_pinned(conn, id, false)),
+        InstanceCommand::Delete(id) => update_item(app, id, |conn| items::soft_delete(conn, id)),
+        // Window changes touch AppKit/Win32 state and must happen on the main thread
+        _ => {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let handle = app.clone();
+            app.run_on_main_thread(move || {
+                let _ = sender.send(run_window_command(&handle, command));
+            })
+            .map_err(|e| e.to_string())?;
+            receiver
+                .recv_timeout(FORWARD_TIMEOUT)
+                .map_err(|e| format!("Window command did not finish: {}", e))?
+        }
+    }
+}
+
+fn run_window_command(app: &AppHandle, command: InstanceCommand) -> Result<(), String> {
+    match command {
+        InstanceCommand::Toggle => window_manager::toggle_window(app.clone()),
+        InstanceCommand::Show => {
+            let visible = app
+                .get_webview_window("main")
+                .and_then(|window| window.is_visible().ok())
+                .unwrap_or(false);
+            if visible {
+                Ok(())
+            } else {
+                window_manager::show_window_activated(app.clone())
+            }
+        }
+        InstanceCommand::Hide => window_manager::hide_window(app.clone()),
+        _ => Ok(()),
+    }
+}
+
+/// Change an item in the database and tell the frontend to reload
+fn update_item(
+    app: &AppHandle,
+    id: i64,
+    update: impl FnOnce(&rusqlite::Connection) -> Result<bool, String>,
+) -> Result<(), String> {
+    let conn = items::open_read_write(&database_path()?)?;
+    if !update(&conn)? {
+        return Err(format!("Item {} not found", id));
+    }
+    let _ = app.emit("items-changed", id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["--toggle", "--pause"])),
+            vec![InstanceCommand::Toggle, InstanceCommand::Pause]
+        );
+        assert_eq!(parse_args(&args(&["--resume", "--show", "--hide"])), vec![
+            InstanceCommand::Resume,
+            InstanceCommand::Show,
+            InstanceCommand::Hide,
+        ]);
+    }
+
+    #[test]
+    fn test_parse_args_with_values() {
+        assert_eq!(
+            parse_args(&args(&["--copy", "42", "--pin", "7", "--pause", "15"])),
+            vec![
+                InstanceCommand::Copy(42),
+                InstanceCommand::Pin(7),
+                InstanceCommand::PauseFor(15),
+            ]
+        );
+        // A bare --pause stays indefinite; a missing id drops the command
+        assert_eq!(
+            parse_args(&args(&["--pause", "--delete", "--unpin", "3"])),
+            vec![InstanceCommand::Pause, InstanceCommand::Unpin(3)]
+        );
+    }
+
+    #[test]
+    fn test_parse_args_ignores_unknown_and_autostart() {
+        assert!(parse_args(&args(&["--autostart", "--verbose", "file.txt"])).is_empty());
+    }
+
+    #[test]
+    fn test_forward_request_wire_format() {
+        let request = ForwardRequest { args: args(&["--toggle"]) };
+        assert_eq!(serde_json::to_string(&request).unwrap(), r#"{"args":["--toggle"]}"#);
+
+        let response: ForwardResponse = serde_json::from_str(r#"{"ok":true}"#).unwrap();
+        assert!(response.ok);
+        assert!(response.error.is_none());
+    }
+}
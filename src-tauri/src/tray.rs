@@ -0,0 +1,511 @@
+// Tray Module - System tray icon with quick actions and recent items
+// The menu shows the monitoring state (with pause/resume), the last captured
+// items (click to copy), and entries to clear history, open settings and quit.
+// Recent items are loaded from the database at startup, then kept up to date
+// from the monitor's events. Passwords, API keys and private items are listed
+// by category only. The icon dims while paused.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use tauri::image::Image;
+use tauri::menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Listener, Manager, Wry};
+
+use crate::clipboard_monitor;
+use crate::db::items::{self, ClipboardItem, SENSITIVE_CATEGORIES};
+use crate::window_manager;
+
+const TRAY_ID: &str = "main";
+const MAX_RECENT_ITEMS: usize = 10;
+const LABEL_MAX_CHARS: usize = 40;
+
+// Menu item ids
+const MENU_TOGGLE_MONITORING: &str = "toggle-monitoring";
+const MENU_CLEAR_HISTORY: &str = "clear-history";
+const MENU_OPEN_SETTINGS: &str = "open-settings";
+const MENU_QUIT: &str = "quit";
+const MENU_RECENT_PREFIX: &str = "recent-";
+
+/// A captured item as shown in the tray menu
+#[derive(Debug, Clone, PartialEq)]
+struct RecentItem {
+    content: String,
+    image_path: Option<String>,
+    label: String,
+}
+
+/// The fields of the monitor's `clipboard-changed` event the tray needs
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClipboardChanged {
+    content: String,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    is_image: bool,
+    image_path: Option<String>,
+    image_width: Option<u32>,
+    image_height: Option<u32>,
+}
+
+/// Payload of the monitor's `monitoring-state-changed` event
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitoringStateChanged {
+    is_running: bool,
+}
+
+/// Tray state shared between event handlers
+pub struct TrayState {
+    recent: Mutex<VecDeque<RecentItem>>,
+    monitoring: AtomicBool,
+    icon: Option<Image<'static>>,
+    paused_icon: Option<Image<'static>>,
+}
+
+impl TrayState {
+    fn new(icon: Option<Image<'static>>) -> Self {
+        let paused_icon = icon.as_ref().map(|icon| {
+            Image::new_owned(paused_rgba(icon.rgba()), icon.width(), icon.height())
+        });
+
+        Self {
+            recent: Mutex::new(VecDeque::new()),
+            monitoring: AtomicBool::new(false),
+            icon,
+            paused_icon,
+        }
+    }
+
+    /// Add an item to the top, moving it up if it was already in the list
+    fn push_recent(&self, item: RecentItem) {
+        if let Ok(mut recent) = self.recent.lock() {
+            recent.retain(|existing| {
+                existing.content != item.content || existing.image_path != item.image_path
+            });
+            recent.push_front(item);
+            recent.truncate(MAX_RECENT_ITEMS);
+        }
+    }
+
+    fn clear_recent(&self) {
+        if let Ok(mut recent) = self.recent.lock() {
+            recent.clear();
+        }
+    }
+
+    fn recent_items(&self) -> Vec<RecentItem> {
+        self.recent
+            .lock()
+            .map(|recent| recent.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn recent_item(&self, index: usize) -> Option<RecentItem> {
+        self.recent.lock().ok()?.get(index).cloned()
+    }
+
+    fn is_monitoring(&self) -> bool {
+        self.monitoring.load(Ordering::SeqCst)
+    }
+}
+
+// ============================================
+// SETUP
+// ============================================
+
+/// Create the tray icon and subscribe it to monitor events
+pub fn setup_tray(app: &AppHandle) -> Result<(), String> {
+    let icon = app.default_window_icon().map(|icon| icon.clone().to_owned());
+    app.manage(TrayState::new(icon.clone()));
+
+    let state = app.state::<TrayState>();
+    if let Err(e) = load_recent_items(&state) {
+        println!("[CopyGum] Failed to load recent items for the tray: {}", e);
+    }
+    let menu = build_menu(app, &state).map_err(|e| format!("Failed to build tray menu: {}", e))?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip(tooltip(false))
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            // Left click toggles the overlay; the menu stays on right click
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                let _ = window_manager::toggle_window(tray.app_handle().clone());
+            }
+        });
+
+    if let Some(icon) = state.paused_icon.clone() {
+        builder = builder.icon(icon);
+    }
+
+    builder
+        .build(app)
+        .map_err(|e| format!("Failed to create tray icon: {}", e))?;
+
+    // Keep the recent items in sync with what the monitor captures
+    let handle = app.clone();
+    app.listen("clipboard-changed", move |event| {
+        if let Ok(payload) = serde_json::from_str::<ClipboardChanged>(event.payload()) {
+            handle.state::<TrayState>().push_recent(recent_item_from_event(payload));
+            refresh_tray(&handle);
+        }
+    });
+
+    let handle = app.clone();
+    app.listen("monitoring-state-changed", move |event| {
+        if let Ok(payload) = serde_json::from_str::<MonitoringStateChanged>(event.payload()) {
+            handle.state::<TrayState>().monitoring.store(payload.is_running, Ordering::SeqCst);
+            refresh_tray(&handle);
+        }
+    });
+
+    println!("[CopyGum] Tray icon created");
+    Ok(())
+}
+
+// ============================================
+// MENU
+// ============================================
+
+fn build_menu(app: &AppHandle, state: &TrayState) -> tauri::Result<Menu<Wry>> {
+    let running = state.is_monitoring();
+
+    let status = MenuItem::with_id(
+        app,
+        "status",
+        if running { "Monitoring clipboard" } else { "Monitoring paused" },
+        false,
+        None::<&str>,
+    )?;
+    let toggle = MenuItem::with_id(
+        app,
+        MENU_TOGGLE_MONITORING,
+        if running { "Pause monitoring" } else { "Resume monitoring" },
+        true,
+        None::<&str>,
+    )?;
+
+    let recent: Vec<MenuItem<Wry>> = state
+        .recent_items()
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            MenuItem::with_id(
+                app,
+                format!("{}{}", MENU_RECENT_PREFIX, index),
+                escape_menu_text(&item.label),
+                true,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<_>>()?;
+    let no_recent = MenuItem::with_id(app, "no-recent", "No recent items", false, None::<&str>)?;
+
+    let clear = MenuItem::with_id(app, MENU_CLEAR_HISTORY, "Clear history", true, None::<&str>)?;
+    let settings = MenuItem::with_id(app, MENU_OPEN_SETTINGS, "Open settings", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, MENU_QUIT, "Quit CopyGum", true, None::<&str>)?;
+
+    let separators = [
+        PredefinedMenuItem::separator(app)?,
+        PredefinedMenuItem::separator(app)?,
+        PredefinedMenuItem::separator(app)?,
+    ];
+
+    let mut entries: Vec<&dyn IsMenuItem<Wry>> = vec![&status, &toggle, &separators[0]];
+    if recent.is_empty() {
+        entries.push(&no_recent);
+    } else {
+        entries.extend(recent.iter().map(|item| item as &dyn IsMenuItem<Wry>));
+    }
+    entries.extend([
+        &separators[1] as &dyn IsMenuItem<Wry>,
+        &clear,
+        &settings,
+        &separators[2],
+        &quit,
+    ]);
+
+    Menu::with_items(app, &entries)
+}
+
+/// Rebuild the menu and swap the icon to match the current state
+fn refresh_tray(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let state = app.state::<TrayState>();
+
+    match build_menu(app, &state) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => println!("[CopyGum] Failed to rebuild tray menu: {}", e),
+    }
+
+    let running = state.is_monitoring();
+    let icon = if running { state.icon.clone() } else { state.paused_icon.clone() };
+    if icon.is_some() {
+        let _ = tray.set_icon(icon);
+    }
+    let _ = tray.set_tooltip(Some(tooltip(running)));
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        MENU_TOGGLE_MONITORING => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let result = if app.state::<TrayState>().is_monitoring() {
+                    clipboard_monitor::pause_clipboard_monitoring(app.clone()).await
+                } else {
+                    clipboard_monitor::resume_clipboard_monitoring(app.clone()).await
+                };
+                if let Err(e) = result {
+                    println!("[CopyGum] Failed to toggle monitoring from tray: {}", e);
+                }
+            });
+        }
+        MENU_CLEAR_HISTORY => {
+            // History lives in the frontend's database; let it do the delete
+            app.state::<TrayState>().clear_recent();
+            refresh_tray(app);
+            let _ = app.emit("tray-clear-history", ());
+        }
+        MENU_OPEN_SETTINGS => {
+            if let Err(e) = window_manager::show_window_activated(app.clone()) {
+                println!("[CopyGum] Failed to show window from tray: {}", e);
+            }
+            let _ = app.emit("tray-open-settings", ());
+        }
+        MENU_QUIT => app.exit(0),
+        _ => {
+            if let Some(index) = id.strip_prefix(MENU_RECENT_PREFIX).and_then(|i| i.parse().ok()) {
+                copy_recent_item(app, index);
+            }
+        }
+    }
+}
+
+fn copy_recent_item(app: &AppHandle, index: usize) {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let Some(item) = app.state::<TrayState>().recent_item(index) else {
+        return;
+    };
+
+    match item.image_path {
+        Some(path) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = clipboard_monitor::copy_image_to_clipboard(app, path).await {
+                    println!("[CopyGum] Failed to copy image from tray: {}", e);
+                }
+            });
+        }
+        None => {
+            if let Err(e) = app.clipboard().write_text(item.content) {
+                println!("[CopyGum] Failed to copy item from tray: {}", e);
+            }
+        }
+    }
+}
+
+// ============================================
+// HELPERS
+// ============================================
+
+fn tooltip(running: bool) -> &'static str {
+    if running {
+        "CopyGum"
+    } else {
+        "CopyGum (paused)"
+    }
+}
+
+/// Fill the recent list from history, so it isn't empty after a restart
+fn load_recent_items(state: &TrayState) -> Result<(), String> {
+    let conn = items::open_read_only(&crate::paths::database_path()?)?;
+    // Oldest first, since each push goes to the top
+    for item in items::recent_items(&conn, MAX_RECENT_ITEMS)?.iter().rev() {
+        state.push_recent(recent_item_from_history(item));
+    }
+    Ok(())
+}
+
+fn recent_item_from_event(payload: ClipboardChanged) -> RecentItem {
+    let label = item_label(
+        &payload.content,
+        &payload.category,
+        payload.is_image,
+        payload.image_width.zip(payload.image_height),
+    );
+
+    RecentItem {
+        content: payload.content,
+        image_path: payload.image_path.filter(|_| payload.is_image),
+        label,
+    }
+}
+
+fn recent_item_from_history(item: &ClipboardItem) -> RecentItem {
+    let size = item.image_width.zip(item.image_height).map(|(width, height)| (width as u32, height as u32));
+
+    RecentItem {
+        content: item.content.clone(),
+        image_path: item.image_path.clone().filter(|_| item.is_image),
+        label: item_label(&item.content, &item.category, item.is_image, size),
+    }
+}
+
+/// Menu label for an item; sensitive items show their category, not their content
+fn item_label(content: &str, category: &str, is_image: bool, size: Option<(u32, u32)>) -> String {
+    if is_image {
+        return match size {
+            Some((width, height)) => format!("Image {}×{}", width, height),
+            None => "Image".to_string(),
+        };
+    }
+
+    if SENSITIVE_CATEGORIES.contains(&category) {
+        let name = match category {
+            "password" => "Password",
+            "apikey" => "API key",
+            _ => "Private item",
+        };
+        return format!("{} ••••••••", name);
+    }
+
+    menu_label(content)
+}
+
+/// Single-line, length-limited preview of clipboard text
+fn menu_label(content: &str) -> String {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+    let line: String = line.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if line.chars().count() > LABEL_MAX_CHARS {
+        let truncated: String = line.chars().take(LABEL_MAX_CHARS - 1).collect();
+        format!("{}…", truncated.trim_end())
+    } else {
+        line
+    }
+}
+
+/// Windows menus treat `&` as a mnemonic marker
+fn escape_menu_text(text: &str) -> String {
+    if cfg!(target_os = "windows") {
+        text.replace('&', "&&")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Greyscale, half-transparent copy of an RGBA icon for the paused state
+fn paused_rgba(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|px| {
+            let luma = (0.299 * px[0] as f32 + 0.587 * px[1] as f32 + 0.114 * px[2] as f32).round() as u8;
+            [luma, luma, luma, px[3] / 2]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_item(content: &str) -> RecentItem {
+        RecentItem {
+            content: content.to_string(),
+            image_path: None,
+            label: menu_label(content),
+        }
+    }
+
+    #[test]
+    fn test_menu_label_uses_first_non_empty_line() {
+        assert_eq!(menu_label("\n\n  hello   world  \nsecond line"), "hello world");
+        assert_eq!(menu_label("   "), "");
+    }
+
+    #[test]
+    fn test_menu_label_truncates_on_char_boundary() {
+        let label = menu_label(&"é".repeat(100));
+        assert_eq!(label.chars().count(), LABEL_MAX_CHARS);
+        assert!(label.ends_with('…'));
+    }
+
+    #[test]
+    fn test_recent_items_are_limited_and_deduplicated() {
+        let state = TrayState::new(None);
+        for i in 0..15 {
+            state.push_recent(text_item(&format!("item {}", i)));
+        }
+        state.push_recent(text_item("item 10"));
+
+        let items = state.recent_items();
+        assert_eq!(items.len(), MAX_RECENT_ITEMS);
+        assert_eq!(items[0].content, "item 10");
+        assert_eq!(items[1].content, "item 14");
+        assert_eq!(items.iter().filter(|item| item.content == "item 10").count(), 1);
+    }
+
+    #[test]
+    fn test_image_event_becomes_image_item() {
+        let payload: ClipboardChanged = serde_json::from_str(
+            r#"{"content":"","isImage":true,"imagePath":"/tmp/a.png","imageWidth":640,"imageHeight":480}"#,
+        )
+        .unwrap();
+        let item = recent_item_from_event(payload);
+
+        assert_eq!(item.label, "Image 640×480");
+        assert_eq!(item.image_path.as_deref(), Some("/tmp/a.png"));
+    }
+
+    #[test]
+    fn test_sensitive_items_are_masked() {
+        let payload: ClipboardChanged =
+            serde_json::from_str(r#"{"content":"hunter2","category":"password"}"#).unwrap();
+        let item = recent_item_from_event(payload);
+        assert_eq!(item.label, "Password ••••••••");
+        assert_eq!(item.content, "hunter2");
+
+        let conn = items::test_connection();
+        items::add_text_item(&conn, "sk-live-123", "text", "apikey", None).unwrap();
+        items::add_text_item(&conn, "hello", "text", "text", None).unwrap();
+        let labels: Vec<_> = items::recent_items(&conn, MAX_RECENT_ITEMS)
+            .unwrap()
+            .iter()
+            .map(|item| recent_item_from_history(item).label)
+            .collect();
+        assert!(labels.contains(&"API key ••••••••".to_string()));
+        assert!(labels.contains(&"hello".to_string()));
+        assert!(labels.iter().all(|label| !label.contains("sk-live")));
+    }
+
+    #[test]
+    fn test_paused_icon_is_grey_and_dimmed() {
+        let rgba = paused_rgba(&[255, 0, 0, 255, 10, 20, 30, 0]);
+        assert_eq!(rgba.len(), 8);
+        assert_eq!(rgba[0], rgba[1]);
+        assert_eq!(rgba[1], rgba[2]);
+        assert_eq!(rgba[3], 127);
+        assert_eq!(rgba[7], 0);
+    }
+}
@@ -1,5 +1,17 @@
 // Window Manager - Handles global shortcuts and window visibility
-use tauri::{AppHandle, Manager, PhysicalPosition};
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+use crate::app_detector::{activate_window, get_frontmost_window, get_frontmost_window_bounds, FrontmostWindow};
+#[cfg(target_os = "macos")]
+use crate::geometry::dock_from_insets;
+use crate::geometry::{compute_window_rect, monitor_at_point, DisplayInfo, Edge, PlacementPrefs, Rect};
+#[cfg(target_os = "linux")]
+use crate::geometry::expand_by_frame_extents;
+#[cfg(target_os = "linux")]
+use crate::linux_placement;
+use crate::settings::AppSettings;
 
 // Allow deprecated cocoa APIs until migration to objc2
 #[allow(deprecated)]
@@ -15,6 +27,9 @@ use cocoa::base::{id, NO};
 #[cfg(target_os = "macos")]
 const OVERLAY_WINDOW_LEVEL: i64 = 25;
 
+// Window that had focus before the overlay opened, handed back on hide
+static PREVIOUS_FOCUS: Mutex<Option<FrontmostWindow>> = Mutex::new(None);
+
 #[allow(deprecated)]
 #[tauri::command]
 pub fn toggle_window(app: AppHandle) -> Result<(), String> {
@@ -24,13 +39,16 @@ pub fn toggle_window(app: AppHandle) -> Result<(), String> {
         match window.is_visible() {
             Ok(true) => {
                 println!("[CopyGum] Window is visible, hiding...");
-                window.hide().map_err(|e| e.to_string())?;
+                hide_overlay(&window)?;
             }
             Ok(false) => {
                 println!("[CopyGum] Window is hidden, showing...");
 
-                // Position window at bottom of screen
-                position_window_right(&window)?;
+                // Remember where the user was typing before we take focus
+                remember_focus();
+
+                // Position window on the monitor the user is working on
+                position_overlay_window(&window)?;
 
                 // Show window first using Tauri
                 window.show().map_err(|e| e.to_string())?;
@@ -122,8 +140,7 @@ pub fn toggle_window(app: AppHandle) -> Result<(), String> {
 #[tauri::command]
 pub fn hide_window(app: AppHandle) -> Result<(), String> {
     if let Some(window) = app.get_webview_window("main") {
-        window.hide().map_err(|e| e.to_string())?;
-        Ok(())
+        hide_overlay(&window)
     } else {
         Err("Main window not found".to_string())
     }
@@ -137,8 +154,12 @@ pub fn show_window_activated(app: AppHandle) -> Result<(), String> {
     println!("[CopyGum] show_window_activated called (first run)");
 
     if let Some(window) = app.get_webview_window("main") {
-        // Position window at bottom of screen
-        position_window_right(&window)?;
+        if !window.is_visible().unwrap_or(false) {
+            remember_focus();
+        }
+
+        // Position window on the monitor the user is working on
+        position_overlay_window(&window)?;
 
         // Show window
         window.show().map_err(|e| e.to_string())?;
@@ -204,189 +225,229 @@ pub fn show_window_activated(app: AppHandle) -> Result<(), String> {
     }
 }
 
-fn position_window_right(window: &tauri::WebviewWindow) -> Result<(), String> {
-    // Get primary monitor
-    if let Some(monitor) = window.current_monitor().map_err(|e| e.to_string())? {
-        let screen_size = monitor.size();
-        let monitor_position = monitor.position();
+// ============================================
+// FOCUS RESTORE
+// ============================================
 
-        // On Windows, we need to account for the taskbar and DPI scaling
-        // Use the full screen width but position above the taskbar
-        #[cfg(target_os = "windows")]
-        {
-            use windows::Win32::UI::WindowsAndMessaging::{
-                SystemParametersInfoW, SPI_GETWORKAREA, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
-            };
-            use windows::Win32::Foundation::RECT;
-
-            let mut work_area = RECT::default();
-            unsafe {
-                let _ = SystemParametersInfoW(
-                    SPI_GETWORKAREA,
-                    0,
-                    Some(&mut work_area as *mut _ as *mut std::ffi::c_void),
-                    SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
-                );
-            }
+fn remember_focus() {
+    let previous = get_frontmost_window();
+    println!("[CopyGum] Remembering focused window: {:?}", previous);
+    if let Ok(mut slot) = PREVIOUS_FOCUS.lock() {
+        *slot = previous;
+    }
+}
 
-            let work_width = (work_area.right - work_area.left) as u32;
-            let work_height = (work_area.bottom - work_area.top) as u32;
-            let scale_factor = window.scale_factor().unwrap_or(1.0);
+/// Hide the overlay and give focus back to the window the user came from
+fn hide_overlay(window: &WebviewWindow) -> Result<(), String> {
+    window.hide().map_err(|e| e.to_string())?;
 
-            // Dynamic height: 35% of work area height, min 350px, max 450px
-            let base_height = (work_height as f64 * 0.35) as u32;
-            let window_height = base_height.clamp(350, 450);
+    let previous = PREVIOUS_FOCUS.lock().ok().and_then(|mut slot| slot.take());
+    if let Some(previous) = previous {
+        if !activate_window(previous) {
+            println!("[CopyGum] Could not restore focus to {:?}", previous);
+        }
+    }
 
-            println!("[CopyGum] Windows: work_area={}x{}, scale={}, base_height={}, final_height={}",
-                work_width, work_height, scale_factor, base_height, window_height);
+    Ok(())
+}
 
-            // Resize window to match work area width
-            window.set_size(tauri::PhysicalSize::new(work_width, window_height))
-                .map_err(|e| e.to_string())?;
+// ============================================
+// OVERLAY PLACEMENT
+// ============================================
+// Thin adapters that gather monitor data for the platform-independent
+// placement math in geometry.rs
+
+/// Which monitor the overlay should open on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MonitorPreference {
+    Cursor,
+    FocusedWindow,
+}
 
-            // Position at bottom of work area (above taskbar)
-            let x = work_area.left;
-            let y = work_area.bottom - window_height as i32;
+impl MonitorPreference {
+    fn from_setting(value: &str) -> Self {
+        match value {
+            "focused-window" => MonitorPreference::FocusedWindow,
+            _ => MonitorPreference::Cursor,
+        }
+    }
+}
 
-            window
-                .set_position(PhysicalPosition::new(x, y))
-                .map_err(|e| e.to_string())?;
+fn placement_prefs(settings: &AppSettings) -> PlacementPrefs {
+    PlacementPrefs {
+        edge: Edge::from_setting(&settings.overlay_edge),
+        height_percent: settings.overlay_height_percent,
+        min_height: settings.overlay_min_height,
+        max_height: settings.overlay_max_height,
+    }
+}
 
-            println!("[CopyGum] Window positioned at ({}, {}) with size {}x{}", x, y, work_width, window_height);
-        }
+fn monitor_rect(monitor: &Monitor) -> Rect {
+    Rect::new(
+        monitor.position().x,
+        monitor.position().y,
+        monitor.size().width,
+        monitor.size().height,
+    )
+}
 
-        // On macOS, smart positioning based on Dock settings
-        #[cfg(target_os = "macos")]
-        {
-            use objc::msg_send;
-            use objc::sel;
-            use objc::sel_impl;
-            use objc::runtime::Object;
-            use std::process::Command;
-
-            #[repr(C)]
-            #[derive(Debug, Copy, Clone)]
-            struct NSPoint { x: f64, y: f64 }
-            #[repr(C)]
-            #[derive(Debug, Copy, Clone)]
-            struct NSSize { width: f64, height: f64 }
-            #[repr(C)]
-            #[derive(Debug, Copy, Clone)]
-            struct NSRect { origin: NSPoint, size: NSSize }
-
-            // Check if Dock auto-hide is enabled
-            let dock_autohide = Command::new("defaults")
-                .args(["read", "com.apple.dock", "autohide"])
-                .output()
-                .map(|o| String::from_utf8_lossy(&o.stdout).trim() == "1")
-                .unwrap_or(false);
-
-            // Check Dock position (bottom, left, right)
-            let dock_position = Command::new("defaults")
-                .args(["read", "com.apple.dock", "orientation"])
-                .output()
-                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-                .unwrap_or_else(|_| "bottom".to_string());
-
-            let ns_screen: *mut Object = unsafe { msg_send![objc::class!(NSScreen), mainScreen] };
-
-            if !ns_screen.is_null() {
-                let screen_frame: NSRect = unsafe { msg_send![ns_screen, frame] };
-                let visible_frame: NSRect = unsafe { msg_send![ns_screen, visibleFrame] };
-                let scale_factor = window.scale_factor().unwrap_or(1.0);
-
-                let screen_width = (screen_frame.size.width * scale_factor) as u32;
-                let screen_height = (screen_frame.size.height * scale_factor) as i32;
-                let visible_height = (visible_frame.size.height * scale_factor) as i32;
-
-                // Dynamic height: 35% of visible height, min 350px, max 450px
-                let base_height = (visible_height as f64 * 0.35) as u32;
-                let window_height = base_height.clamp(350, 450);
-
-                // Dock at bottom: visible_frame.origin.y > 0
-                // Dock at left: visible_frame.origin.x > 0
-                // Dock at right: visible_frame width < screen width
-                let dock_at_bottom = visible_frame.origin.y > 0.0;
-                let dock_height = if dock_at_bottom {
-                    (visible_frame.origin.y * scale_factor) as i32
-                } else {
-                    0
-                };
-
-                // Smart positioning:
-                // - If Dock auto-hides OR Dock is not at bottom: position at absolute bottom
-                // - If Dock is visible at bottom: position above it
-                let y = if dock_autohide || !dock_at_bottom {
-                    // Position at absolute bottom - window floats over hidden Dock
-                    screen_height - window_height as i32
-                } else {
-                    // Position above visible Dock
-                    screen_height - dock_height - window_height as i32
-                };
-
-                // Ensure y doesn't go above menu bar
-                let menu_bar_height = 25; // Standard macOS menu bar
-                let y = y.max(menu_bar_height);
-
-                window.set_size(tauri::PhysicalSize::new(screen_width, window_height))
-                    .map_err(|e| e.to_string())?;
-
-                window
-                    .set_position(PhysicalPosition::new(0, y))
-                    .map_err(|e| e.to_string())?;
-
-                let position_type = if dock_autohide || !dock_at_bottom {
-                    "at bottom (Dock auto-hides or not at bottom)"
-                } else {
-                    "above Dock"
-                };
-
-                println!("[CopyGum] macOS: screen={}x{}, visible_height={}, dock_autohide={}, dock_position={}",
-                    screen_frame.size.width, screen_frame.size.height,
-                    visible_height, dock_autohide, dock_position);
-                println!("[CopyGum] macOS: Window at (0, {}) size {}x{} - {}",
-                    y, screen_width, window_height, position_type);
-            } else {
-                // Fallback positioning - use 35% of screen height
-                let base_height = (screen_size.height as f64 * 0.35) as u32;
-                let window_height = base_height.clamp(350, 450);
-
-                window.set_size(tauri::PhysicalSize::new(screen_size.width, window_height))
-                    .map_err(|e| e.to_string())?;
-
-                let x = monitor_position.x;
-                let y = monitor_position.y + screen_size.height as i32 - window_height as i32;
-
-                window
-                    .set_position(PhysicalPosition::new(x, y))
-                    .map_err(|e| e.to_string())?;
-
-                println!("[CopyGum] macOS: Fallback positioning at ({}, {}) size {}x{}", x, y, screen_size.width, window_height);
-            }
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn work_area_rect(monitor: &Monitor) -> Rect {
+    let area = monitor.work_area();
+    Rect::new(area.position.x, area.position.y, area.size.width, area.size.height)
+}
+
+/// Pick the monitor the user is working on
+///
+/// Tries the cursor and the focused window in the order given by the
+/// preference, then falls back to the window's current monitor and finally
+/// the primary monitor.
+fn target_monitor(window: &WebviewWindow, preference: MonitorPreference) -> Result<Option<Monitor>, String> {
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+    let rects: Vec<Rect> = monitors.iter().map(monitor_rect).collect();
+
+    let cursor_point = || window.cursor_position().ok().map(|p| (p.x, p.y));
+    let focused_point = || {
+        get_frontmost_window_bounds().map(|b| {
+            (b.x as f64 + b.width as f64 / 2.0, b.y as f64 + b.height as f64 / 2.0)
+        })
+    };
+
+    let candidates = match preference {
+        MonitorPreference::Cursor => [cursor_point(), focused_point()],
+        MonitorPreference::FocusedWindow => [focused_point(), cursor_point()],
+    };
+
+    for (x, y) in candidates.into_iter().flatten() {
+        if let Some(index) = monitor_at_point(&rects, x, y) {
+            return Ok(Some(monitors[index].clone()));
         }
+    }
 
-        // On other platforms (Linux, etc.), use dynamic height
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-        {
-            // Dynamic height: 35% of screen height, min 350px, max 450px
-            let base_height = (screen_size.height as f64 * 0.35) as u32;
-            let window_height = base_height.clamp(350, 450);
+    if let Some(monitor) = window.current_monitor().map_err(|e| e.to_string())? {
+        return Ok(Some(monitor));
+    }
 
-            // Resize window to match screen width
-            window.set_size(tauri::PhysicalSize::new(screen_size.width, window_height))
-                .map_err(|e| e.to_string())?;
+    window.primary_monitor().map_err(|e| e.to_string())
+}
 
-            // Position at bottom of screen, full width
-            let x = monitor_position.x;
-            let y = monitor_position.y + screen_size.height as i32 - window_height as i32;
+/// Describe a monitor for the placement math
+#[cfg(target_os = "windows")]
+fn display_info(monitor: &Monitor) -> DisplayInfo {
+    // Per-monitor work area already excludes the taskbar, and an
+    // auto-hidden taskbar doesn't reserve any space
+    DisplayInfo {
+        bounds: monitor_rect(monitor),
+        work_area: work_area_rect(monitor),
+        scale_factor: monitor.scale_factor(),
+        dock: None,
+    }
+}
+
+/// Describe a monitor for the placement math
+#[cfg(target_os = "macos")]
+fn display_info(monitor: &Monitor) -> DisplayInfo {
+    // Work area comes from NSScreen visibleFrame: it excludes the menu bar
+    // and the Dock (a thin strip when the Dock auto-hides)
+    let bounds = monitor_rect(monitor);
+    let work_area = work_area_rect(monitor);
+
+    DisplayInfo {
+        bounds,
+        work_area,
+        scale_factor: monitor.scale_factor(),
+        dock: dock_from_insets(&bounds, &work_area, dock_auto_hides()),
+    }
+}
 
-            window
-                .set_position(PhysicalPosition::new(x, y))
+/// Describe a monitor for the placement math
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn display_info(monitor: &Monitor) -> DisplayInfo {
+    let bounds = monitor_rect(monitor);
+
+    // X11 panels and docks reserve space through struts. On Wayland the
+    // compositor keeps the layer-shell overlay clear of them instead.
+    #[cfg(target_os = "linux")]
+    let work_area = linux_placement::work_area(&bounds).unwrap_or(bounds);
+    #[cfg(not(target_os = "linux"))]
+    let work_area = bounds;
+
+    DisplayInfo {
+        bounds,
+        work_area,
+        scale_factor: monitor.scale_factor(),
+        dock: None,
+    }
+}
+
+/// Read the Dock auto-hide preference without spawning `defaults`
+#[allow(deprecated)]
+#[cfg(target_os = "macos")]
+fn dock_auto_hides() -> bool {
+    use cocoa::base::{nil, BOOL, YES};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let suite = NSString::alloc(nil).init_str("com.apple.dock");
+        let key = NSString::alloc(nil).init_str("autohide");
+
+        let defaults: id = msg_send![class!(NSUserDefaults), alloc];
+        let defaults: id = msg_send![defaults, initWithSuiteName: suite];
+
+        let auto_hide = if defaults != nil {
+            let value: BOOL = msg_send![defaults, boolForKey: key];
+            let _: () = msg_send![defaults, release];
+            value == YES
+        } else {
+            false
+        };
+
+        let _: () = msg_send![suite, release];
+        let _: () = msg_send![key, release];
+
+        auto_hide
+    }
+}
+
+fn position_overlay_window(window: &WebviewWindow) -> Result<(), String> {
+    let settings = AppSettings::load(window.app_handle()).unwrap_or_default();
+    let prefs = placement_prefs(&settings);
+    let preference = MonitorPreference::from_setting(&settings.overlay_monitor);
+
+    let Some(monitor) = target_monitor(window, preference)? else {
+        return Ok(());
+    };
+
+    let display = display_info(&monitor);
+    let rect = compute_window_rect(&display, &prefs);
+
+    #[cfg(target_os = "linux")]
+    let rect = {
+        // Layer-shell surfaces are placed by the compositor; only the size matters
+        if linux_placement::anchor_layer_surface(window, prefs.edge) {
+            window.set_size(PhysicalSize::new(rect.width, rect.height))
                 .map_err(|e| e.to_string())?;
+            println!("[CopyGum] Overlay anchored to {:?} edge via layer shell", prefs.edge);
+            return Ok(());
+        }
 
-            println!("[CopyGum] Linux: Window at ({}, {}) size {}x{}", x, y, screen_size.width, window_height);
+        // Keep GTK's client-side shadow from pushing the overlay off the work area
+        match linux_placement::frame_extents(window) {
+            Some(extents) => expand_by_frame_extents(rect, extents),
+            None => rect,
         }
-    }
+    };
+
+    window.set_size(PhysicalSize::new(rect.width, rect.height))
+        .map_err(|e| e.to_string())?;
+
+    window
+        .set_position(PhysicalPosition::new(rect.x, rect.y))
+        .map_err(|e| e.to_string())?;
+
+    println!("[CopyGum] Overlay on {:?} edge of {:?}: display={:?}, window={:?}",
+        prefs.edge, monitor.name(), display, rect);
 
     Ok(())
 }
@@ -421,3 +482,4 @@ pub fn setup_global_shortcut(app: &AppHandle) -> Result<(), String> {
     println!("[CopyGum] Global shortcut registered successfully!");
     Ok(())
 }
+
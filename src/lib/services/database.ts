@@ -2,6 +2,7 @@
 // Uses @tauri-apps/plugin-sql for direct database queries from frontend
 
 import Database from '@tauri-apps/plugin-sql';
+import { invoke } from '@tauri-apps/api/core';
 
 export interface ClipboardItem {
   id?: number;
@@ -15,9 +16,16 @@ export interface ClipboardItem {
   image_path?: string;
   image_thumbnail?: Uint8Array;
   image_dominant_color?: string;
+  image_palette?: string; // JSON ImagePalette
   image_width?: number;
   image_height?: number;
   image_size?: number;
+  // What the image file says about itself (image_metadata.rs)
+  image_format?: string; // "PNG", "JPEG", "WebP", ...
+  image_color_space?: string; // ICC profile description, else "sRGB" or "Gray"
+  image_dpi?: number;
+  image_has_alpha?: boolean;
+  image_animated?: boolean; // GIF, WebP or PNG with more than one frame
   timestamp: number;
   created_at?: number;
   updated_at?: number;
@@ -31,6 +39,18 @@ export interface ClipboardItem {
   is_deleted: boolean;
   deleted_at?: number;
   search_text?: string;
+  // QR code / barcode payload found in an image, and its detected type
+  decoded_text?: string;
+  decoded_type?: string;
+  // Perceptual hash (hex) and the id of the first image of its stack
+  image_phash?: string;
+  stack_id?: number;
+}
+
+// Main colors of an image (palette.rs), most common first
+export interface ImagePalette {
+  colors: { color: string; proportion: number }[];
+  average: string; // Averaged in Lab space
 }
 
 export interface Category {
@@ -165,7 +185,18 @@ export async function saveImageClipboardItem(
   imageWidth: number,
   imageHeight: number,
   imageSize: number,
+  imageFormat: string | null,
+  colorSpace: string | null,
+  dpi: number | null,
+  hasAlpha: boolean,
+  animated: boolean,
   dominantColor: string | null,
+  palette: string | null,
+  decodedText: string | null,
+  decodedType: string | null,
+  searchText: string | null,
+  perceptualHash: string | null,
+  stackId: number | null,
   appName?: string,
   appIcon?: string,
   appBundleId?: string
@@ -202,9 +233,10 @@ export async function saveImageClipboardItem(
     const result = await database.execute(
       `INSERT INTO clipboard_items
        (content, content_type, category, app_name, app_icon, app_bundle_id, is_image,
-        image_path, image_width, image_height, image_size, image_dominant_color,
-        timestamp, is_pinned, is_deleted)
-       VALUES ($1, $2, $3, $4, $5, $6, 1, $7, $8, $9, $10, $11, $12, 0, 0)`,
+        image_path, image_width, image_height, image_size, image_format, image_color_space, image_dpi,
+        image_has_alpha, image_animated, image_dominant_color, image_palette, decoded_text, decoded_type,
+        search_text, image_phash, stack_id, timestamp, is_pinned, is_deleted)
+       VALUES ($1, $2, $3, $4, $5, $6, 1, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, 0, 0)`,
       [
         filename,           // content - use filename as display text
         'image',            // content_type
@@ -216,7 +248,18 @@ export async function saveImageClipboardItem(
         imageWidth,
         imageHeight,
         imageSize,
+        imageFormat,
+        colorSpace,
+        dpi,
+        hasAlpha ? 1 : 0,
+        animated ? 1 : 0,
         dominantColor,
+        palette,            // JSON palette
+        decodedText,        // QR code / barcode payload, if any
+        decodedType,
+        searchText,         // decoded payload unless sensitive
+        perceptualHash,
+        stackId,            // set when it looks like a recent image
         timestamp
       ]
     );
@@ -333,23 +376,102 @@ export async function pinMultipleItems(ids: number[]): Promise<void> {
   }
 }
 
+/** Filters for the backend full-text search (timestamps in ms) */
+export interface SearchOptions {
+  categories?: string[];
+  tags?: string[];
+  app?: string;
+  since?: number;
+  until?: number;
+  pinned_only?: boolean;
+  limit?: number;
+  cursor?: string; // next_cursor of the previous page
+}
+
+export interface SnippetPart {
+  text: string;
+  matched: boolean;
+}
+
+export interface SearchHit {
+  item: ClipboardItem;
+  score: number;
+  snippet: SnippetPart[];
+}
+
+/** A problem in the query syntax; span is in characters, end exclusive */
+export interface QueryError {
+  message: string;
+  span: { start: number; end: number };
+}
+
+export interface SearchPage {
+  hits: SearchHit[];
+  next_cursor: string | null;
+  errors: QueryError[];
+}
+
+/**
+ * Full-text search in the backend (fts5, ranked with bm25)
+ * Bare words match as prefixes, "quoted text" as a phrase, and fields
+ * such as app:, type:, tag:, after:, before: and is: filter the results.
+ * Pass next_cursor back as options.cursor for the next page.
+ */
+export async function searchItems(query: string, options: SearchOptions = {}): Promise<SearchPage> {
+  type BackendHit = Omit<ClipboardItem, 'tags' | 'is_deleted'> & {
+    tags: string[];
+    score: number;
+    snippet: SnippetPart[];
+  };
+  const page = await invoke<{ hits: BackendHit[]; next_cursor: string | null; errors: QueryError[] }>('search_items', {
+    query: { ...options, query }
+  });
+  return {
+    next_cursor: page.next_cursor,
+    errors: page.errors,
+    hits: page.hits.map(({ score, snippet, tags, ...item }) => ({
+      // Same shape as rows from clipboard_items_with_tags
+      item: { ...item, tag_names: tags.join(','), is_deleted: false },
+      score,
+      snippet,
+    })),
+  };
+}
+
+export interface SearchIndexReport {
+  indexed: number;
+  added: number;
+  removed: number;
+  updated: number;
+}
+
+/**
+ * Reindex the whole history for search (trashed and sensitive items are left out)
+ */
+export async function rebuildSearchIndex(): Promise<SearchIndexReport> {
+  return invoke<SearchIndexReport>('rebuild_search_index');
+}
+
+export interface SimilarImage {
+  item: ClipboardItem;
+  distance: number; // Differing hash bits, 0 = looks identical
+}
+
+/**
+ * Images that look like the given one, closest first
+ * (threshold defaults to the Similarity setting)
+ */
+export async function findSimilarImages(id: number, threshold?: number): Promise<SimilarImage[]> {
+  return invoke<SimilarImage[]>('find_similar_images', { id, threshold });
+}
+
 /**
  * Search clipboard items
  */
 export async function searchClipboard(query: string): Promise<ClipboardItem[]> {
   try {
-    const database = await getDb();
-
-    const results = await database.select<ClipboardItem[]>(
-      `SELECT * FROM clipboard_items
-       WHERE is_deleted = 0
-       AND content LIKE $1
-       ORDER BY is_pinned DESC, timestamp DESC
-       LIMIT 100`,
-      [`%${query}%`]
-    );
-
-    return results;
+    const page = await searchItems(query, { limit: 100 });
+    return page.hits.map(hit => hit.item);
   } catch (error) {
     console.error('Failed to search clipboard:', error);
     return [];
@@ -1,5 +1,6 @@
 // Export/Import Service
-// Handles exporting clipboard history to JSON/CSV and importing from JSON
+// Exports and validated JSON imports run in the backend
+// .copygum archives carry images too and can be imported on another machine
 
 import { invoke } from '@tauri-apps/api/core';
 import { save, open } from '@tauri-apps/plugin-dialog';
@@ -13,166 +14,285 @@ export interface ExportData {
   items: ClipboardItem[];
 }
 
+export type ExportFormat = 'json' | 'csv' | 'markdown' | 'html';
+
+/** Filters applied by the backend exporter (timestamps in ms) */
+export interface ExportFilter {
+  since?: number;
+  until?: number;
+  categories?: string[];
+  tags?: string[];
+  pinned_only?: boolean;
+  include_sensitive?: boolean; // password, apikey and private items are skipped by default
+}
+
+export interface ExportStats {
+  version: string;
+  export_date: string;
+  item_count: number;
+  image_count: number;
+  content_bytes: number;
+  image_bytes: number;
+}
+
+export interface ExportSummary {
+  path: string;
+  item_count: number;
+  bytes: number;
+}
+
+const EXPORT_FILE_TYPES: Record<ExportFormat, { name: string; extension: string }> = {
+  json: { name: 'JSON', extension: 'json' },
+  csv: { name: 'CSV', extension: 'csv' },
+  markdown: { name: 'Markdown', extension: 'md' },
+  html: { name: 'HTML', extension: 'html' },
+};
+
 /**
- * Export clipboard history to JSON
+ * Whether a file was exported with a passphrase (so imports can ask for it)
  */
-export async function exportToJSON(includeImages: boolean = false): Promise<void> {
+export async function isEncryptedExport(filePath: string): Promise<boolean> {
+  return invoke<boolean>('is_encrypted_export', { filePath });
+}
+
+/**
+ * Count the items (and their size) an export with this filter would contain
+ */
+export async function getExportStats(filter: ExportFilter = {}): Promise<ExportStats> {
+  return invoke<ExportStats>('get_export_stats', { filter });
+}
+
+/**
+ * Export clipboard history in any format
+ * The backend reads the database and streams the file, emitting
+ * `export-progress` events ({ done, total }) along the way.
+ * With a passphrase the file is encrypted (Argon2id + XChaCha20-Poly1305).
+ * Returns null if the user cancelled the save dialog.
+ */
+export async function exportHistory(
+  format: ExportFormat,
+  filter: ExportFilter = {},
+  passphrase?: string
+): Promise<ExportSummary | null> {
   try {
-    // Get all clipboard items from database
-    const db = await Database.load('sqlite:copygum.db');
-    const items: ClipboardItem[] = await db.select(
-      'SELECT * FROM clipboard_items ORDER BY timestamp DESC'
-    );
-
-    // Prepare export data
-    const exportData: ExportData = {
-      version: '1.0',
-      export_date: new Date().toISOString(),
-      item_count: items.length,
-      items: includeImages ? items : items.map(item => ({
-        ...item,
-        image_path: undefined,
-        image_thumbnail: undefined,
-        image_dominant_color: undefined,
-      })),
-    };
+    const fileType = EXPORT_FILE_TYPES[format];
 
     // Show save dialog
     const filePath = await save({
-      defaultPath: `copygum-export-${Date.now()}.json`,
+      defaultPath: `copygum-export-${Date.now()}.${fileType.extension}`,
       filters: [{
-        name: 'JSON',
-        extensions: ['json']
+        name: fileType.name,
+        extensions: [fileType.extension]
       }]
     });
 
-    if (!filePath) return; // User cancelled
+    if (!filePath) return null; // User cancelled
 
-    // Save file
-    const jsonData = JSON.stringify(exportData, null, 2);
-    await invoke('save_export_file', {
+    const summary = await invoke<ExportSummary>('export_history', {
       filePath,
-      data: jsonData
+      format,
+      filter,
+      passphrase: passphrase ?? null
     });
 
-    console.log('✅ Exported to:', filePath);
+    console.log(`✅ Exported ${summary.item_count} items to:`, summary.path);
+    return summary;
   } catch (error) {
-    console.error('Failed to export:', error);
+    console.error(`Failed to export ${format}:`, error);
     throw error;
   }
 }
 
+/**
+ * Export clipboard history to JSON
+ */
+export async function exportToJSON(filter: ExportFilter = {}): Promise<ExportSummary | null> {
+  return exportHistory('json', filter);
+}
+
 /**
  * Export clipboard history to CSV
  */
-export async function exportToCSV(): Promise<void> {
-  try {
-    const db = await Database.load('sqlite:copygum.db');
-    const items: ClipboardItem[] = await db.select(
-      'SELECT * FROM clipboard_items ORDER BY timestamp DESC'
-    );
-
-    // Convert to CSV
-    const headers = ['Content', 'Type', 'Category', 'Date', 'Pinned', 'Tags', 'App Name'];
-    const rows = items.map(item => [
-      `"${(item.content || '').replace(/"/g, '""').replace(/\n/g, ' ')}"`, // Escape quotes and newlines
-      item.content_type,
-      item.category,
-      new Date(item.timestamp).toISOString(),
-      item.is_pinned ? 'Yes' : 'No',
-      item.tags || '',
-      item.app_name || '',
-    ]);
-
-    const csvData = [
-      headers.join(','),
-      ...rows.map(row => row.join(','))
-    ].join('\n');
+export async function exportToCSV(filter: ExportFilter = {}): Promise<ExportSummary | null> {
+  return exportHistory('csv', filter);
+}
 
-    // Show save dialog
+export interface ArchiveSummary {
+  item_count: number;
+  image_count: number;
+  missing_images: number;
+}
+
+export interface ImportConflict {
+  kind: 'item' | 'category' | 'tag';
+  name: string;
+  reason: string;
+}
+
+export interface ArchiveImportReport {
+  added: number;
+  skipped: number;
+  conflicted: number;
+  categories_added: string[];
+  tags_added: string[];
+  conflicts: ImportConflict[];
+}
+
+const ARCHIVE_FILE_TYPE = { name: 'CopyGum Archive', extensions: ['copygum'] };
+
+/**
+ * Export history with images to a portable .copygum archive
+ * With a passphrase the archive is encrypted.
+ * Returns null if the user cancelled the save dialog.
+ */
+export async function exportArchive(
+  filter: ExportFilter = {},
+  passphrase?: string
+): Promise<ArchiveSummary | null> {
+  try {
     const filePath = await save({
-      defaultPath: `copygum-export-${Date.now()}.csv`,
-      filters: [{
-        name: 'CSV',
-        extensions: ['csv']
-      }]
+      defaultPath: `copygum-archive-${Date.now()}.copygum`,
+      filters: [ARCHIVE_FILE_TYPE]
     });
 
-    if (!filePath) return; // User cancelled
+    if (!filePath) return null; // User cancelled
 
-    // Save file
-    await invoke('save_export_file', {
+    const summary = await invoke<ArchiveSummary>('export_archive', {
       filePath,
-      data: csvData
+      filter,
+      passphrase: passphrase ?? null
     });
+    console.log(`✅ Archived ${summary.item_count} items (${summary.image_count} images) to:`, filePath);
+    return summary;
+  } catch (error) {
+    console.error('Failed to export archive:', error);
+    throw error;
+  }
+}
+
+/**
+ * Import a .copygum archive, merging it into the current history
+ * Encrypted archives call requestPassphrase first.
+ * Returns null if the user cancelled the open dialog or the passphrase prompt.
+ */
+export async function importArchive(
+  requestPassphrase: () => string | null = () => null
+): Promise<ArchiveImportReport | null> {
+  try {
+    const filePath = await open({
+      multiple: false,
+      filters: [ARCHIVE_FILE_TYPE]
+    });
+
+    if (!filePath) return null; // User cancelled
 
-    console.log('✅ Exported to CSV:', filePath);
+    let passphrase: string | null = null;
+    if (await isEncryptedExport(filePath)) {
+      passphrase = requestPassphrase();
+      if (!passphrase) return null;
+    }
+
+    const report = await invoke<ArchiveImportReport>('import_archive_file', { filePath, passphrase });
+    console.log(`✅ Archive import: ${report.added} added, ${report.skipped} skipped, ${report.conflicted} conflicted`);
+    return report;
   } catch (error) {
-    console.error('Failed to export CSV:', error);
+    console.error('Failed to import archive:', error);
     throw error;
   }
 }
 
+export type ImportSource = 'copyq' | 'gpaste' | 'clipman' | 'ditto' | 'maccy' | 'text';
+
+export interface ImportSummary {
+  source: ImportSource;
+  found: number;
+  imported: number;
+  skipped: number;
+  unsupported: number;
+}
+
+const IMPORT_FILE_TYPES: Record<ImportSource, { name: string; extensions: string[] }> = {
+  copyq: { name: 'CopyQ Export', extensions: ['cpq'] },
+  gpaste: { name: 'GPaste History', extensions: ['xml'] },
+  clipman: { name: 'Clipman History', extensions: ['*'] },
+  ditto: { name: 'Ditto Database', extensions: ['db'] },
+  maccy: { name: 'Maccy Database', extensions: ['sqlite'] },
+  text: { name: 'Text', extensions: ['txt', '*'] },
+};
+
 /**
- * Import clipboard history from JSON
+ * Import history from another clipboard manager
+ * The dialog opens at the tool's default history location when it has one.
+ * Returns null if the user cancelled the open dialog.
  */
-export async function importFromJSON(): Promise<number> {
+export async function importFromApp(source: ImportSource): Promise<ImportSummary | null> {
   try {
-    // Show open dialog
+    const defaultPath = await invoke<string | null>('get_import_default_path', { source });
     const filePath = await open({
       multiple: false,
-      filters: [{
-        name: 'JSON',
-        extensions: ['json']
-      }]
+      defaultPath: defaultPath ?? undefined,
+      filters: [IMPORT_FILE_TYPES[source]]
     });
 
-    if (!filePath) return 0; // User cancelled
+    if (!filePath) return null; // User cancelled
 
-    // Read file
-    const contents = await invoke<string>('read_import_file', { filePath });
-    const importData: ExportData = JSON.parse(contents);
+    const summary = await invoke<ImportSummary>('import_from_app', { source, filePath });
+    console.log(`✅ Imported ${summary.imported} of ${summary.found} items from ${source}`);
+    return summary;
+  } catch (error) {
+    console.error(`Failed to import from ${source}:`, error);
+    throw error;
+  }
+}
 
-    // Validate version
-    if (importData.version !== '1.0') {
-      throw new Error(`Unsupported export version: ${importData.version}`);
-    }
+export interface ImportDiff {
+  version: string;
+  dry_run: boolean;
+  total: number;
+  added: number;
+  duplicates: number;
+  invalid: number;
+  by_category: Record<string, number>;
+  new_tags: string[];
+  errors: { index: number; reason: string }[];
+}
 
-    // Import items into database
-    const db = await Database.load('sqlite:copygum.db');
-    let importedCount = 0;
-
-    for (const item of importData.items) {
-      try {
-        // Insert item (will skip if duplicate based on content hash)
-        await db.execute(
-          `INSERT OR IGNORE INTO clipboard_items
-           (content, content_type, category, timestamp, is_pinned, tags, app_name, app_icon,
-            image_path, image_thumbnail, image_dominant_color, search_index)
-           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`,
-          [
-            item.content,
-            item.content_type,
-            item.category,
-            item.timestamp,
-            item.is_pinned ? 1 : 0,
-            item.tags || null,
-            item.app_name || null,
-            item.app_icon || null,
-            item.image_path || null,
-            item.image_thumbnail || null,
-            item.image_dominant_color || null,
-            item.content.toLowerCase(), // search_index
-          ]
-        );
-        importedCount++;
-      } catch (err) {
-        console.warn(`Failed to import item:`, err);
-      }
-    }
+/**
+ * Pick a JSON export to import
+ * Returns null if the user cancelled the open dialog.
+ */
+export async function pickJSONImportFile(): Promise<string | null> {
+  const filePath = await open({
+    multiple: false,
+    filters: [{
+      name: 'JSON',
+      extensions: ['json']
+    }]
+  });
+  return filePath || null;
+}
 
-    console.log(`✅ Imported ${importedCount} items from ${filePath}`);
-    return importedCount;
+/**
+ * Import clipboard history from JSON
+ * The backend validates every record against the export's version. With
+ * dryRun nothing is written and the diff shows what an import would do.
+ * Encrypted exports need their passphrase.
+ */
+export async function importFromJSON(
+  filePath: string,
+  dryRun: boolean,
+  passphrase?: string
+): Promise<ImportDiff> {
+  try {
+    const diff = await invoke<ImportDiff>('import_json_file', {
+      filePath,
+      dryRun,
+      passphrase: passphrase ?? null
+    });
+    if (!dryRun) {
+      console.log(`✅ Imported ${diff.added} items from ${filePath}`);
+    }
+    return diff;
   } catch (error) {
     console.error('Failed to import:', error);
     throw error;
@@ -187,3 +307,58 @@ export async function clearAllHistory(): Promise<void> {
   await db.execute('DELETE FROM clipboard_items WHERE is_pinned = 0');
   console.log('✅ Cleared all unpinned history');
 }
+
+export interface BackupInfo {
+  path: string;
+  name: string;
+  created_at: number;
+  item_count: number;
+  reason: 'scheduled' | 'manual' | 'pre-restore';
+  bytes: number;
+}
+
+export interface RestoreReport {
+  item_count: number;
+  images_restored: number;
+  safety_backup: string;
+}
+
+/**
+ * Pick the folder automatic backups are written to
+ * Returns null if the user cancelled the dialog.
+ */
+export async function pickBackupDirectory(): Promise<string | null> {
+  const dir = await open({ directory: true, multiple: false });
+  return dir || null;
+}
+
+/**
+ * Take a backup now and rotate old ones
+ */
+export async function createBackupNow(): Promise<BackupInfo> {
+  const info = await invoke<BackupInfo>('create_backup_now');
+  console.log(`✅ Backed up ${info.item_count} items to ${info.path}`);
+  return info;
+}
+
+/**
+ * Backups in the configured folder, newest first
+ */
+export async function listBackups(): Promise<BackupInfo[]> {
+  return invoke<BackupInfo[]>('list_backup_snapshots');
+}
+
+/**
+ * Restore a backup over the current history
+ * The backend validates the snapshot and backs up the current state first.
+ */
+export async function restoreBackup(backupPath: string): Promise<RestoreReport> {
+  try {
+    const report = await invoke<RestoreReport>('restore_backup', { backupPath });
+    console.log(`✅ Restored ${report.item_count} items from ${backupPath}`);
+    return report;
+  } catch (error) {
+    console.error('Failed to restore backup:', error);
+    throw error;
+  }
+}
@@ -4,7 +4,7 @@
 import { writable, derived, get } from 'svelte/store';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { ClipboardItem } from '../services/database';
+import type { ClipboardItem, QueryError } from '../services/database';
 import {
   getClipboardItems,
   saveClipboardItem,
@@ -12,6 +12,8 @@ import {
   deleteClipboardItem as deleteItemFromDb,
   togglePin as togglePinInDb,
   searchClipboard as searchInDb,
+  searchItems,
+  findSimilarImages,
   updateItemCategory as updateItemCategoryInDb
 } from '../services/database';
 import { settings } from './settingsStore';
@@ -61,19 +63,124 @@ searchQuery.subscribe(value => {
   }, 300);
 });
 
+// Ranked backend results (exact, then fuzzy) for the debounced query;
+// null while there is no query or the backend search failed
+export const searchResults = writable<ClipboardItem[] | null>(null);
+// Syntax problems in the debounced query (app:, type:, after: ...)
+export const searchErrors = writable<QueryError[]>([]);
+
+let searchGeneration = 0;
+
+debouncedSearchQuery.subscribe(async value => {
+  const generation = ++searchGeneration;
+  if (!value.trim()) {
+    searchResults.set(null);
+    searchErrors.set([]);
+    return;
+  }
+  try {
+    const page = await searchItems(value, { limit: 200 });
+    // Drop results of a query the user has typed past
+    if (generation === searchGeneration) {
+      searchResults.set(page.hits.map(hit => hit.item));
+      searchErrors.set(page.errors);
+    }
+  } catch (e) {
+    console.error('Backend search failed, filtering locally:', e);
+    if (generation === searchGeneration) {
+      searchResults.set(null);
+      searchErrors.set([]);
+    }
+  }
+});
+
+// Near-duplicate images stacked by the backend (similar_images.rs): stack
+// id (the first image's id) -> number of images in the stack
+export const imageStacks = derived(clipboardItems, $items => {
+  const stacks = new Map<number, number>();
+  for (const item of $items) {
+    if (item.stack_id != null) {
+      stacks.set(item.stack_id, (stacks.get(item.stack_id) ?? 0) + 1);
+    }
+  }
+  // The first image of a stack has no stack_id itself
+  for (const item of $items) {
+    if (item.id !== undefined && stacks.has(item.id)) {
+      stacks.set(item.id, stacks.get(item.id)! + 1);
+    }
+  }
+  return stacks;
+});
+
+/** Stack an item is part of, if any */
+export function stackOf(item: ClipboardItem, stacks: Map<number, number>): number | null {
+  if (item.stack_id != null) return item.stack_id;
+  return item.id !== undefined && stacks.has(item.id) ? item.id : null;
+}
+
+// Stacks show as their newest image until expanded
+export const expandedStacks = writable<Set<number>>(new Set());
+
+export function toggleStack(stackId: number): void {
+  expandedStacks.update(expanded => {
+    const next = new Set(expanded);
+    if (!next.delete(stackId)) {
+      next.add(stackId);
+    }
+    return next;
+  });
+}
+
+// "Find similar" on an image card: that image, then its look-alikes
+export const similarImages = writable<{ id: number; items: ClipboardItem[] } | null>(null);
+
+export async function showSimilarImages(id: number): Promise<void> {
+  const results = await findSimilarImages(id);
+  const source = get(clipboardItems).find(item => item.id === id);
+  similarImages.set({
+    id,
+    items: [...(source ? [source] : []), ...results.map(result => result.item)]
+  });
+}
+
+export function clearSimilarImages(): void {
+  similarImages.set(null);
+}
+
 // Derived store: filtered items based on all filters
 export const filteredItems = derived(
-  [clipboardItems, selectedCategory, debouncedSearchQuery, advancedFilters],
-  ([$items, $category, $search, $filters]) => {
+  [clipboardItems, selectedCategory, debouncedSearchQuery, advancedFilters, searchResults, imageStacks, expandedStacks, similarImages],
+  ([$items, $category, $search, $filters, $results, $stacks, $expanded, $similar]) => {
     let filtered = $items;
+    const loaded = new Map($items.map(item => [item.id, item]));
+
+    // Search results keep the backend's ranking; use the loaded copy of
+    // each item where there is one (it has icons and thumbnails)
+    if ($search && $results) {
+      filtered = $results.map(item => loaded.get(item.id) ?? item);
+    }
+
+    if ($similar) {
+      filtered = $similar.items.map(item => loaded.get(item.id) ?? item);
+    } else if (!$search) {
+      // Collapse stacks to their first (newest) card
+      const seen = new Set<number>();
+      filtered = filtered.filter(item => {
+        const stack = stackOf(item, $stacks);
+        if (stack === null || $expanded.has(stack)) return true;
+        if (seen.has(stack)) return false;
+        seen.add(stack);
+        return true;
+      });
+    }
 
     // Filter by category
     if ($category && $category !== 'all') {
       filtered = filtered.filter(item => item.category === $category);
     }
 
-    // Filter by search (client-side for instant feedback)
-    if ($search) {
+    // Filter by search (client-side until the backend answers)
+    if ($search && !$results) {
       const query = $search.toLowerCase();
       filtered = filtered.filter(item =>
         item.content.toLowerCase().includes(query) ||
@@ -172,7 +279,18 @@ async function processQueue() {
           event.imageWidth || 0,
           event.imageHeight || 0,
           event.imageSize || 0,
+          event.imageFormat || null,
+          event.imageColorSpace || null,
+          event.imageDpi ?? null,
+          event.imageHasAlpha ?? false,
+          event.imageAnimated ?? false,
           event.dominantColor || null,
+          event.palette || null,
+          event.decodedText || null,
+          event.decodedType || null,
+          event.searchText || null,
+          event.perceptualHash || null,
+          event.stackId ?? null,
           event.sourceAppName,
           event.sourceAppIcon,
           event.sourceBundleId
@@ -295,7 +413,18 @@ interface ClipboardQueueItem {
   imageWidth?: number;
   imageHeight?: number;
   imageSize?: number;
+  imageFormat?: string;
+  imageColorSpace?: string;
+  imageDpi?: number | null;
+  imageHasAlpha?: boolean;
+  imageAnimated?: boolean;
   dominantColor?: string;
+  palette?: string;
+  decodedText?: string;
+  decodedType?: string;
+  searchText?: string;
+  perceptualHash?: string;
+  stackId?: number | null;
 }
 
 // Use window object for truly global state (survives HMR reloads)
@@ -362,7 +491,18 @@ export async function startClipboardMonitoring(): Promise<void> {
       imageWidth?: number;
       imageHeight?: number;
       imageSize?: number;
+      imageFormat?: string;
+      imageColorSpace?: string;
+      imageDpi?: number | null;
+      imageHasAlpha?: boolean;
+      imageAnimated?: boolean;
       dominantColor?: string;
+      palette?: string;
+      decodedText?: string;
+      decodedType?: string;
+      searchText?: string;
+      perceptualHash?: string;
+      stackId?: number | null;
       sourceAppName?: string;
       sourceAppIcon?: string;
       sourceBundleId?: string;
@@ -396,7 +536,18 @@ export async function startClipboardMonitoring(): Promise<void> {
         imageWidth: event.payload.imageWidth,
         imageHeight: event.payload.imageHeight,
         imageSize: event.payload.imageSize,
-        dominantColor: event.payload.dominantColor
+        imageFormat: event.payload.imageFormat,
+        imageColorSpace: event.payload.imageColorSpace,
+        imageDpi: event.payload.imageDpi,
+        imageHasAlpha: event.payload.imageHasAlpha,
+        imageAnimated: event.payload.imageAnimated,
+        dominantColor: event.payload.dominantColor,
+        palette: event.payload.palette,
+        decodedText: event.payload.decodedText,
+        decodedType: event.payload.decodedType,
+        searchText: event.payload.searchText,
+        perceptualHash: event.payload.perceptualHash,
+        stackId: event.payload.stackId
       });
 
       // Start processing if not already processing
@@ -10,12 +10,17 @@ export interface AppSettings {
   auto_start_monitoring: boolean;
   show_on_startup: boolean;
   minimize_to_tray: boolean;
+  launch_at_startup: boolean;
 
   // Storage
   history_limit: number;
   auto_delete_days: number;
   save_images: boolean;
   max_image_size_mb: number;
+  ocr_enabled: boolean;
+  ocr_language: string;
+  similar_images: string; // 'off' | 'stack' | 'merge'
+  similar_image_threshold: number;
 
   // Appearance
   theme: string;
@@ -27,6 +32,19 @@ export interface AppSettings {
   window_opacity: number;
   enable_blur: boolean;
 
+  // Overlay placement
+  overlay_edge: string;
+  overlay_monitor: string;
+  overlay_height_percent: number;
+  overlay_min_height: number;
+  overlay_max_height: number;
+
+  // Backups
+  backup_enabled: boolean;
+  backup_frequency: string;
+  backup_directory?: string;
+  backup_keep: number;
+
   // Shortcuts
   toggle_window_shortcut: string;
   search_shortcut: string;
@@ -35,6 +53,7 @@ export interface AppSettings {
   exclude_apps: string[];
   sensitive_keywords: string[];
   enable_analytics: boolean;
+  strip_image_metadata: boolean; // Remove EXIF/GPS from saved and pasted images
 
   // First-run
   hasShownOverlayInfo: boolean;
@@ -45,10 +64,15 @@ const defaultSettings: AppSettings = {
   auto_start_monitoring: true,
   show_on_startup: false,
   minimize_to_tray: true,
+  launch_at_startup: false,
   history_limit: 500,
   auto_delete_days: 0,
   save_images: true,
   max_image_size_mb: 10,
+  ocr_enabled: false,
+  ocr_language: 'eng',
+  similar_images: 'stack',
+  similar_image_threshold: 6,
   theme: 'auto',
   card_size: 'medium',
   font_size: 14,
@@ -57,11 +81,21 @@ const defaultSettings: AppSettings = {
   accent_color: undefined,
   window_opacity: 100,
   enable_blur: false,
+  overlay_edge: 'bottom',
+  overlay_monitor: 'cursor',
+  overlay_height_percent: 35,
+  overlay_min_height: 350,
+  overlay_max_height: 450,
+  backup_enabled: false,
+  backup_frequency: 'daily',
+  backup_directory: undefined,
+  backup_keep: 7,
   toggle_window_shortcut: 'CommandOrControl+Shift+V',
   search_shortcut: 'CommandOrControl+F',
   exclude_apps: [],
   sensitive_keywords: ['password', 'secret'],
   enable_analytics: false,
+  strip_image_metadata: true,
   hasShownOverlayInfo: false,
 };
 
@@ -69,6 +103,8 @@ const defaultSettings: AppSettings = {
 export const settings = writable<AppSettings>(defaultSettings);
 export const isLoadingSettings = writable<boolean>(false);
 export const settingsError = writable<string | null>(null);
+// Whether this build can recognize text in images (ocr.rs)
+export const ocrAvailable = writable<boolean>(false);
 
 /**
  * Load settings from backend
@@ -251,6 +287,7 @@ function applyBlur(enableBlur: boolean): void {
 export async function initSettingsStore(): Promise<void> {
   // Load initial settings
   await loadSettings();
+  invoke<boolean>('ocr_available').then(available => ocrAvailable.set(available)).catch(() => {});
 
   // Listen for settings changes from backend
   await listen<AppSettings>('settings-changed', (event) => {